@@ -0,0 +1,67 @@
+//! Compares registry decoding against a plain match over the same messages.
+//!
+//! Run with `cargo bench --bench registry`.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use phnx_candefs::testing::GOLDEN;
+use phnx_candefs::*;
+use std::hint::black_box;
+
+/// The match `CanMessage::from_frame` used before the registry.
+fn match_decode(id: u32, data: &[u8]) -> Result<CanMessage, ConvertErr> {
+    match id {
+        EstopAll::ID => EstopAll::decode(data).map(CanMessage::EstopAll),
+        SetBrake::ID => SetBrake::decode(data).map(CanMessage::SetBrake),
+        LockBrake::ID => LockBrake::decode(data).map(CanMessage::LockBrake),
+        UnlockBrake::ID => UnlockBrake::decode(data).map(CanMessage::UnlockBrake),
+        SetAngle::ID => SetAngle::decode(data).map(CanMessage::SetAngle),
+        GetAngle::ID => GetAngle::decode(data).map(CanMessage::GetAngle),
+        SetSpeed::ID => SetSpeed::decode(data).map(CanMessage::SetSpeed),
+        EncoderCount::ID => EncoderCount::decode(data).map(CanMessage::EncoderCount),
+        TrainingMode::ID => TrainingMode::decode(data).map(CanMessage::TrainingMode),
+        MotorTemperature::ID => MotorTemperature::decode(data).map(CanMessage::MotorTemperature),
+        SetAngleCompact::ID => SetAngleCompact::decode(data).map(CanMessage::SetAngleCompact),
+        GetAngleCompact::ID => GetAngleCompact::decode(data).map(CanMessage::GetAngleCompact),
+        CellVoltages::ID => CellVoltages::decode(data).map(CanMessage::CellVoltages),
+        ImuRates::ID => ImuRates::decode(data).map(CanMessage::ImuRates),
+        Heartbeat::ID => Heartbeat::decode(data).map(CanMessage::Heartbeat),
+        AutonDisable::ID => AutonDisable::decode(data).map(CanMessage::AutonDisable),
+        _ => Err(ConvertErr::UnknownId { id }),
+    }
+}
+
+fn bench(c: &mut Criterion) {
+    // Every message, and a few IDs past the last
+    let frames: Vec<(u32, &[u8])> = GOLDEN
+        .iter()
+        .map(|g| (g.id, g.data))
+        .chain((0xE..=0x10).map(|id| (id, &[][..])))
+        .collect();
+
+    let mut group = c.benchmark_group("registry");
+    group.throughput(Throughput::Elements(frames.len() as u64));
+    group.bench_function("registry", |b| {
+        b.iter(|| {
+            frames
+                .iter()
+                .filter(|(id, data)| {
+                    REGISTRY
+                        .decode(black_box(*id), black_box(data))
+                        .is_some_and(|r| r.is_ok())
+                })
+                .count()
+        })
+    });
+    group.bench_function("match", |b| {
+        b.iter(|| {
+            frames
+                .iter()
+                .filter(|(id, data)| match_decode(black_box(*id), black_box(data)).is_ok())
+                .count()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
@@ -0,0 +1,92 @@
+//! Decode and encode throughput, for the per-frame budget of the interface board, which forwards
+//! every frame both ways.
+//!
+//! Run with `cargo bench --bench throughput`. Compare against a saved run with
+//! `--save-baseline before` and `--baseline before`.
+//!
+//! Baseline, on one core of a virtualized Xeon, about 10 ns a frame to decode and 3 to 10 ns to
+//! encode:
+//!
+//! | Bench                              | Time    |
+//! |------------------------------------|---------|
+//! | decode/from_parts, 1000 mixed      | 10.4 us |
+//! | decode/view, 1000 mixed            | 3.2 us  |
+//! | encode/ImuRates                    | 4.9 ns  |
+//! | encode/CellVoltages, the slowest   | 9.4 ns  |
+//! | batch/encode_into, 16 mixed        | 470 ns  |
+//! | batch/encode_into_lossy, 16 mixed  | 557 ns  |
+
+#[path = "../tests/common/mix.rs"]
+mod mix;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use phnx_candefs::testing::{MockFrame, GOLDEN};
+use phnx_candefs::*;
+use std::hint::black_box;
+
+const FRAMES: usize = 1000;
+const BATCH: usize = 16;
+
+fn decode(c: &mut Criterion) {
+    let frames = mix::mixed_frames(FRAMES);
+
+    let mut group = c.benchmark_group("decode");
+    group.throughput(Throughput::Elements(FRAMES as u64));
+    group.bench_function("from_parts", |b| {
+        b.iter(|| {
+            frames
+                .iter()
+                .filter(|(id, data)| {
+                    CanMessage::from_parts(black_box(*id), black_box(data)).is_ok()
+                })
+                .count()
+        })
+    });
+    group.bench_function("view", |b| {
+        b.iter(|| {
+            frames
+                .iter()
+                .filter(|(id, data)| {
+                    CanMessageRef::from_parts(black_box(*id), black_box(data)).is_ok()
+                })
+                .count()
+        })
+    });
+    group.finish();
+}
+
+fn encode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("encode");
+    for info in &MESSAGES {
+        let Some(golden) = GOLDEN.iter().find(|g| g.id == info.id) else {
+            continue;
+        };
+        group.bench_with_input(
+            BenchmarkId::from_parameter(info.name),
+            &golden.msg,
+            |b, msg| b.iter(|| black_box(msg).to_parts()),
+        );
+    }
+    group.finish();
+}
+
+fn batch(c: &mut Criterion) {
+    let msgs: Vec<CanMessage> = mix::mixed_frames(BATCH)
+        .into_iter()
+        .map(|(id, data)| CanMessage::from_parts(id, data).unwrap())
+        .collect();
+    let mut out: [Option<MockFrame>; BATCH] = [None; BATCH];
+
+    let mut group = c.benchmark_group("batch");
+    group.throughput(Throughput::Elements(BATCH as u64));
+    group.bench_function("encode_into", |b| {
+        b.iter(|| encode_into(black_box(&msgs), &mut out))
+    });
+    group.bench_function("encode_into_lossy", |b| {
+        b.iter(|| encode_into_lossy(black_box(&msgs), &mut out))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, decode, encode, batch);
+criterion_main!(benches);
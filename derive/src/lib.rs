@@ -0,0 +1,541 @@
+//! Derive macros for `phnx-candefs`. These are re-exported there, where they are documented.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Literal, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, Data, DeriveInput, Error, Expr, ExprLit, ExprPath, ExprUnary, Field, Fields,
+    Lit, LitStr, Path, UnOp,
+};
+
+/// Derives `IscFrame`, with the layout given by `#[isc(...)]` attributes.
+#[proc_macro_derive(IscFrame, attributes(isc))]
+pub fn derive_isc_frame(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    wire: Option<Path>,
+    scale: Option<f64>,
+    offset: Option<f64>,
+    unit: Option<LitStr>,
+    label: Option<LitStr>,
+    min: Option<Expr>,
+    max: Option<Expr>,
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let krate = quote!(::phnx_candefs);
+
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "IscFrame cannot be derived for generic structs",
+        ));
+    }
+
+    let mut id = None;
+    let mut page = None;
+    let mut legacy = None;
+    let mut display_impl = false;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("isc")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("id") {
+                id = Some(meta.value()?.parse::<Expr>()?);
+                Ok(())
+            } else if meta.path.is_ident("mux") {
+                let mut value: Expr = meta.value()?.parse()?;
+                while let Expr::Group(group) = value {
+                    value = *group.expr;
+                }
+                page = Some(match value {
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Int(i), ..
+                    }) => i.base10_parse::<u8>()?,
+                    other => return Err(Error::new_spanned(other, "expected a page number")),
+                });
+                Ok(())
+            } else if meta.path.is_ident("legacy") {
+                legacy = Some(meta.value()?.parse::<Path>()?);
+                Ok(())
+            } else if meta.path.is_ident("display") {
+                display_impl = true;
+                Ok(())
+            } else {
+                Err(meta.error("expected `id`, `mux`, `legacy`, or `display`"))
+            }
+        })?;
+    }
+    let id = id.ok_or_else(|| Error::new_spanned(name, "missing `#[isc(id = ...)]`"))?;
+
+    let fields: Vec<&Field> = match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(f) => f.named.iter().collect(),
+            Fields::Unit => Vec::new(),
+            Fields::Unnamed(f) => {
+                return Err(Error::new_spanned(f, "IscFrame fields must be named"));
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                name,
+                "IscFrame can only be derived for structs",
+            ))
+        }
+    };
+
+    // Pages of a multiplexed message start with their selector
+    let (mut start, mux, selector_encode, selector_check) = match page {
+        Some(page) => (
+            quote!(1),
+            quote!(#krate::Multiplex::Page(#page)),
+            quote!(buf[0] = #page;),
+            quote! {
+                if data.first() != Some(&#page) {
+                    return Err(#krate::ConvertErr::InvalidFrame);
+                }
+            },
+        ),
+        None => (
+            quote!(0),
+            quote!(#krate::Multiplex::Plain),
+            quote!(),
+            quote!(),
+        ),
+    };
+    let mut encode = Vec::new();
+    let mut decode = Vec::new();
+    let mut signals = Vec::new();
+    let mut accessors = Vec::new();
+    let mut view_accessors = Vec::new();
+    let mut owned = Vec::new();
+    let mut debug = Vec::new();
+    let mut compact_len = Vec::new();
+    let mut compact = Vec::new();
+    let mut display = Vec::new();
+    let mut range_checks = Vec::new();
+
+    for field in fields {
+        let attrs = field_attrs(field)?;
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let wire = attrs
+            .wire
+            .ok_or_else(|| Error::new_spanned(ident, "missing `#[isc(wire = ...)]` encoding"))?;
+        // Names in codec are bare, anything else is a full path
+        let codec = match wire.get_ident() {
+            Some(name) => quote!(#krate::codec::#name),
+            None => quote!(#wire),
+        };
+
+        encode.push(quote!(#codec::encode(&self.#ident, &mut buf, #start);));
+        decode.push(quote!(#ident: #codec::decode_with(data, #start, policy)?,));
+
+        // Views of the legacy encoding read through a full decode, which cannot fail once
+        // the view is made
+        let legacy_read = legacy.as_ref().map(|_| {
+            quote! {
+                if self.data.len() != <#name as #krate::IscFrame>::DLC {
+                    if let Ok(msg) = <#name as #krate::IscFrame>::decode(self.data) {
+                        return msg.#ident;
+                    }
+                }
+            }
+        });
+        let field_doc = format!("Reads `{ident}` from the payload.");
+        view_accessors.push(quote! {
+            #[doc = #field_doc]
+            #[allow(clippy::identity_op)]
+            pub fn #ident(&self) -> #ty {
+                #legacy_read
+                #codec::read(self.data, #start)
+            }
+        });
+        owned.push(quote!(#ident: view.#ident(),));
+        debug.push(quote!(.field(stringify!(#ident), &self.#ident())));
+
+        let label = LitStr::new(&format!(" {ident}="), ident.span());
+        compact_len.push(quote!(+ #label.len() + #codec::COMPACT_LEN));
+        compact.push(quote! {
+            out.write_str(#label)?;
+            #codec::write_compact(&self.#ident, out)?;
+        });
+
+        // Engineering units, to the precision of the scale, and otherwise like compact text
+        let label = attrs
+            .label
+            .as_ref()
+            .map_or_else(|| ident.to_string(), LitStr::value);
+        let label = if display.is_empty() {
+            format!("{label}=")
+        } else {
+            format!(", {label}=")
+        };
+        let value = match attrs.scale.or(attrs.offset.map(|_| 1.0)) {
+            Some(scale) => {
+                let getter = format_ident!("{}_scaled", ident);
+                let decimals = (-scale.log10() - 1e-9).ceil().max(0.0) as usize;
+                quote!(write!(f, "{:.*}", #decimals, self.#getter())?;)
+            }
+            None => quote!(#codec::write_compact(&self.#ident, f)?;),
+        };
+        let unit = attrs.unit.as_ref().map(|u| {
+            let unit = display_unit(&u.value());
+            quote!(f.write_str(#unit)?;)
+        });
+        display.push(quote! {
+            f.write_str(#label)?;
+            #value
+            #unit
+        });
+
+        let scale = Literal::f32_suffixed(attrs.scale.unwrap_or(1.0) as f32);
+        let offset = Literal::f32_suffixed(attrs.offset.unwrap_or(0.0) as f32);
+        let unit = attrs
+            .unit
+            .clone()
+            .unwrap_or_else(|| LitStr::new("", ident.span()));
+        let signal_name = LitStr::new(&ident.to_string(), ident.span());
+
+        // Written as a range so NaN is outside it
+        let range = match (&attrs.min, &attrs.max) {
+            (Some(min), Some(max)) => Some(quote!((#min..=#max))),
+            (Some(min), None) => Some(quote!((#min..))),
+            (None, Some(max)) => Some(quote!((..=#max))),
+            (None, None) => None,
+        };
+        if let Some(range) = range {
+            range_checks.push(quote! {
+                if !#range.contains(&self.#ident) {
+                    return Err(#krate::RangeErr { field: #signal_name });
+                }
+            });
+        }
+
+        signals.push(quote! {
+            #krate::Signal {
+                name: #signal_name,
+                start: #start,
+                size: #codec::SIZE,
+                kind: #codec::KIND,
+                order: #codec::ORDER,
+                scale: #scale,
+                offset: #offset,
+                unit: #unit,
+                mux: #mux,
+            }
+        });
+
+        if attrs.scale.is_some() || attrs.offset.is_some() {
+            let getter = format_ident!("{}_scaled", ident);
+            let setter = format_ident!("set_{}_scaled", ident);
+            let unit_doc = match &attrs.unit {
+                Some(u) => format!(" in {}", u.value()),
+                None => String::new(),
+            };
+            let get_doc = format!("`{ident}` in engineering units{unit_doc}.");
+            let set_doc = format!(
+                "Sets `{ident}` from engineering units{unit_doc}, rounding to the nearest raw \
+                 value and saturating at its limits."
+            );
+
+            accessors.push(quote! {
+                #[doc = #get_doc]
+                #[allow(clippy::unnecessary_cast)]
+                pub fn #getter(&self) -> f32 {
+                    self.#ident as f32 * #scale + #offset
+                }
+
+                #[doc = #set_doc]
+                #[allow(clippy::unnecessary_cast)]
+                pub fn #setter(&mut self, value: f32) {
+                    let raw = (value - #offset) / #scale;
+                    self.#ident = match #codec::KIND {
+                        #krate::SignalKind::Float => raw as #ty,
+                        _ if raw < 0.0 => (raw - 0.5) as #ty,
+                        _ => (raw + 0.5) as #ty,
+                    };
+                }
+            });
+
+            view_accessors.push(quote! {
+                #[doc = #get_doc]
+                #[allow(clippy::unnecessary_cast)]
+                pub fn #getter(&self) -> f32 {
+                    self.#ident() as f32 * #scale + #offset
+                }
+            });
+        }
+
+        start = quote!(#start + #codec::SIZE);
+    }
+
+    // An older layout under the same ID, told apart by its length
+    let (legacy_dlc, legacy_decode, legacy_check) = match &legacy {
+        Some(legacy) => (
+            quote!(const LEGACY_DLC: Option<usize> = Some(<#legacy as #krate::IscFrame>::DLC);),
+            quote! {
+                if data.len() == <#legacy as #krate::IscFrame>::DLC
+                    && data.len() != <Self as #krate::IscFrame>::DLC
+                {
+                    return <#legacy as #krate::IscFrame>::decode_with(data, policy).map(Self::from);
+                }
+            },
+            quote! {
+                const _: () = assert!(
+                    <#legacy as #krate::IscFrame>::ID == <#name as #krate::IscFrame>::ID,
+                    concat!(stringify!(#name), " legacy encoding has another ID")
+                );
+            },
+        ),
+        None => (quote!(), quote!(), quote!()),
+    };
+
+    // Only on request, so a message can still implement Display itself
+    let display_impl = display_impl.then(|| {
+        let (open, close) = if display.is_empty() {
+            (quote!(), quote!())
+        } else {
+            (quote!(f.write_str("(")?;), quote!(f.write_str(")")?;))
+        };
+        quote! {
+            /// Writes the message like `SetAngle(angle=4.82°)`, in engineering units.
+            impl core::fmt::Display for #name {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    f.write_str(stringify!(#name))?;
+                    #open
+                    #(#display)*
+                    #close
+                    Ok(())
+                }
+            }
+        }
+    });
+
+    let vis = &input.vis;
+    let view = format_ident!("{}Ref", name);
+    let view_doc = format!(
+        "Borrowed view of a [`{name}`] payload, validated on construction like its decode."
+    );
+
+    Ok(quote! {
+        impl #krate::IscFrame for #name {
+            const NAME: &'static str = stringify!(#name);
+            const ID: u32 = #id;
+            const DLC: usize = #start;
+            #legacy_dlc
+            const SIGNALS: &'static [#krate::Signal] = &[#(#signals),*];
+
+            fn encode(&self) -> ([u8; 8], usize) {
+                // The inherent const fn below
+                Self::encode(self)
+            }
+
+            fn decode(data: &[u8]) -> Result<Self, #krate::ConvertErr> {
+                Self::decode_with(data, #krate::DecodePolicy::Normalize)
+            }
+
+            #[allow(clippy::identity_op)]
+            fn decode_with(
+                data: &[u8],
+                policy: #krate::DecodePolicy,
+            ) -> Result<Self, #krate::ConvertErr> {
+                let _ = (data, policy);
+                #legacy_decode
+                if data.len() != <Self as #krate::IscFrame>::DLC {
+                    return Err(#krate::ConvertErr::WrongLength {
+                        expected: <Self as #krate::IscFrame>::DLC,
+                        got: data.len(),
+                    });
+                }
+                #selector_check
+                let msg = Self { #(#decode)* };
+                if policy == #krate::DecodePolicy::Normalize {
+                    #krate::IscFrame::check_range(&msg)?;
+                }
+                Ok(msg)
+            }
+
+            fn check_range(&self) -> Result<(), #krate::RangeErr> {
+                #(#range_checks)*
+                Ok(())
+            }
+
+            const COMPACT_LEN: usize = stringify!(#name).len() #(#compact_len)*;
+
+            fn write_compact<W: core::fmt::Write>(&self, out: &mut W) -> core::fmt::Result {
+                out.write_str(stringify!(#name))?;
+                #(#compact)*
+                Ok(())
+            }
+        }
+
+        #display_impl
+
+        impl #name {
+            /// Encodes the payload, returning the buffer and the number of bytes used. This is
+            /// the same as `IscFrame::encode`, but usable in const contexts.
+            #[allow(clippy::identity_op)]
+            pub const fn encode(&self) -> ([u8; 8], usize) {
+                #[allow(unused_mut)]
+                let mut buf = [0; 8];
+                #selector_encode
+                #(#encode)*
+                (buf, <Self as #krate::IscFrame>::DLC)
+            }
+
+            #(#accessors)*
+        }
+
+        #[doc = #view_doc]
+        #[derive(Copy, Clone)]
+        #vis struct #view<'a> {
+            data: &'a [u8],
+        }
+
+        #[allow(dead_code)]
+        impl<'a> #view<'a> {
+            /// Creates a view over a payload without copying it.
+            pub fn new(data: &'a [u8]) -> Result<Self, #krate::ConvertErr> {
+                <#name as #krate::IscFrame>::decode(data)?;
+                Ok(Self { data })
+            }
+
+            /// The payload this views.
+            pub fn as_bytes(&self) -> &'a [u8] {
+                self.data
+            }
+
+            /// Decodes the full message.
+            pub fn into_owned(self) -> #name {
+                self.into()
+            }
+
+            #(#view_accessors)*
+        }
+
+        impl From<#view<'_>> for #name {
+            fn from(view: #view<'_>) -> Self {
+                let _ = view;
+                Self { #(#owned)* }
+            }
+        }
+
+        impl core::fmt::Debug for #view<'_> {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.debug_struct(stringify!(#view))#(#debug)*.finish()
+            }
+        }
+
+        impl #krate::IscView for #name {
+            type Ref<'a> = #view<'a>;
+
+            fn view(data: &[u8]) -> Result<#view<'_>, #krate::ConvertErr> {
+                #view::new(data)
+            }
+        }
+
+        const _: () = assert!(
+            <#name as #krate::IscFrame>::ID <= 0x1FFF_FFFF,
+            concat!(stringify!(#name), " ID does not fit in an extended frame")
+        );
+        const _: () = assert!(
+            <#name as #krate::IscFrame>::DLC <= 8,
+            concat!(stringify!(#name), " payload is over 8 bytes")
+        );
+        #legacy_check
+    })
+}
+
+fn field_attrs(field: &Field) -> syn::Result<FieldAttrs> {
+    let mut out = FieldAttrs::default();
+
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("isc")) {
+        attr.parse_nested_meta(|meta| {
+            let mut value: Expr = meta.value()?.parse()?;
+            // Fragments passed through macro_rules arrive in invisible groups
+            while let Expr::Group(group) = value {
+                value = *group.expr;
+            }
+
+            if meta.path.is_ident("wire") {
+                out.wire = Some(match value {
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(s), ..
+                    }) => s.parse()?,
+                    Expr::Path(ExprPath { path, .. }) => path,
+                    other => return Err(Error::new_spanned(other, "expected an encoding path")),
+                });
+            } else if meta.path.is_ident("scale") {
+                out.scale = Some(number(&value)?);
+            } else if meta.path.is_ident("offset") {
+                out.offset = Some(number(&value)?);
+            } else if meta.path.is_ident("min") {
+                out.min = Some(value);
+            } else if meta.path.is_ident("max") {
+                out.max = Some(value);
+            } else if meta.path.is_ident("unit") {
+                out.unit = Some(string(value)?);
+            } else if meta.path.is_ident("label") {
+                out.label = Some(string(value)?);
+            } else {
+                return Err(meta.error(
+                    "expected `wire`, `scale`, `offset`, `unit`, `label`, `min`, or `max`",
+                ));
+            }
+            Ok(())
+        })?;
+    }
+
+    if out.scale == Some(0.0) {
+        return Err(Error::new_spanned(field, "scale cannot be zero"));
+    }
+
+    Ok(out)
+}
+
+fn string(expr: Expr) -> syn::Result<LitStr> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Str(s), ..
+        }) => Ok(s),
+        other => Err(Error::new_spanned(other, "expected a string")),
+    }
+}
+
+/// Evaluates a possibly negated integer or float literal.
+fn number(expr: &Expr) -> syn::Result<f64> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Float(f), ..
+        }) => f.base10_parse(),
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(i), ..
+        }) => i.base10_parse(),
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_),
+            expr,
+            ..
+        }) => number(expr).map(|n| -n),
+        other => Err(Error::new_spanned(other, "expected a number")),
+    }
+}
+
+/// A unit as written after a value in `Display`, with degrees as a symbol. Symbols follow the
+/// value directly, and anything else after a space.
+fn display_unit(unit: &str) -> String {
+    let unit = match unit.strip_prefix("deg") {
+        Some(rest) => format!("°{rest}"),
+        None => unit.to_string(),
+    };
+    if unit.starts_with(['°', '%']) {
+        unit
+    } else {
+        format!(" {unit}")
+    }
+}
@@ -0,0 +1,459 @@
+//! Calls the public API of `phnx-candefs` with opaque inputs. The panic handler refers to a
+//! symbol that does not exist, so this only links if the optimizer removed every panic.
+//!
+//! Built by `tests/no_panic.rs`. Each area has its own function, so a failure can be found by
+//! building with `--features diagnose`, which swaps the handler for a loop, and looking for
+//! `check_*` functions calling `core::panicking`.
+//!
+//! Formatting is not covered, since `core::fmt` has panics of its own, so neither is
+//! `write_dbc`. `write_compact` avoids it, so is.
+
+#![no_std]
+#![no_main]
+
+use core::hint::black_box;
+use embedded_hal::can::{ExtendedId, Frame, Id, StandardId};
+use phnx_candefs::*;
+
+#[link(name = "c")]
+extern "C" {}
+
+#[cfg(not(feature = "diagnose"))]
+#[panic_handler]
+fn panic(_: &core::panic::PanicInfo) -> ! {
+    extern "C" {
+        fn panic_is_reachable() -> !;
+    }
+    unsafe { panic_is_reachable() }
+}
+
+#[cfg(feature = "diagnose")]
+#[panic_handler]
+fn panic(_: &core::panic::PanicInfo) -> ! {
+    loop {}
+}
+
+/// Opaque inputs, so nothing is folded away.
+struct Input(u32);
+
+impl Input {
+    fn u32(&mut self) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        black_box(self.0)
+    }
+
+    fn u8(&mut self) -> u8 {
+        self.u32() as u8
+    }
+
+    fn u16(&mut self) -> u16 {
+        self.u32() as u16
+    }
+
+    fn i16(&mut self) -> i16 {
+        self.u32() as i16
+    }
+
+    fn bool(&mut self) -> bool {
+        self.u32() & 1 == 1
+    }
+
+    /// Any bit pattern, including NaN and infinities.
+    fn f32(&mut self) -> f32 {
+        f32::from_bits(self.u32())
+    }
+
+    fn usize(&mut self) -> usize {
+        self.u32() as usize
+    }
+
+    fn payload(&mut self) -> ([u8; 8], usize) {
+        let mut data = [0; 8];
+        for b in &mut data {
+            *b = self.u8();
+        }
+        (data, self.usize())
+    }
+
+    fn message(&mut self) -> Option<CanMessage> {
+        let (data, len) = self.payload();
+        CanMessageRef::from_parts(self.u32(), data.get(..len)?)
+            .ok()
+            .map(CanMessageRef::into_owned)
+    }
+}
+
+/// A frame type with unchecked inputs.
+struct RawFrame {
+    id: Id,
+    remote: bool,
+    dlc: usize,
+    data: [u8; 8],
+}
+
+impl Frame for RawFrame {
+    fn new(id: impl Into<Id>, data: &[u8]) -> Option<Self> {
+        let mut buf = [0; 8];
+        buf.get_mut(..data.len())?.copy_from_slice(data);
+        Some(Self {
+            id: id.into(),
+            remote: false,
+            dlc: data.len(),
+            data: buf,
+        })
+    }
+
+    fn new_remote(id: impl Into<Id>, dlc: usize) -> Option<Self> {
+        Some(Self {
+            id: id.into(),
+            remote: true,
+            dlc,
+            data: [0; 8],
+        })
+    }
+
+    fn is_extended(&self) -> bool {
+        matches!(self.id, Id::Extended(_))
+    }
+
+    fn is_remote_frame(&self) -> bool {
+        self.remote
+    }
+
+    fn id(&self) -> Id {
+        self.id
+    }
+
+    fn dlc(&self) -> usize {
+        self.dlc
+    }
+
+    fn data(&self) -> &[u8] {
+        self.data.get(..self.dlc).unwrap_or(&self.data)
+    }
+}
+
+/// Discards text, failing at random.
+struct Sink(u32);
+
+impl core::fmt::Write for Sink {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.0 = self.0.wrapping_add(s.len() as u32);
+        if black_box(self.0) & 0x100 == 0 {
+            Ok(())
+        } else {
+            Err(core::fmt::Error)
+        }
+    }
+}
+
+#[inline(never)]
+fn check_decode(input: &mut Input) {
+    let (data, len) = input.payload();
+    let id = input.u32();
+    let Some(data) = data.get(..len) else {
+        return;
+    };
+
+    black_box(REGISTRY.get(id).map(|e| e.dlc));
+    black_box(REGISTRY.decode(id, data).map(|r| r.is_ok()));
+
+    let mut frame = RawFrame {
+        id: match ExtendedId::new(id) {
+            Some(id) => Id::Extended(id),
+            None => Id::Standard(StandardId::ZERO),
+        },
+        remote: input.bool(),
+        dlc: input.usize(),
+        data: [0; 8],
+    };
+    frame.data.copy_from_slice(&input.payload().0);
+    black_box(CanMessage::from_frame(RawFrame { ..frame }).is_ok());
+    black_box(CanMessage::from_frame_with(RawFrame { ..frame }, &REGISTRY).is_ok());
+
+    if let Ok(view) = CanMessageRef::from_frame(&frame) {
+        black_box(view.as_bytes());
+        black_box(view.into_owned());
+    }
+    if let Ok(view) = CanMessageRef::from_parts(id, data) {
+        black_box(view.into_owned());
+    }
+    if let Ok(msg) = CanMessage::from_parts(id, data) {
+        black_box(msg.to_parts().is_ok());
+    }
+}
+
+#[inline(never)]
+fn check_encode(input: &mut Input) {
+    let Some(msg) = input.message() else {
+        return;
+    };
+    let msgs = [msg, msg, msg];
+
+    let mut out: [Option<RawFrame>; 2] = [None, None];
+    black_box(encode_into(&msgs, &mut out).map_err(|e| e.index())).ok();
+    black_box(encode_into_lossy(&msgs, &mut out));
+
+    let (data, len) = input.payload();
+    let parts = (ExtendedId::MAX, data, len);
+    black_box(frame_from_parts::<RawFrame>(parts).is_ok());
+    black_box(AUTON_DISABLE_WIRE);
+    black_box(FULL_BRAKE_WIRE);
+}
+
+#[inline(never)]
+fn check_messages(input: &mut Input) {
+    let mut temp = MotorTemperature { temp: input.i16() };
+    temp.set_temp_scaled(input.f32());
+    black_box(temp.temp_scaled());
+    black_box(temp.into_frame::<RawFrame>().is_ok());
+
+    let mut angle = SetAngleCompact { angle: input.i16() };
+    angle.set_angle_scaled(input.f32());
+    black_box(SetAngle::from(angle));
+    black_box(SetAngleCompact::from(SetAngle { angle: input.f32() }));
+    black_box(GetAngleCompact::from(GetAngle { angle: input.f32() }));
+    black_box(GetAngle::from(GetAngleCompact { angle: input.i16() }));
+    black_box(quantize_centideg(input.f32()));
+    black_box(dequantize_centideg(input.i16()));
+
+    let mut cells = CellVoltages1 {
+        cell_4: input.u16(),
+        cell_5: input.u16(),
+        cell_6: input.u16(),
+    };
+    cells.set_cell_5_scaled(input.f32());
+    black_box(cells.cell_6_scaled());
+
+    if let Some(msg) = input.message() {
+        black_box(msg.set_angle());
+        black_box(msg.get_angle());
+    }
+}
+
+#[inline(never)]
+fn check_mux(input: &mut Input) {
+    let mut asm = Assembler::<CellVoltages, 2>::new(input.u32());
+    for _ in 0..4 {
+        if let Some(CanMessage::CellVoltages(page)) = input.message() {
+            asm.push(page, input.u32());
+        }
+    }
+
+    let now = input.u32();
+    black_box(asm.snapshot(now));
+    black_box(asm.partial(now));
+    black_box(asm.page(input.u8(), now));
+    black_box(asm.age_ms(input.u8(), now));
+    asm.clear();
+}
+
+#[inline(never)]
+fn check_compact(input: &mut Input) {
+    let mut sink = Sink(input.u32());
+    if let Some(msg) = input.message() {
+        black_box(msg.write_compact(&mut sink).is_ok());
+    }
+    let angle = SetAngle { angle: input.f32() };
+    black_box(angle.write_compact(&mut sink).is_ok());
+    black_box(sink.0);
+}
+
+#[inline(never)]
+fn check_steering(input: &mut Input) {
+    let geom = AckermannGeometry {
+        wheelbase_m: input.f32(),
+        track_m: input.f32(),
+    };
+    let steer = GetAngle { angle: input.f32() };
+    let convention = if input.bool() {
+        SignConvention::Inverted
+    } else {
+        SignConvention::Normal
+    };
+
+    let cal = SteeringCalibration {
+        gain: input.f32(),
+        offset: input.f32(),
+    };
+
+    black_box(wheel_angles(&steer, &geom));
+    black_box(wheel_angles_with(&steer, &geom, &cal));
+    black_box(wheel_angles_with_convention(
+        &steer, &geom, convention, &cal,
+    ));
+    black_box(bicycle_angle(&WheelAngles {
+        left_deg: input.f32(),
+        right_deg: input.f32(),
+    }));
+    black_box(steer.ackermann_angle_with_convention(convention, &cal));
+    black_box(steer.ackermann_angle_with(&cal));
+    black_box(SetAngle::from_ackermann(input.f32(), &cal));
+
+    let mut slew = SteeringSlewLimiter::new(input.f32());
+    slew.snap(&steer);
+    black_box(slew.next(&SetAngle { angle: input.f32() }, input.f32()));
+    black_box(slew.next_with_convention(input.f32(), input.f32(), convention));
+
+    let mut deadband = Deadband::new(input.f32(), input.f32());
+    for _ in 0..4 {
+        black_box(deadband.filter(&GetAngle { angle: input.f32() }));
+    }
+    black_box(deadband.changed());
+    deadband.reset();
+}
+
+#[inline(never)]
+fn check_commands(input: &mut Input) {
+    let mut arbiter =
+        CommandArbiter::new().with_slew(SteeringSlewLimiter::new(input.f32()), input.f32());
+    let mut gate = BrakeGate::new();
+    let mut tracker = AutonStateTracker::<4>::new(input.u32());
+    let mut empty = AutonStateTracker::<0>::new(input.u32());
+
+    gate.set_emergency_override(input.bool());
+    for _ in 0..8 {
+        if let Some(msg) = input.message() {
+            arbiter.push(&msg);
+            gate.observe(&msg);
+            black_box(gate.filter(&msg));
+            tracker.observe(&msg, input.u32());
+            empty.observe(&msg, input.u32());
+        }
+        if input.bool() {
+            tracker.auton_switch(input.u32());
+            empty.power_cycle(input.u32());
+        }
+    }
+
+    black_box(arbiter.resolve());
+    black_box(gate.suppressed());
+    black_box(tracker.time_in_state_ms(input.u32()));
+    black_box(tracker.events().count());
+    black_box(empty.events().count());
+    tracker.power_cycle(input.u32());
+    tracker.clear_events();
+
+    let profile = if input.bool() {
+        StopProfile::Immediate
+    } else {
+        StopProfile::Controlled {
+            rate_pct_per_s: input.u16(),
+            interval_ms: input.u16(),
+        }
+    };
+    black_box(safe_stop_sequence(profile).count());
+}
+
+#[inline(never)]
+fn check_maneuver(input: &mut Input) {
+    let segments = [
+        Segment::Ramp {
+            angle: input.f32(),
+            speed: input.u8(),
+            duration_ms: input.u32(),
+        },
+        Segment::Hold {
+            angle: input.f32(),
+            speed: input.u8(),
+            duration_ms: input.u32(),
+        },
+    ];
+    let maneuver = Maneuver {
+        segments: &segments,
+        limits: ManeuverLimits {
+            max_steer_deg_per_s: input.f32(),
+            max_speed_pct_per_s: input.f32(),
+        },
+    };
+
+    black_box(maneuver.duration_ms());
+    for sample in maneuver.sample(input.u32()).take(16) {
+        black_box(sample);
+    }
+}
+
+#[inline(never)]
+fn check_tables(input: &mut Input) {
+    let x = [input.f32(), input.f32(), input.f32()];
+    let y = [input.f32(), input.f32(), input.f32()];
+    let Ok(table) = LinearTable::new(x, y) else {
+        return;
+    };
+
+    black_box(table.interpolate(input.f32()));
+    black_box(table.inverse(input.f32()));
+
+    let speed = SpeedMap { table };
+    black_box(speed.duty(&SetSpeed {
+        centi_percent: input.u16(),
+    }));
+    black_box(speed.command_for(input.f32()));
+
+    let brake = BrakeMap { table };
+    black_box(brake.output(&SetBrake {
+        centi_percent: input.u16(),
+    }));
+    black_box(brake.command_for(input.f32()));
+
+    let curve = BrakeCurve { table };
+    let enc = EncoderCount {
+        count: input.u16(),
+        velocity: input.f32(),
+    };
+    black_box(stopping_distance(&enc, &curve, input.u32()));
+    black_box(max_safe_speed(input.f32(), &curve, input.u32()));
+}
+
+#[inline(never)]
+fn check_encoder(input: &mut Input) {
+    let mode = if input.bool() {
+        TickMode::Absolute
+    } else {
+        TickMode::Delta
+    };
+    let mut ticks = TickAccumulator::new(mode, input.u32());
+    let mut median = VelocityFilter::<5>::new(FilterMode::Median);
+    let mut none = VelocityFilter::<0>::new(FilterMode::Median);
+    let mut decay = DecayingVelocityFilter::<3>::new(
+        FilterMode::Ema { alpha: input.f32() },
+        input.f32(),
+        input.f32(),
+    );
+
+    for _ in 0..8 {
+        let enc = EncoderCount {
+            count: input.u16(),
+            velocity: input.f32(),
+        };
+        black_box(ticks.update(&enc, input.u32()));
+        black_box(median.update(&enc, input.f32()));
+        black_box(none.update(&enc, input.f32()));
+        black_box(decay.update(&enc, input.f32()));
+        black_box(decay.idle(input.f32()));
+    }
+
+    black_box(ticks.total());
+    ticks.reset();
+    median.reset();
+    decay.reset();
+}
+
+#[no_mangle]
+extern "C" fn main(argc: i32, _argv: *const *const u8) -> i32 {
+    let mut input = Input(black_box(argc as u32) | 1);
+
+    check_decode(&mut input);
+    check_encode(&mut input);
+    check_messages(&mut input);
+    check_mux(&mut input);
+    check_compact(&mut input);
+    check_steering(&mut input);
+    check_commands(&mut input);
+    check_maneuver(&mut input);
+    check_tables(&mut input);
+    check_encoder(&mut input);
+    0
+}
@@ -0,0 +1,155 @@
+//! Encodes and decodes every golden vector through [`MockFrame`], with no std, panicking on the
+//! first that does not behave.
+//!
+//! Built for `thumbv7m-none-eabi` by `tests/no_std.rs`, which has no std to link, so any use of
+//! it in `phnx-candefs` fails the build, and run on the host by `main.rs`.
+
+#![no_std]
+
+use embedded_hal::can::Frame;
+use phnx_candefs::testing::{Golden, MockFrame, GOLDEN};
+use phnx_candefs::*;
+
+fn name(id: u32) -> &'static str {
+    MESSAGES
+        .iter()
+        .find(|m| m.id == id)
+        .map_or("an unnamed message", |m| m.name)
+}
+
+/// Runs every check, returning how many golden vectors were checked.
+pub fn run() -> usize {
+    check_complete();
+    for golden in GOLDEN {
+        check_golden(golden);
+    }
+    check_values();
+    check_rejects();
+    GOLDEN.len()
+}
+
+/// Every message has a golden vector to check.
+fn check_complete() {
+    for id in ALL_IDS {
+        assert!(
+            GOLDEN.iter().any(|g| g.id == id),
+            "{} has no golden vector",
+            name(id)
+        );
+    }
+}
+
+/// Encodes to the golden frame, and decodes from it every way back to the same message.
+fn check_golden(golden: &Golden) {
+    let name = name(golden.id);
+    let frame = MockFrame::extended(golden.id, golden.data);
+
+    let parts = golden.msg.to_parts().unwrap();
+    assert_eq!(
+        parts.0.as_raw(),
+        golden.id,
+        "{name} encodes with another ID"
+    );
+    assert_eq!(&parts.1[..parts.2], golden.data, "{name} encodes wrong");
+    let encoded: MockFrame = frame_from_parts(parts).unwrap();
+    assert_eq!(encoded, frame, "{name} encodes to another frame");
+
+    let decoded = [
+        CanMessage::from_frame(frame),
+        CanMessage::from_parts(golden.id, golden.data),
+        CanMessageRef::from_frame(&frame).map(|view| view.into_owned()),
+        REGISTRY
+            .decode(golden.id, golden.data)
+            .unwrap_or(Err(ConvertErr::UnknownId { id: golden.id })),
+    ];
+    for (i, msg) in decoded.into_iter().enumerate() {
+        let Ok(msg) = msg else {
+            panic!("{name} does not decode, way {i}");
+        };
+        assert_eq!(
+            msg.to_parts().ok(),
+            Some(parts),
+            "{name} decodes wrong, way {i}"
+        );
+    }
+
+    // Only whole payloads with extended IDs decode
+    let standard = MockFrame::standard(golden.id as u16, golden.data);
+    assert!(standard.is_standard());
+    assert!(
+        CanMessage::from_frame(standard).is_err(),
+        "{name} decodes with a standard ID"
+    );
+    // Unless short is a legacy layout
+    let legacy_dlc = MESSAGES
+        .iter()
+        .find(|m| m.id == golden.id)
+        .and_then(|m| m.legacy_dlc);
+    if let Some((_, short)) = golden
+        .data
+        .split_last()
+        .filter(|(_, s)| Some(s.len()) != legacy_dlc)
+    {
+        assert!(
+            CanMessage::from_parts(golden.id, short).is_err(),
+            "{name} decodes short"
+        );
+    }
+}
+
+/// Decoded values, for the encodings most likely to differ on a target.
+fn check_values() {
+    let msg = |id, data: &[u8]| CanMessage::from_parts(id, data).unwrap();
+
+    assert!(matches!(
+        msg(SetBrake::ID, &[0x4C, 0x1D]),
+        CanMessage::SetBrake(SetBrake {
+            centi_percent: 7500
+        })
+    ));
+    assert!(matches!(
+        msg(SetBrake::ID, &[75]),
+        CanMessage::SetBrake(SetBrake {
+            centi_percent: 7500
+        })
+    ));
+    assert!(matches!(
+        msg(SetAngle::ID, &1.5f32.to_le_bytes()),
+        CanMessage::SetAngle(SetAngle { angle }) if angle == 1.5
+    ));
+
+    let CanMessage::MotorTemperature(temp) = msg(MotorTemperature::ID, &[0x83, 0xFF]) else {
+        panic!("MotorTemperature decodes as another message");
+    };
+    assert_eq!(temp.temp, -125);
+    assert_eq!(temp.temp_scaled(), -12.5);
+
+    // Big endian
+    let CanMessage::ImuRates(imu) = msg(ImuRates::ID, &[0xFF, 0x38, 0x0B, 0xB8, 0x12, 0x34]) else {
+        panic!("ImuRates decodes as another message");
+    };
+    assert_eq!(
+        imu,
+        ImuRates {
+            yaw_rate: -200,
+            lateral_accel: 3000,
+            sequence: 0x1234,
+        }
+    );
+
+    // Multiplexed
+    let data = [1, 0x74, 0x0E, 0x80, 0x0E, 0xFF, 0xFF];
+    let CanMessage::CellVoltages(CellVoltages::CellVoltages1(cells)) = msg(CellVoltages::ID, &data)
+    else {
+        panic!("CellVoltages page 1 decodes as another message");
+    };
+    assert_eq!((cells.cell_4, cells.cell_6), (3700, u16::MAX));
+}
+
+/// Frames that must not decode.
+fn check_rejects() {
+    assert!(CanMessage::from_parts(0x1FFF_FFFF, &[]).is_err());
+    assert!(CanMessage::from_parts(SetAngle::ID, &f32::NAN.to_le_bytes()).is_err());
+    assert!(CanMessage::from_parts(GetAngle::ID, &f32::INFINITY.to_le_bytes()).is_err());
+    assert!(CanMessage::from_parts(CellVoltages::ID, &[2, 0, 0, 0, 0, 0, 0]).is_err());
+}
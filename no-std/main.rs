@@ -0,0 +1,38 @@
+//! Runs the checks in `lib.rs` on the host, still without std, printing panics to stderr and
+//! exiting with 1 on the first failure.
+//!
+//! Run by `tests/no_std.rs`.
+
+#![no_std]
+#![no_main]
+
+use core::fmt::{self, Write};
+
+#[link(name = "c")]
+extern "C" {
+    fn write(fd: i32, buf: *const u8, count: usize) -> isize;
+    fn exit(status: i32) -> !;
+}
+
+/// Writes to a file descriptor, dropping what does not fit.
+struct Fd(i32);
+
+impl Write for Fd {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        unsafe { write(self.0, s.as_ptr(), s.len()) };
+        Ok(())
+    }
+}
+
+#[panic_handler]
+fn panic(info: &core::panic::PanicInfo) -> ! {
+    writeln!(Fd(2), "{info}").ok();
+    unsafe { exit(1) }
+}
+
+#[no_mangle]
+extern "C" fn main(_argc: i32, _argv: *const *const u8) -> i32 {
+    let checked = phnx_candefs_no_std::run();
+    writeln!(Fd(1), "checked {checked} golden vectors").ok();
+    0
+}
@@ -0,0 +1,228 @@
+use crate::{GetAngle, SetAngle};
+use libm::{atan2f, atanf, tanf};
+
+/// Bicycle model angles smaller than this, in degrees, are treated as straight ahead.
+pub const STRAIGHT_EPSILON_DEG: f32 = 1e-3;
+
+/// Front axle geometry of the kart.
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+pub struct AckermannGeometry {
+    /// Distance between the front and rear axles, in meters.
+    pub wheelbase_m: f32,
+    /// Distance between the front wheel kingpins, in meters.
+    pub track_m: f32,
+}
+
+/// Linear fit from the steering motor angle to the bicycle model wheel angle, in degrees, as
+/// `wheel = motor * gain + offset`. It depends on the steering linkage, so is measured again
+/// whenever that is rebuilt.
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+pub struct SteeringCalibration {
+    pub gain: f32,
+    pub offset: f32,
+}
+
+impl SteeringCalibration {
+    /// The fit for the current linkage, used by [`GetAngle::ackermann_angle`].
+    pub const DEFAULT: Self = Self {
+        gain: 2.62,
+        offset: -0.832,
+    };
+
+    /// The wheel angle for a steering motor angle.
+    pub fn wheel_angle(&self, motor_angle: f32) -> f32 {
+        motor_angle * self.gain + self.offset
+    }
+
+    /// The steering motor angle for a wheel angle. This is not finite if the gain is zero.
+    pub fn motor_angle(&self, wheel_angle: f32) -> f32 {
+        (wheel_angle - self.offset) / self.gain
+    }
+}
+
+impl GetAngle {
+    /// Converts the steering angle to ackermann wheel angle, with the default calibration.
+    pub fn ackermann_angle(&self) -> f32 {
+        self.ackermann_angle_with(&SteeringCalibration::DEFAULT)
+    }
+
+    /// Converts the steering angle to ackermann wheel angle, with the given calibration.
+    pub fn ackermann_angle_with(&self, cal: &SteeringCalibration) -> f32 {
+        cal.wheel_angle(self.angle)
+    }
+}
+
+impl SetAngle {
+    /// Creates a command for an ackermann wheel angle, the inverse of
+    /// [`GetAngle::ackermann_angle_with`].
+    ///
+    /// The motor angle is not range checked, so [`SetAngle::new`] it to check it.
+    pub fn from_ackermann(wheel_angle: f32, cal: &SteeringCalibration) -> SetAngle {
+        SetAngle {
+            angle: cal.motor_angle(wheel_angle),
+        }
+    }
+}
+
+/// Individual front wheel angles, in degrees. Left is negative, and right is positive.
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+pub struct WheelAngles {
+    pub left_deg: f32,
+    pub right_deg: f32,
+}
+
+/// Computes the front wheel angles for a steering angle, with the default calibration. See
+/// [`wheel_angles_with`].
+pub fn wheel_angles(steer: &GetAngle, geom: &AckermannGeometry) -> WheelAngles {
+    wheel_angles_with(steer, geom, &SteeringCalibration::DEFAULT)
+}
+
+/// Computes the front wheel angles for a steering angle, using the full Ackermann relation
+/// instead of the bicycle model approximation of [`GetAngle::ackermann_angle_with`], with the
+/// given calibration.
+///
+/// The inner wheel turns more sharply than the bicycle angle, and the outer wheel less.
+/// If the bicycle angle is within [`STRAIGHT_EPSILON_DEG`] of zero, or the geometry is not
+/// positive and finite, both wheels are given the bicycle angle.
+pub fn wheel_angles_with(
+    steer: &GetAngle,
+    geom: &AckermannGeometry,
+    cal: &SteeringCalibration,
+) -> WheelAngles {
+    let bicycle = steer.ackermann_angle_with(cal);
+
+    if bicycle.abs() < STRAIGHT_EPSILON_DEG || !geom.is_valid() {
+        return WheelAngles {
+            left_deg: bicycle,
+            right_deg: bicycle,
+        };
+    }
+
+    let l = geom.wheelbase_m;
+    let half_track = geom.track_m / 2.0;
+    let tan = tanf(bicycle.to_radians());
+
+    // A positive angle turns right, about a center at L / tan to the right of the rear axle
+    WheelAngles {
+        left_deg: atan2f(l * tan, l + half_track * tan).to_degrees(),
+        right_deg: atan2f(l * tan, l - half_track * tan).to_degrees(),
+    }
+}
+
+/// Computes the bicycle model angle equivalent to a pair of front wheel angles, in degrees.
+///
+/// This is the inverse of [`wheel_angles`], with the same straight ahead fallback.
+pub fn bicycle_angle(wheels: &WheelAngles) -> f32 {
+    let (left, right) = (wheels.left_deg, wheels.right_deg);
+
+    if left.abs() < STRAIGHT_EPSILON_DEG || right.abs() < STRAIGHT_EPSILON_DEG {
+        return (left + right) / 2.0;
+    }
+
+    // The bicycle cotangent is the mean of the wheel cotangents, independent of geometry
+    let cot = (1.0 / tanf(left.to_radians()) + 1.0 / tanf(right.to_radians())) / 2.0;
+    atanf(1.0 / cot).to_degrees()
+}
+
+impl AckermannGeometry {
+    fn is_valid(&self) -> bool {
+        self.wheelbase_m > 0.0
+            && self.wheelbase_m.is_finite()
+            && self.track_m >= 0.0
+            && self.track_m.is_finite()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::MAX_ANGLE;
+
+    const GEOM: AckermannGeometry = AckermannGeometry {
+        wheelbase_m: 1.05,
+        track_m: 0.9,
+    };
+
+    /// Steering motor angle producing the given bicycle model angle.
+    fn steer_for(bicycle_deg: f32) -> GetAngle {
+        GetAngle {
+            angle: SteeringCalibration::DEFAULT.motor_angle(bicycle_deg),
+        }
+    }
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-3, "{a} != {b}");
+    }
+
+    #[test]
+    fn test_hand_computed() {
+        // (bicycle, left, right), from L / (R -+ t/2) with R = L / tan
+        let cases = [
+            (20.0, 17.476953, 23.327534),
+            (-20.0, -23.327534, -17.476953),
+            (5.0, 4.820167, 5.193734),
+            (35.0, 28.30621, 45.012133),
+        ];
+
+        for (bicycle, left, right) in cases {
+            let wheels = wheel_angles(&steer_for(bicycle), &GEOM);
+            assert_close(wheels.left_deg, left);
+            assert_close(wheels.right_deg, right);
+            assert_close(bicycle_angle(&wheels), bicycle);
+        }
+    }
+
+    #[test]
+    fn test_straight_ahead() {
+        let wheels = wheel_angles(&steer_for(0.0), &GEOM);
+        assert_eq!(wheels.left_deg, wheels.right_deg);
+        assert!(wheels.left_deg.abs() < STRAIGHT_EPSILON_DEG);
+        assert_close(bicycle_angle(&wheels), 0.0);
+
+        // Degenerate geometry falls back to the bicycle model
+        let geom = AckermannGeometry {
+            wheelbase_m: 0.0,
+            track_m: 0.9,
+        };
+        let wheels = wheel_angles(&steer_for(10.0), &geom);
+        assert_eq!(wheels.left_deg, wheels.right_deg);
+    }
+
+    #[test]
+    fn test_calibration() {
+        let rebuilt = SteeringCalibration {
+            gain: -2.4,
+            offset: 1.5,
+        };
+
+        for cal in [SteeringCalibration::DEFAULT, rebuilt] {
+            let (low, high) = (cal.wheel_angle(-MAX_ANGLE), cal.wheel_angle(MAX_ANGLE));
+            for step in 0..=400 {
+                let wheel = low + (high - low) * step as f32 / 400.0;
+                let cmd = SetAngle::from_ackermann(wheel, &cal);
+                assert!(cmd.angle.abs() <= MAX_ANGLE + 1e-4, "{wheel}");
+
+                let back = GetAngle { angle: cmd.angle }.ackermann_angle_with(&cal);
+                assert!((back - wheel).abs() < 1e-4, "{back} != {wheel}");
+            }
+        }
+
+        // The default is the old fit
+        let get = GetAngle { angle: 4.818 };
+        assert_eq!(get.ackermann_angle(), 4.818 * 2.62 + -0.832);
+        assert_ne!(get.ackermann_angle_with(&rebuilt), get.ackermann_angle());
+
+        // Each wheel follows the calibration too, as in test_hand_computed
+        let steer = GetAngle {
+            angle: rebuilt.motor_angle(20.0),
+        };
+        let wheels = wheel_angles_with(&steer, &GEOM, &rebuilt);
+        assert_close(wheels.left_deg, 17.476953);
+        assert_close(wheels.right_deg, 23.327534);
+        assert_ne!(wheel_angles(&steer, &GEOM), wheels);
+        assert_eq!(
+            wheel_angles_with(&steer, &GEOM, &SteeringCalibration::DEFAULT),
+            wheel_angles(&steer, &GEOM)
+        );
+    }
+}
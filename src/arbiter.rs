@@ -0,0 +1,337 @@
+use crate::{CanMessage, SetAngle, SetBrake, SetSpeed, SteeringSlewLimiter};
+
+/// Why some commands in a cycle were not passed through unchanged.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum InhibitReason {
+    /// An emergency stop was received, so no commands are passed on.
+    Estopped,
+    /// Auton was disabled, so no ROS commands are passed on.
+    AutonDisabled,
+    /// The brake is locked, so brake commands were dropped.
+    BrakeLocked,
+    /// A brake command overrode the speed command.
+    Braking,
+}
+
+/// One step of command resolution. See [`PRECEDENCE`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum Stage {
+    /// An EstopAll in the cycle inhibits every other command.
+    Estop,
+    /// An AutonDisable in the cycle inhibits every other command.
+    AutonDisable,
+    /// While locked, brake commands are dropped.
+    BrakeLock,
+    /// A non-zero SetBrake forces speed to zero.
+    Brake,
+    /// SetSpeed passes through if nothing above decided the speed.
+    Speed,
+    /// SetAngle passes through, slew limited if configured.
+    Steering,
+}
+
+/// Order that stages are applied in, highest precedence first.
+///
+/// A stage may decide outputs, which later stages may not override, or end resolution entirely.
+pub const PRECEDENCE: [Stage; 6] = [
+    Stage::Estop,
+    Stage::AutonDisable,
+    Stage::BrakeLock,
+    Stage::Brake,
+    Stage::Speed,
+    Stage::Steering,
+];
+
+/// Actuator intents resolved from a cycle of commands. None means no command should be sent.
+#[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
+pub struct ResolvedCommands {
+    pub speed: Option<SetSpeed>,
+    pub brake: Option<SetBrake>,
+    pub angle: Option<SetAngle>,
+    /// Highest precedence reason any command was inhibited, if one was.
+    pub inhibit_reason: Option<InhibitReason>,
+}
+
+/// Commands received in the current cycle. Later commands of the same kind replace earlier ones.
+#[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
+struct Cycle {
+    estop: bool,
+    auton_disable: bool,
+    brake: Option<SetBrake>,
+    speed: Option<SetSpeed>,
+    angle: Option<SetAngle>,
+}
+
+/// Result of applying a stage.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Flow {
+    Continue,
+    Stop,
+}
+
+/// Resolves conflicting commands received in the same control cycle, applying [`PRECEDENCE`].
+///
+/// Commands are collected with [`CommandArbiter::push`], then [`CommandArbiter::resolve`] is called
+/// once per cycle. Brake lock state persists between cycles.
+#[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
+pub struct CommandArbiter {
+    cycle: Cycle,
+    brake_locked: bool,
+    slew: Option<(SteeringSlewLimiter, f32)>,
+}
+
+impl CommandArbiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Slew limits resolved angles with the given limiter, assuming cycles are `period_s` apart.
+    pub fn with_slew(mut self, limiter: SteeringSlewLimiter, period_s: f32) -> Self {
+        self.slew = Some((limiter, period_s));
+        self
+    }
+
+    /// Adds a command received this cycle. Messages that are not commands are ignored.
+    pub fn push(&mut self, msg: &CanMessage) {
+        match *msg {
+            CanMessage::EstopAll(_) => self.cycle.estop = true,
+            CanMessage::AutonDisable(_) => self.cycle.auton_disable = true,
+            CanMessage::LockBrake(_) => self.brake_locked = true,
+            CanMessage::UnlockBrake(_) => self.brake_locked = false,
+            CanMessage::SetBrake(b) => self.cycle.brake = Some(b),
+            CanMessage::SetSpeed(s) => self.cycle.speed = Some(s),
+            CanMessage::SetAngle(a) => self.cycle.angle = Some(a),
+            CanMessage::SetAngleCompact(a) => self.cycle.angle = Some(a.into()),
+            _ => {}
+        }
+    }
+
+    pub fn is_brake_locked(&self) -> bool {
+        self.brake_locked
+    }
+
+    /// Resolves the commands pushed this cycle, and starts a new cycle.
+    pub fn resolve(&mut self) -> ResolvedCommands {
+        let cycle = core::mem::take(&mut self.cycle);
+        let mut out = ResolvedCommands::default();
+
+        for stage in PRECEDENCE {
+            if self.apply(stage, &cycle, &mut out) == Flow::Stop {
+                break;
+            }
+        }
+
+        out
+    }
+
+    fn apply(&mut self, stage: Stage, cycle: &Cycle, out: &mut ResolvedCommands) -> Flow {
+        match stage {
+            Stage::Estop => {
+                if cycle.estop {
+                    out.inhibit_reason = Some(InhibitReason::Estopped);
+                    return Flow::Stop;
+                }
+            }
+            Stage::AutonDisable => {
+                if cycle.auton_disable {
+                    out.inhibit_reason = Some(InhibitReason::AutonDisabled);
+                    return Flow::Stop;
+                }
+            }
+            Stage::BrakeLock => {
+                if self.brake_locked && cycle.brake.is_some() {
+                    out.inhibit_reason = Some(InhibitReason::BrakeLocked);
+                }
+            }
+            Stage::Brake => {
+                if !self.brake_locked {
+                    out.brake = cycle.brake;
+                }
+
+                if matches!(out.brake, Some(b) if b.centi_percent > 0) {
+                    out.speed = Some(SetSpeed { centi_percent: 0 });
+                    if cycle.speed.is_some_and(|s| s.centi_percent > 0) {
+                        out.inhibit_reason.get_or_insert(InhibitReason::Braking);
+                    }
+                }
+            }
+            Stage::Speed => {
+                if out.speed.is_none() {
+                    out.speed = cycle.speed;
+                }
+            }
+            Stage::Steering => {
+                out.angle = match (cycle.angle, &mut self.slew) {
+                    (Some(target), Some((limiter, period_s))) => {
+                        Some(limiter.next(&target, *period_s))
+                    }
+                    (angle, _) => angle,
+                };
+            }
+        }
+
+        Flow::Continue
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{AutonDisable, EstopAll, EstopSource, LockBrake, SetAngleCompact, UnlockBrake};
+
+    const ESTOP: CanMessage = CanMessage::EstopAll(EstopAll {
+        source: EstopSource::Ros,
+    });
+    const DISABLE: CanMessage = CanMessage::AutonDisable(AutonDisable {});
+    const LOCK: CanMessage = CanMessage::LockBrake(LockBrake {});
+    const UNLOCK: CanMessage = CanMessage::UnlockBrake(UnlockBrake {});
+
+    fn brake(percent: u8) -> CanMessage {
+        CanMessage::SetBrake(SetBrake {
+            centi_percent: percent as u16 * 100,
+        })
+    }
+
+    fn speed(percent: u8) -> CanMessage {
+        CanMessage::SetSpeed(SetSpeed {
+            centi_percent: percent as u16 * 100,
+        })
+    }
+
+    fn angle(angle: f32) -> CanMessage {
+        CanMessage::SetAngle(SetAngle { angle })
+    }
+
+    #[test]
+    fn test_conflicting_combinations() {
+        use InhibitReason::*;
+
+        // (messages in cycle, speed, brake, angle, reason)
+        #[allow(clippy::type_complexity)]
+        let cases: &[(
+            &[CanMessage],
+            Option<u8>,
+            Option<u8>,
+            Option<f32>,
+            Option<InhibitReason>,
+        )] = &[
+            (&[speed(30), angle(5.0)], Some(30), None, Some(5.0), None),
+            (
+                &[speed(30), brake(50)],
+                Some(0),
+                Some(50),
+                None,
+                Some(Braking),
+            ),
+            (&[brake(0), speed(30)], Some(30), Some(0), None, None),
+            (&[brake(20)], Some(0), Some(20), None, None),
+            (&[speed(30), speed(40)], Some(40), None, None, None),
+            (
+                &[speed(30), brake(50), angle(3.0), DISABLE],
+                None,
+                None,
+                None,
+                Some(AutonDisabled),
+            ),
+            (
+                &[DISABLE, speed(30), brake(50), ESTOP],
+                None,
+                None,
+                None,
+                Some(Estopped),
+            ),
+            (
+                &[LOCK, brake(50), speed(30)],
+                Some(30),
+                None,
+                None,
+                Some(BrakeLocked),
+            ),
+            (
+                &[LOCK, UNLOCK, brake(50), speed(30)],
+                Some(0),
+                Some(50),
+                None,
+                Some(Braking),
+            ),
+            (
+                &[brake(50), LOCK, angle(-2.0)],
+                None,
+                None,
+                Some(-2.0),
+                Some(BrakeLocked),
+            ),
+        ];
+
+        for (i, (msgs, speed, brake, angle, reason)) in cases.iter().enumerate() {
+            let mut arbiter = CommandArbiter::new();
+            for m in msgs.iter() {
+                arbiter.push(m);
+            }
+            let out = arbiter.resolve();
+
+            assert_eq!(
+                out.speed.map(|s| (s.centi_percent / 100) as u8),
+                *speed,
+                "case {i}"
+            );
+            assert_eq!(
+                out.brake.map(|b| (b.centi_percent / 100) as u8),
+                *brake,
+                "case {i}"
+            );
+            assert_eq!(out.angle.map(|a| a.angle), *angle, "case {i}");
+            assert_eq!(out.inhibit_reason, *reason, "case {i}");
+        }
+    }
+
+    #[test]
+    fn test_state_between_cycles() {
+        let mut arbiter = CommandArbiter::new();
+
+        arbiter.push(&LOCK);
+        arbiter.push(&speed(10));
+        assert_eq!(
+            arbiter.resolve().speed,
+            Some(SetSpeed {
+                centi_percent: 1000
+            })
+        );
+
+        // Lock persists, but commands do not
+        arbiter.push(&brake(100));
+        let out = arbiter.resolve();
+        assert_eq!(out.brake, None);
+        assert_eq!(out.speed, None);
+        assert!(arbiter.is_brake_locked());
+
+        arbiter.push(&UNLOCK);
+        arbiter.push(&brake(100));
+        assert_eq!(
+            arbiter.resolve().brake,
+            Some(SetBrake {
+                centi_percent: 10_000
+            })
+        );
+    }
+
+    #[test]
+    fn test_steering_slew() {
+        let mut arbiter = CommandArbiter::new().with_slew(SteeringSlewLimiter::new(10.0), 0.5);
+
+        arbiter.push(&angle(20.0));
+        assert_eq!(arbiter.resolve().angle, Some(SetAngle { angle: 5.0 }));
+        arbiter.push(&angle(20.0));
+        assert_eq!(arbiter.resolve().angle, Some(SetAngle { angle: 10.0 }));
+    }
+
+    #[test]
+    fn test_compact_angle() {
+        let mut arbiter = CommandArbiter::new();
+
+        arbiter.push(&CanMessage::SetAngleCompact(SetAngleCompact {
+            angle: -250,
+        }));
+        assert_eq!(arbiter.resolve().angle, Some(SetAngle { angle: -2.5 }));
+    }
+}
@@ -0,0 +1,231 @@
+use crate::CanMessage;
+
+/// Driving mode of the kart, as inferred from bus traffic.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum AutonState {
+    /// No mode messages seen since power on.
+    Unknown,
+    /// ROS is in control.
+    Auton,
+    /// A human is in control.
+    Teleop,
+    /// Training mode is engaged. This latches until power cycle.
+    Training,
+}
+
+/// A state change recorded by an [`AutonStateTracker`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Transition {
+    pub from: AutonState,
+    pub to: AutonState,
+    /// Time of the transition, in ms.
+    pub at_ms: u32,
+}
+
+/// Tracks the auton, teleop, and training state of the kart from decoded messages.
+///
+/// The semantics are:
+/// - [`AutonDisable`](crate::AutonDisable) forces Teleop.
+/// - Auton can only be entered via the physical switch, reported with [`AutonStateTracker::auton_switch`].
+/// - [`TrainingMode`](crate::TrainingMode) latches Training, which nothing but [`AutonStateTracker::power_cycle`] leaves.
+///
+/// The last N transitions are kept in a ring for logging, with the oldest overwritten once full.
+#[derive(Copy, Clone, Debug)]
+pub struct AutonStateTracker<const N: usize = 8> {
+    state: AutonState,
+    entered_ms: u32,
+    events: [Transition; N],
+    /// Index of the oldest event.
+    head: usize,
+    len: usize,
+    overwritten: u32,
+}
+
+impl<const N: usize> AutonStateTracker<N> {
+    /// Creates a tracker in the Unknown state, entered at `now_ms`.
+    pub const fn new(now_ms: u32) -> Self {
+        Self {
+            state: AutonState::Unknown,
+            entered_ms: now_ms,
+            events: [Transition {
+                from: AutonState::Unknown,
+                to: AutonState::Unknown,
+                at_ms: 0,
+            }; N],
+            head: 0,
+            len: 0,
+            overwritten: 0,
+        }
+    }
+
+    /// Updates the state from a received message.
+    pub fn observe(&mut self, msg: &CanMessage, now_ms: u32) {
+        match msg {
+            CanMessage::AutonDisable(_) => self.transition(AutonState::Teleop, now_ms),
+            CanMessage::TrainingMode(_) => self.transition(AutonState::Training, now_ms),
+            // Unreachable if only the system messages are compiled in
+            #[allow(unreachable_patterns)]
+            _ => {}
+        }
+    }
+
+    /// Reports that the physical auton switch was toggled on.
+    pub fn auton_switch(&mut self, now_ms: u32) {
+        self.transition(AutonState::Auton, now_ms);
+    }
+
+    /// Reports that the CAN network was power cycled, which is the only way to leave Training.
+    ///
+    /// The event ring is kept so the transitions leading up to the power cycle can still be logged.
+    pub fn power_cycle(&mut self, now_ms: u32) {
+        self.record(AutonState::Unknown, now_ms);
+    }
+
+    pub fn state(&self) -> AutonState {
+        self.state
+    }
+
+    /// Time spent in the current state, in ms.
+    pub fn time_in_state_ms(&self, now_ms: u32) -> u32 {
+        now_ms.wrapping_sub(self.entered_ms)
+    }
+
+    /// Recorded transitions, oldest first.
+    pub fn events(&self) -> impl Iterator<Item = &Transition> + '_ {
+        let (newer, older) = self.events.split_at(self.head.min(N));
+        older.iter().chain(newer).take(self.len)
+    }
+
+    /// Number of transitions lost because the ring was full.
+    pub fn overwritten(&self) -> u32 {
+        self.overwritten
+    }
+
+    /// Clears the event ring.
+    pub fn clear_events(&mut self) {
+        self.head = 0;
+        self.len = 0;
+        self.overwritten = 0;
+    }
+
+    fn transition(&mut self, to: AutonState, now_ms: u32) {
+        if self.state == AutonState::Training || self.state == to {
+            return;
+        }
+
+        self.record(to, now_ms);
+    }
+
+    fn record(&mut self, to: AutonState, now_ms: u32) {
+        let event = Transition {
+            from: self.state,
+            to,
+            at_ms: now_ms,
+        };
+
+        self.state = to;
+        self.entered_ms = now_ms;
+
+        if N == 0 {
+            self.overwritten = self.overwritten.saturating_add(1);
+        } else if self.len < N {
+            self.events[self.head.wrapping_add(self.len) % N] = event;
+            self.len += 1;
+        } else {
+            if let Some(oldest) = self.events.get_mut(self.head) {
+                *oldest = event;
+            }
+            self.head = self.head.wrapping_add(1) % N;
+            self.overwritten = self.overwritten.saturating_add(1);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "msgs-drive"))]
+mod test {
+    use super::*;
+    use crate::{AutonDisable, SetSpeed, TrainingMode};
+    use AutonState::*;
+
+    const DISABLE: CanMessage = CanMessage::AutonDisable(AutonDisable {});
+    const TRAINING: CanMessage = CanMessage::TrainingMode(TrainingMode {});
+
+    #[test]
+    fn test_transitions() {
+        let mut tracker: AutonStateTracker = AutonStateTracker::new(0);
+        assert_eq!(tracker.state(), Unknown);
+
+        tracker.observe(&CanMessage::SetSpeed(SetSpeed { centi_percent: 500 }), 5);
+        assert_eq!(tracker.state(), Unknown);
+
+        tracker.auton_switch(10);
+        assert_eq!(tracker.state(), Auton);
+        assert_eq!(tracker.time_in_state_ms(25), 15);
+
+        tracker.observe(&DISABLE, 30);
+        assert_eq!(tracker.state(), Teleop);
+
+        // Repeated disables are not transitions
+        tracker.observe(&DISABLE, 40);
+        assert_eq!(tracker.time_in_state_ms(50), 20);
+
+        tracker.auton_switch(60);
+        assert_eq!(tracker.state(), Auton);
+
+        let expected = [
+            Transition {
+                from: Unknown,
+                to: Auton,
+                at_ms: 10,
+            },
+            Transition {
+                from: Auton,
+                to: Teleop,
+                at_ms: 30,
+            },
+            Transition {
+                from: Teleop,
+                to: Auton,
+                at_ms: 60,
+            },
+        ];
+        assert!(tracker.events().eq(expected.iter()));
+    }
+
+    #[test]
+    fn test_training_latches() {
+        let mut tracker: AutonStateTracker = AutonStateTracker::new(0);
+        tracker.auton_switch(1);
+        tracker.observe(&TRAINING, 2);
+        assert_eq!(tracker.state(), Training);
+
+        tracker.observe(&DISABLE, 3);
+        tracker.auton_switch(4);
+        tracker.observe(&TRAINING, 5);
+        assert_eq!(tracker.state(), Training);
+        assert_eq!(tracker.time_in_state_ms(10), 8);
+
+        tracker.power_cycle(20);
+        assert_eq!(tracker.state(), Unknown);
+        tracker.observe(&DISABLE, 21);
+        assert_eq!(tracker.state(), Teleop);
+        assert_eq!(tracker.events().count(), 4);
+    }
+
+    #[test]
+    fn test_event_ring_overflow() {
+        let mut tracker = AutonStateTracker::<2>::new(0);
+
+        tracker.auton_switch(1);
+        tracker.observe(&DISABLE, 2);
+        tracker.auton_switch(3);
+        tracker.observe(&DISABLE, 4);
+
+        assert_eq!(tracker.overwritten(), 2);
+        let times: [u32; 2] = [3, 4];
+        assert!(tracker.events().map(|e| e.at_ms).eq(times));
+
+        tracker.clear_events();
+        assert_eq!(tracker.events().count(), 0);
+    }
+}
@@ -0,0 +1,175 @@
+use crate::{frame_from_parts, CanMessage};
+use embedded_hal::can::Frame;
+
+/// Why [`encode_into`] stopped, with the index of the message it stopped at. Every message
+/// before it was written.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum EncodeBatchError {
+    /// The message could not be made into a frame of the output type.
+    Frame(usize),
+    /// The output slice has no room for the message.
+    OutputFull(usize),
+}
+
+impl EncodeBatchError {
+    /// Index of the message that was not written.
+    pub fn index(&self) -> usize {
+        match *self {
+            EncodeBatchError::Frame(i) | EncodeBatchError::OutputFull(i) => i,
+        }
+    }
+}
+
+fn encode_one<T: Frame>(msg: &CanMessage) -> Option<T> {
+    frame_from_parts(msg.to_parts().ok()?).ok()
+}
+
+/// Encodes messages into frames in order, returning how many were written to the start of
+/// `out`. Stops at the first message that fails.
+pub fn encode_into<T: Frame>(
+    msgs: &[CanMessage],
+    out: &mut [Option<T>],
+) -> Result<usize, EncodeBatchError> {
+    for (i, msg) in msgs.iter().enumerate() {
+        let slot = out.get_mut(i).ok_or(EncodeBatchError::OutputFull(i))?;
+        *slot = Some(encode_one(msg).ok_or(EncodeBatchError::Frame(i))?);
+    }
+    Ok(msgs.len())
+}
+
+/// Encodes messages into frames in order, skipping any that fail, for paths where losing a
+/// frame is fine.
+///
+/// Returns how many frames were written to the start of `out`, and a mask with bit `i` set if
+/// message `i` failed or did not fit. Only the first 32 messages are encoded.
+pub fn encode_into_lossy<T: Frame>(msgs: &[CanMessage], out: &mut [Option<T>]) -> (usize, u32) {
+    let mut written = 0;
+    let mut failed = 0;
+
+    for (i, msg) in msgs.iter().take(32).enumerate() {
+        match (out.get_mut(written), encode_one(msg)) {
+            (Some(slot), Some(frame)) => {
+                *slot = Some(frame);
+                written += 1;
+            }
+            _ => failed |= 1u32.checked_shl(i as u32).unwrap_or(0),
+        }
+    }
+    (written, failed)
+}
+
+#[cfg(all(test, feature = "msgs-all"))]
+mod test {
+    use super::*;
+    use crate::testing::MockFrame;
+    use crate::*;
+    use embedded_hal::can::{ExtendedId, Id};
+    extern crate std;
+
+    /// A frame that only fits four bytes, so longer messages fail.
+    #[derive(Debug)]
+    struct ShortFrame {
+        id: u32,
+        len: usize,
+    }
+
+    impl Frame for ShortFrame {
+        fn new(id: impl Into<Id>, data: &[u8]) -> Option<Self> {
+            let Id::Extended(id) = id.into() else {
+                return None;
+            };
+            (data.len() <= 4).then_some(Self {
+                id: id.as_raw(),
+                len: data.len(),
+            })
+        }
+
+        fn new_remote(_: impl Into<Id>, _: usize) -> Option<Self> {
+            None
+        }
+
+        fn is_extended(&self) -> bool {
+            true
+        }
+
+        fn is_remote_frame(&self) -> bool {
+            false
+        }
+
+        fn id(&self) -> Id {
+            Id::Extended(ExtendedId::new(self.id).unwrap())
+        }
+
+        fn dlc(&self) -> usize {
+            self.len
+        }
+
+        fn data(&self) -> &[u8] {
+            &[]
+        }
+    }
+
+    const MSGS: [CanMessage; 4] = [
+        CanMessage::SetBrake(SetBrake {
+            centi_percent: 4000,
+        }),
+        CanMessage::SetAngle(SetAngle { angle: 2.0 }),
+        CanMessage::EncoderCount(EncoderCount {
+            count: 3,
+            velocity: 1.0,
+        }),
+        CanMessage::SetSpeed(SetSpeed {
+            centi_percent: 2000,
+        }),
+    ];
+
+    fn ids(out: &[Option<ShortFrame>]) -> [Option<u32>; 4] {
+        core::array::from_fn(|i| out.get(i)?.as_ref().map(|f| f.id))
+    }
+
+    #[test]
+    fn test_success() {
+        let mut out: [Option<MockFrame>; 4] = Default::default();
+        assert_eq!(encode_into(&MSGS, &mut out), Ok(4));
+
+        for (msg, frame) in MSGS.iter().zip(&out) {
+            let frame = frame.unwrap();
+            assert_eq!(
+                std::format!("{:?}", CanMessage::from_frame(frame).unwrap()),
+                std::format!("{msg:?}")
+            );
+        }
+        assert_eq!(encode_into::<MockFrame>(&[], &mut []), Ok(0));
+    }
+
+    #[test]
+    fn test_failure_index() {
+        let mut out: [Option<ShortFrame>; 4] = Default::default();
+        let err = encode_into(&MSGS, &mut out).unwrap_err();
+
+        assert_eq!(err, EncodeBatchError::Frame(2));
+        assert_eq!(err.index(), 2);
+        assert_eq!(ids(&out), [Some(0x1), Some(0x4), None, None]);
+    }
+
+    #[test]
+    fn test_output_full() {
+        let mut out: [Option<MockFrame>; 3] = Default::default();
+        assert_eq!(
+            encode_into(&MSGS, &mut out),
+            Err(EncodeBatchError::OutputFull(3))
+        );
+        assert!(out.iter().all(Option::is_some));
+    }
+
+    #[test]
+    fn test_lossy() {
+        let mut out: [Option<ShortFrame>; 4] = Default::default();
+        assert_eq!(encode_into_lossy(&MSGS, &mut out), (3, 0b0100));
+        assert_eq!(ids(&out), [Some(0x1), Some(0x4), Some(0x6), None]);
+
+        // Messages that do not fit are failures too
+        let mut out: [Option<ShortFrame>; 2] = Default::default();
+        assert_eq!(encode_into_lossy(&MSGS, &mut out), (2, 0b1100));
+    }
+}
@@ -0,0 +1,179 @@
+use crate::CanMessage;
+
+/// What a [`BrakeGate`] decided to do with a message.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum GateDecision {
+    /// Send the message to the bus.
+    Forward,
+    /// Do not send the message.
+    Drop,
+    /// Do not send the message, and count it as a suppressed brake command.
+    DropAndCount,
+}
+
+/// Enforces the [`LockBrake`](crate::LockBrake) and [`UnlockBrake`](crate::UnlockBrake) semantics
+/// on the interface board.
+///
+/// While locked, [`SetBrake`](crate::SetBrake) messages are not forwarded to the bus. Every message
+/// should be passed to [`BrakeGate::observe`] as it is received, and [`BrakeGate::filter`] asked
+/// before forwarding it.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct BrakeGate {
+    locked: bool,
+    emergency_override: bool,
+    suppressed: u32,
+}
+
+impl BrakeGate {
+    /// Creates an unlocked gate.
+    pub const fn new() -> Self {
+        Self {
+            locked: false,
+            emergency_override: false,
+            suppressed: 0,
+        }
+    }
+
+    /// Updates the gate state from a received message.
+    ///
+    /// Locking an already locked gate, or unlocking an unlocked gate, does nothing.
+    pub fn observe(&mut self, msg: &CanMessage) {
+        match msg {
+            CanMessage::LockBrake(_) => self.locked = true,
+            CanMessage::UnlockBrake(_) => {
+                if self.locked {
+                    self.locked = false;
+                    self.suppressed = 0;
+                }
+            }
+            _ => {
+                if self.filter(msg) == GateDecision::DropAndCount {
+                    self.suppressed = self.suppressed.saturating_add(1);
+                }
+            }
+        }
+    }
+
+    /// Decides if a message should be forwarded to the bus.
+    pub fn filter(&self, msg: &CanMessage) -> GateDecision {
+        if is_emergency(msg) {
+            return GateDecision::Forward;
+        }
+
+        match msg {
+            CanMessage::SetBrake(_) if self.locked && !self.emergency_override => {
+                GateDecision::DropAndCount
+            }
+            _ => GateDecision::Forward,
+        }
+    }
+
+    /// While set, brake commands are forwarded even if the gate is locked.
+    ///
+    /// This is for use by emergency stop logic that must be able to brake regardless of lock state.
+    pub fn set_emergency_override(&mut self, active: bool) {
+        self.emergency_override = active;
+    }
+
+    pub fn emergency_override(&self) -> bool {
+        self.emergency_override
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    /// Number of brake commands suppressed since the gate was last unlocked.
+    pub fn suppressed(&self) -> u32 {
+        self.suppressed
+    }
+}
+
+/// Messages that must always pass the gate, locked or not: an [`EstopAll`](crate::EstopAll).
+fn is_emergency(msg: &CanMessage) -> bool {
+    matches!(msg, CanMessage::EstopAll(_))
+}
+
+#[cfg(all(test, feature = "msgs-drive"))]
+mod test {
+    use super::*;
+    use crate::{EstopAll, EstopSource, LockBrake, SetBrake, SetSpeed, UnlockBrake};
+
+    const LOCK: CanMessage = CanMessage::LockBrake(LockBrake {});
+    const UNLOCK: CanMessage = CanMessage::UnlockBrake(UnlockBrake {});
+    const BRAKE: CanMessage = CanMessage::SetBrake(SetBrake {
+        centi_percent: 5000,
+    });
+
+    /// Runs a message through the gate the same way the interface board would.
+    fn feed(gate: &mut BrakeGate, msg: &CanMessage) -> GateDecision {
+        let decision = gate.filter(msg);
+        gate.observe(msg);
+        decision
+    }
+
+    #[test]
+    fn test_lock_command_unlock() {
+        let mut gate = BrakeGate::new();
+
+        assert_eq!(feed(&mut gate, &BRAKE), GateDecision::Forward);
+        assert_eq!(feed(&mut gate, &LOCK), GateDecision::Forward);
+        assert!(gate.is_locked());
+
+        assert_eq!(feed(&mut gate, &BRAKE), GateDecision::DropAndCount);
+        assert_eq!(feed(&mut gate, &BRAKE), GateDecision::DropAndCount);
+        assert_eq!(gate.suppressed(), 2);
+
+        // Only brake commands are gated
+        let speed = CanMessage::SetSpeed(SetSpeed {
+            centi_percent: 1000,
+        });
+        assert_eq!(feed(&mut gate, &speed), GateDecision::Forward);
+        assert_eq!(gate.suppressed(), 2);
+
+        assert_eq!(feed(&mut gate, &UNLOCK), GateDecision::Forward);
+        assert!(!gate.is_locked());
+        assert_eq!(gate.suppressed(), 0);
+        assert_eq!(feed(&mut gate, &BRAKE), GateDecision::Forward);
+    }
+
+    #[test]
+    fn test_emergency_override() {
+        let mut gate = BrakeGate::new();
+        feed(&mut gate, &LOCK);
+
+        gate.set_emergency_override(true);
+        assert_eq!(feed(&mut gate, &BRAKE), GateDecision::Forward);
+        assert_eq!(gate.suppressed(), 0);
+        assert!(gate.is_locked());
+
+        gate.set_emergency_override(false);
+        assert_eq!(feed(&mut gate, &BRAKE), GateDecision::DropAndCount);
+
+        // An emergency stop always passes, and is not counted
+        let estop = CanMessage::EstopAll(EstopAll {
+            source: EstopSource::RemoteKillSwitch,
+        });
+        assert!(is_emergency(&estop));
+        assert!(!is_emergency(&BRAKE));
+        assert_eq!(feed(&mut gate, &estop), GateDecision::Forward);
+        assert_eq!(gate.suppressed(), 1);
+        assert!(gate.is_locked());
+    }
+
+    #[test]
+    fn test_idempotent_lock_unlock() {
+        let mut gate = BrakeGate::new();
+
+        feed(&mut gate, &LOCK);
+        feed(&mut gate, &BRAKE);
+        feed(&mut gate, &LOCK);
+        assert!(gate.is_locked());
+        assert_eq!(gate.suppressed(), 1);
+
+        feed(&mut gate, &UNLOCK);
+        feed(&mut gate, &UNLOCK);
+        assert!(!gate.is_locked());
+        assert_eq!(gate.suppressed(), 0);
+    }
+}
@@ -0,0 +1,476 @@
+//! Field encodings used by message payloads.
+//!
+//! Each encoding is a module with the encoded `SIZE` in bytes, and how it is described in a
+//! [`Signal`](crate::Signal). Its `encode` and `decode` write or read one field at a byte offset
+//! in the payload, while `read` reads a field of a payload that was already decoded once, for
+//! views. Encoding is `const`, so payloads can be built at compile time. `decode_with` decodes
+//! under a [`DecodePolicy`], which only changes how floats are read, and `decode` and `read`
+//! use [`DecodePolicy::Normalize`].
+//!
+//! Multi-byte integers come in either byte order, with the `read_*` and `write_*` helpers here
+//! shared by their encodings and by hand-written ones.
+//!
+//! Each encoding also writes its value as text with `write_compact`, in at most `COMPACT_LEN`
+//! bytes, for [`IscFrame::write_compact`](crate::IscFrame::write_compact). This only uses
+//! `write_str`, not the formatting machinery of `core::fmt`.
+
+use crate::{ByteOrder, ConvertErr, DecodePolicy, SignalKind};
+use core::fmt::{self, Write};
+
+/// Gets the `SIZE` bytes at `at`, erroring if the payload is too short.
+fn field<const SIZE: usize>(data: &[u8], at: usize) -> Result<[u8; SIZE], ConvertErr> {
+    data.get(at..)
+        .and_then(|d| d.get(..SIZE))
+        .and_then(|b| b.try_into().ok())
+        .ok_or(ConvertErr::InvalidFrame)
+}
+
+/// Copies `bytes` into `buf` at `at`, dropping any past the end.
+const fn put<const N: usize>(buf: &mut [u8; 8], at: usize, bytes: [u8; N]) {
+    let mut i = 0;
+    while i < N {
+        let to = at.saturating_add(i);
+        if to < buf.len() {
+            buf[to] = bytes[i];
+        }
+        i += 1;
+    }
+}
+
+const fn encode_u8(v: u8) -> [u8; 1] {
+    [v]
+}
+
+/// Decodes a byte from the start of `data`, erroring if it is empty.
+fn decode_u8(data: &[u8]) -> Result<u8, ConvertErr> {
+    field(data, 0).map(|[b]: [u8; 1]| b)
+}
+
+const fn encode_f32_le(v: f32) -> [u8; 4] {
+    v.to_le_bytes()
+}
+
+/// Decodes an f32 from the start of `data`, erroring if it is too short, or the policy rejects
+/// it.
+fn decode_f32_le(data: &[u8], policy: DecodePolicy) -> Result<f32, ConvertErr> {
+    let v = f32::from_le_bytes(field(data, 0)?);
+    policy.float(v).ok_or(ConvertErr::InvalidFrame)
+}
+
+/// Reads a little endian u16 at `at`, erroring if the payload is too short.
+pub fn read_u16_le(data: &[u8], at: usize) -> Result<u16, ConvertErr> {
+    field(data, at).map(u16::from_le_bytes)
+}
+
+/// Reads a big endian u16 at `at`, erroring if the payload is too short.
+pub fn read_u16_be(data: &[u8], at: usize) -> Result<u16, ConvertErr> {
+    field(data, at).map(u16::from_be_bytes)
+}
+
+/// Reads a little endian i16 at `at`, erroring if the payload is too short.
+pub fn read_i16_le(data: &[u8], at: usize) -> Result<i16, ConvertErr> {
+    field(data, at).map(i16::from_le_bytes)
+}
+
+/// Reads a big endian i16 at `at`, erroring if the payload is too short.
+pub fn read_i16_be(data: &[u8], at: usize) -> Result<i16, ConvertErr> {
+    field(data, at).map(i16::from_be_bytes)
+}
+
+/// Writes a little endian u16 at `at`.
+pub const fn write_u16_le(v: u16, buf: &mut [u8; 8], at: usize) {
+    put(buf, at, v.to_le_bytes());
+}
+
+/// Writes a big endian u16 at `at`.
+pub const fn write_u16_be(v: u16, buf: &mut [u8; 8], at: usize) {
+    put(buf, at, v.to_be_bytes());
+}
+
+/// Writes a little endian i16 at `at`.
+pub const fn write_i16_le(v: i16, buf: &mut [u8; 8], at: usize) {
+    put(buf, at, v.to_le_bytes());
+}
+
+/// Writes a big endian i16 at `at`.
+pub const fn write_i16_be(v: i16, buf: &mut [u8; 8], at: usize) {
+    put(buf, at, v.to_be_bytes());
+}
+
+/// Writes an integer in decimal.
+pub fn fmt_uint<W: Write>(out: &mut W, mut v: u64) -> fmt::Result {
+    // Filled from the end, u64::MAX is 20 digits
+    let mut buf = [0; 20];
+    let mut len = 0;
+    for digit in buf.iter_mut().rev() {
+        *digit = b'0' + (v % 10) as u8;
+        v /= 10;
+        len += 1;
+        if v == 0 {
+            break;
+        }
+    }
+
+    let digits = buf.get(buf.len().saturating_sub(len)..).unwrap_or(&[]);
+    out.write_str(core::str::from_utf8(digits).map_err(|_| fmt::Error)?)
+}
+
+/// Writes a signed integer in decimal.
+pub fn fmt_int<W: Write>(out: &mut W, v: i64) -> fmt::Result {
+    if v < 0 {
+        out.write_str("-")?;
+    }
+    fmt_uint(out, v.unsigned_abs())
+}
+
+/// Writes a float with two decimals, rounding to the nearest, as `nan`, `inf`, or `-inf` if
+/// not finite. Values past ±9.2e16 saturate.
+pub fn fmt_fixed2<W: Write>(out: &mut W, v: f32) -> fmt::Result {
+    if v.is_nan() {
+        return out.write_str("nan");
+    }
+    if v.is_infinite() {
+        return out.write_str(if v < 0.0 { "-inf" } else { "inf" });
+    }
+
+    let scaled = v as f64 * 100.0;
+    let hundredths = if scaled < 0.0 {
+        (scaled - 0.5) as i64
+    } else {
+        (scaled + 0.5) as i64
+    };
+    if hundredths < 0 {
+        out.write_str("-")?;
+    }
+
+    let abs = hundredths.unsigned_abs();
+    fmt_uint(out, abs / 100)?;
+    let frac = (abs % 100) as u8;
+    let frac = [b'.', b'0' + frac / 10, b'0' + frac % 10];
+    out.write_str(core::str::from_utf8(&frac).map_err(|_| fmt::Error)?)
+}
+
+/// Payload from `at`, erroring if it starts past the end.
+fn from(data: &[u8], at: usize) -> Result<&[u8], ConvertErr> {
+    data.get(at..).ok_or(ConvertErr::InvalidFrame)
+}
+
+/// A single unsigned byte.
+pub mod byte {
+    use super::*;
+
+    pub const SIZE: usize = 1;
+    pub const KIND: SignalKind = SignalKind::Unsigned;
+    pub const ORDER: ByteOrder = ByteOrder::LittleEndian;
+
+    pub const fn encode(v: &u8, buf: &mut [u8; 8], at: usize) {
+        put(buf, at, encode_u8(*v));
+    }
+
+    pub fn decode(data: &[u8], at: usize) -> Result<u8, ConvertErr> {
+        decode_u8(from(data, at)?)
+    }
+
+    /// The same as `decode`, since the policy only changes floats.
+    pub fn decode_with(data: &[u8], at: usize, _: DecodePolicy) -> Result<u8, ConvertErr> {
+        decode(data, at)
+    }
+
+    /// Reads a field already validated by `decode`.
+    pub fn read(data: &[u8], at: usize) -> u8 {
+        field(data, at).map(|[b]: [u8; 1]| b).unwrap_or_default()
+    }
+
+    pub const COMPACT_LEN: usize = 3;
+
+    pub fn write_compact<W: Write>(v: &u8, out: &mut W) -> fmt::Result {
+        fmt_uint(out, *v as u64)
+    }
+}
+
+/// Little endian u16.
+pub mod u16_le {
+    use super::*;
+
+    pub const SIZE: usize = 2;
+    pub const KIND: SignalKind = SignalKind::Unsigned;
+    pub const ORDER: ByteOrder = ByteOrder::LittleEndian;
+
+    pub const fn encode(v: &u16, buf: &mut [u8; 8], at: usize) {
+        write_u16_le(*v, buf, at);
+    }
+
+    pub fn decode(data: &[u8], at: usize) -> Result<u16, ConvertErr> {
+        read_u16_le(data, at)
+    }
+
+    /// The same as `decode`, since the policy only changes floats.
+    pub fn decode_with(data: &[u8], at: usize, _: DecodePolicy) -> Result<u16, ConvertErr> {
+        decode(data, at)
+    }
+
+    /// Reads a field already validated by `decode`.
+    pub fn read(data: &[u8], at: usize) -> u16 {
+        read_u16_le(data, at).unwrap_or_default()
+    }
+
+    pub const COMPACT_LEN: usize = 5;
+
+    pub fn write_compact<W: Write>(v: &u16, out: &mut W) -> fmt::Result {
+        fmt_uint(out, *v as u64)
+    }
+}
+
+/// Big endian u16.
+pub mod u16_be {
+    use super::*;
+
+    pub const SIZE: usize = 2;
+    pub const KIND: SignalKind = SignalKind::Unsigned;
+    pub const ORDER: ByteOrder = ByteOrder::BigEndian;
+
+    pub const fn encode(v: &u16, buf: &mut [u8; 8], at: usize) {
+        write_u16_be(*v, buf, at);
+    }
+
+    pub fn decode(data: &[u8], at: usize) -> Result<u16, ConvertErr> {
+        read_u16_be(data, at)
+    }
+
+    /// The same as `decode`, since the policy only changes floats.
+    pub fn decode_with(data: &[u8], at: usize, _: DecodePolicy) -> Result<u16, ConvertErr> {
+        decode(data, at)
+    }
+
+    /// Reads a field already validated by `decode`.
+    pub fn read(data: &[u8], at: usize) -> u16 {
+        read_u16_be(data, at).unwrap_or_default()
+    }
+
+    pub const COMPACT_LEN: usize = 5;
+
+    pub fn write_compact<W: Write>(v: &u16, out: &mut W) -> fmt::Result {
+        fmt_uint(out, *v as u64)
+    }
+}
+
+/// Little endian i16.
+pub mod i16_le {
+    use super::*;
+
+    pub const SIZE: usize = 2;
+    pub const KIND: SignalKind = SignalKind::Signed;
+    pub const ORDER: ByteOrder = ByteOrder::LittleEndian;
+
+    pub const fn encode(v: &i16, buf: &mut [u8; 8], at: usize) {
+        write_i16_le(*v, buf, at);
+    }
+
+    pub fn decode(data: &[u8], at: usize) -> Result<i16, ConvertErr> {
+        read_i16_le(data, at)
+    }
+
+    /// The same as `decode`, since the policy only changes floats.
+    pub fn decode_with(data: &[u8], at: usize, _: DecodePolicy) -> Result<i16, ConvertErr> {
+        decode(data, at)
+    }
+
+    /// Reads a field already validated by `decode`.
+    pub fn read(data: &[u8], at: usize) -> i16 {
+        read_i16_le(data, at).unwrap_or_default()
+    }
+
+    pub const COMPACT_LEN: usize = 6;
+
+    pub fn write_compact<W: Write>(v: &i16, out: &mut W) -> fmt::Result {
+        fmt_int(out, *v as i64)
+    }
+}
+
+/// Big endian i16.
+pub mod i16_be {
+    use super::*;
+
+    pub const SIZE: usize = 2;
+    pub const KIND: SignalKind = SignalKind::Signed;
+    pub const ORDER: ByteOrder = ByteOrder::BigEndian;
+
+    pub const fn encode(v: &i16, buf: &mut [u8; 8], at: usize) {
+        write_i16_be(*v, buf, at);
+    }
+
+    pub fn decode(data: &[u8], at: usize) -> Result<i16, ConvertErr> {
+        read_i16_be(data, at)
+    }
+
+    /// The same as `decode`, since the policy only changes floats.
+    pub fn decode_with(data: &[u8], at: usize, _: DecodePolicy) -> Result<i16, ConvertErr> {
+        decode(data, at)
+    }
+
+    /// Reads a field already validated by `decode`.
+    pub fn read(data: &[u8], at: usize) -> i16 {
+        read_i16_be(data, at).unwrap_or_default()
+    }
+
+    pub const COMPACT_LEN: usize = 6;
+
+    pub fn write_compact<W: Write>(v: &i16, out: &mut W) -> fmt::Result {
+        fmt_int(out, *v as i64)
+    }
+}
+
+/// Little endian IEEE 754 f32. NaN and infinite values are rejected on decode.
+pub mod f32_le {
+    use super::*;
+
+    pub const SIZE: usize = 4;
+    pub const KIND: SignalKind = SignalKind::Float;
+    pub const ORDER: ByteOrder = ByteOrder::LittleEndian;
+
+    pub const fn encode(v: &f32, buf: &mut [u8; 8], at: usize) {
+        put(buf, at, encode_f32_le(*v));
+    }
+
+    pub fn decode(data: &[u8], at: usize) -> Result<f32, ConvertErr> {
+        decode_with(data, at, DecodePolicy::Normalize)
+    }
+
+    pub fn decode_with(data: &[u8], at: usize, policy: DecodePolicy) -> Result<f32, ConvertErr> {
+        decode_f32_le(from(data, at)?, policy)
+    }
+
+    /// Reads a field already validated by `decode`.
+    pub fn read(data: &[u8], at: usize) -> f32 {
+        decode(data, at).unwrap_or_default()
+    }
+
+    pub const COMPACT_LEN: usize = "-92233720368547758.08".len();
+
+    pub fn write_compact<W: Write>(v: &f32, out: &mut W) -> fmt::Result {
+        fmt_fixed2(out, *v)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_offsets() {
+        let mut buf = [0; 8];
+        u16_le::encode(&0x1234, &mut buf, 1);
+        f32_le::encode(&1.5, &mut buf, 3);
+        byte::encode(&7, &mut buf, 7);
+        assert_eq!(buf, [0x00, 0x34, 0x12, 0x00, 0x00, 0xC0, 0x3F, 0x07]);
+
+        assert_eq!(u16_le::decode(&buf, 1).unwrap(), 0x1234);
+        assert_eq!(f32_le::decode(&buf, 3).unwrap(), 1.5);
+        assert_eq!(byte::decode(&buf, 7).unwrap(), 7);
+
+        i16_le::encode(&-2, &mut buf, 0);
+        assert_eq!(buf[..2], [0xFE, 0xFF]);
+        assert_eq!(i16_le::decode(&buf, 0).unwrap(), -2);
+    }
+
+    #[test]
+    fn test_byte_order() {
+        let mut buf = [0; 8];
+        u16_le::encode(&0xA5C3, &mut buf, 0);
+        u16_be::encode(&0xA5C3, &mut buf, 2);
+        i16_le::encode(&-32767, &mut buf, 4);
+        i16_be::encode(&-32767, &mut buf, 6);
+        assert_eq!(buf, [0xC3, 0xA5, 0xA5, 0xC3, 0x01, 0x80, 0x80, 0x01]);
+
+        assert_eq!(read_u16_le(&buf, 0).unwrap(), 0xA5C3);
+        assert_eq!(read_u16_be(&buf, 2).unwrap(), 0xA5C3);
+        assert_eq!(read_i16_le(&buf, 4).unwrap(), -32767);
+        assert_eq!(read_i16_be(&buf, 6).unwrap(), -32767);
+
+        // The most significant bit of a big endian field is the top bit of its first byte
+        let mut buf = [0; 8];
+        write_u16_be(0x8000, &mut buf, 3);
+        assert_eq!(u64::from_le_bytes(buf), 1 << (3 * 8 + 7));
+        write_i16_be(-2, &mut buf, 3);
+        assert_eq!(buf[3..5], [0xFF, 0xFE]);
+        assert_eq!(i16_be::read(&buf, 3), -2);
+
+        assert!(read_u16_be(&buf, 7).is_err());
+        assert_eq!(u16_be::read(&buf, 7), 0);
+    }
+
+    #[test]
+    fn test_compact() {
+        extern crate std;
+        use std::string::String;
+
+        fn text(f: impl FnOnce(&mut String) -> fmt::Result) -> String {
+            let mut out = String::new();
+            f(&mut out).unwrap();
+            out
+        }
+
+        assert_eq!(text(|o| fmt_uint(o, 0)), "0");
+        assert_eq!(text(|o| fmt_uint(o, u64::MAX)), "18446744073709551615");
+        assert_eq!(text(|o| fmt_int(o, i64::MIN)), "-9223372036854775808");
+        assert_eq!(text(|o| fmt_fixed2(o, 10.2)), "10.20");
+        assert_eq!(text(|o| fmt_fixed2(o, -0.006)), "-0.01");
+        assert_eq!(text(|o| fmt_fixed2(o, -0.004)), "0.00");
+        assert_eq!(text(|o| fmt_fixed2(o, 0.999)), "1.00");
+        assert_eq!(text(|o| fmt_fixed2(o, f32::NEG_INFINITY)), "-inf");
+
+        let max = text(|o| f32_le::write_compact(&f32::MIN, o));
+        assert_eq!(max, "-92233720368547758.08");
+        assert_eq!(max.len(), f32_le::COMPACT_LEN);
+        assert_eq!(
+            text(|o| i16_be::write_compact(&i16::MIN, o)).len(),
+            i16_be::COMPACT_LEN
+        );
+        assert_eq!(
+            text(|o| u16_le::write_compact(&u16::MAX, o)).len(),
+            u16_le::COMPACT_LEN
+        );
+        assert_eq!(
+            text(|o| byte::write_compact(&u8::MAX, o)).len(),
+            byte::COMPACT_LEN
+        );
+    }
+
+    #[test]
+    fn test_short_payload() {
+        assert!(byte::decode(&[], 0).is_err());
+        assert!(u16_le::decode(&[1, 2], 1).is_err());
+        assert!(f32_le::decode(&[0; 5], 2).is_err());
+        assert!(f32_le::decode(&[0; 4], usize::MAX).is_err());
+    }
+
+    #[test]
+    fn test_non_finite() {
+        for v in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            let mut buf = [0; 8];
+            f32_le::encode(&v, &mut buf, 2);
+            assert!(f32_le::decode(&buf, 2).is_err());
+        }
+        let raw = f32_le::decode_with(&buf_of(f32::NAN), 0, DecodePolicy::Raw).unwrap();
+        assert!(raw.is_nan());
+    }
+
+    fn buf_of(v: f32) -> [u8; 8] {
+        let mut buf = [0; 8];
+        f32_le::encode(&v, &mut buf, 0);
+        buf
+    }
+
+    #[test]
+    fn test_normalized() {
+        let normalize = |v: f32| f32_le::decode(&buf_of(v), 0).unwrap().to_bits();
+        assert_eq!(normalize(-0.0), 0);
+        assert_eq!(normalize(f32::from_bits(1)), 0);
+        assert_eq!(normalize(-f32::from_bits(0x007F_FFFF)), 0);
+        assert_eq!(normalize(f32::MAX), f32::MAX.to_bits());
+        assert_eq!(f32_le::read(&buf_of(-0.0), 0).to_bits(), 0);
+
+        let raw = decode_f32_le(&encode_f32_le(-0.0), DecodePolicy::Raw).unwrap();
+        assert_eq!(raw.to_bits(), (-0.0f32).to_bits());
+    }
+}
@@ -0,0 +1,137 @@
+use crate::{CanMessage, GetAngle, GetAngleCompact, SetAngle, SetAngleCompact};
+
+/// Quantizes degrees to hundredths of a degree, rounding to the nearest and saturating at the
+/// i16 range. NaN quantizes to zero.
+pub fn quantize_centideg(deg: f32) -> i16 {
+    let raw = deg * 100.0;
+    if raw < 0.0 {
+        (raw - 0.5) as i16
+    } else {
+        (raw + 0.5) as i16
+    }
+}
+
+/// Converts hundredths of a degree to degrees.
+pub fn dequantize_centideg(centideg: i16) -> f32 {
+    centideg as f32 / 100.0
+}
+
+impl From<SetAngleCompact> for SetAngle {
+    fn from(value: SetAngleCompact) -> Self {
+        Self {
+            angle: dequantize_centideg(value.angle),
+        }
+    }
+}
+
+impl From<SetAngle> for SetAngleCompact {
+    /// Quantizes to the nearest hundredth of a degree, see [`quantize_centideg`].
+    fn from(value: SetAngle) -> Self {
+        Self {
+            angle: quantize_centideg(value.angle),
+        }
+    }
+}
+
+impl From<GetAngleCompact> for GetAngle {
+    fn from(value: GetAngleCompact) -> Self {
+        Self {
+            angle: dequantize_centideg(value.angle),
+        }
+    }
+}
+
+impl From<GetAngle> for GetAngleCompact {
+    /// Quantizes to the nearest hundredth of a degree, see [`quantize_centideg`].
+    fn from(value: GetAngle) -> Self {
+        Self {
+            angle: quantize_centideg(value.angle),
+        }
+    }
+}
+
+impl CanMessage {
+    /// The steering command in this message, from either the full or compact encoding.
+    pub fn set_angle(&self) -> Option<SetAngle> {
+        match *self {
+            CanMessage::SetAngle(a) => Some(a),
+            CanMessage::SetAngleCompact(a) => Some(a.into()),
+            _ => None,
+        }
+    }
+
+    /// The steering feedback in this message, from either the full or compact encoding.
+    pub fn get_angle(&self) -> Option<GetAngle> {
+        match *self {
+            CanMessage::GetAngle(a) => Some(a),
+            CanMessage::GetAngleCompact(a) => Some(a.into()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::MockFrame;
+    use crate::IscFrame;
+    use embedded_hal::can::Frame;
+
+    #[test]
+    fn test_quantize_rounding() {
+        assert_eq!(quantize_centideg(0.0), 0);
+        assert_eq!(quantize_centideg(4.818), 482);
+        assert_eq!(quantize_centideg(4.814), 481);
+        assert_eq!(quantize_centideg(-4.818), -482);
+        assert_eq!(quantize_centideg(-4.814), -481);
+        assert_eq!(quantize_centideg(0.004), 0);
+        assert_eq!(quantize_centideg(-0.004), 0);
+        assert_eq!(quantize_centideg(f32::NAN), 0);
+    }
+
+    #[test]
+    fn test_quantize_saturation() {
+        assert_eq!(quantize_centideg(327.67), i16::MAX);
+        assert_eq!(quantize_centideg(400.0), i16::MAX);
+        assert_eq!(quantize_centideg(f32::INFINITY), i16::MAX);
+        assert_eq!(quantize_centideg(-327.68), i16::MIN);
+        assert_eq!(quantize_centideg(-1e9), i16::MIN);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for i in -4000..=4000 {
+            let angle = i as f32 * 0.0173;
+            let set: SetAngle = SetAngleCompact::from(SetAngle { angle }).into();
+            let get: GetAngle = GetAngleCompact::from(GetAngle { angle }).into();
+
+            assert!((set.angle - angle).abs() <= 0.01, "{angle} {}", set.angle);
+            assert!((get.angle - angle).abs() <= 0.01, "{angle} {}", get.angle);
+        }
+
+        // Lossless from compact
+        for centideg in [i16::MIN, -482, 0, 1, 482, i16::MAX] {
+            let compact = SetAngleCompact { angle: centideg };
+            assert_eq!(SetAngleCompact::from(SetAngle::from(compact)), compact);
+        }
+    }
+
+    #[test]
+    fn test_both_ids_decode() {
+        let full: MockFrame = SetAngle { angle: 4.82 }.into_frame().unwrap();
+        let compact: MockFrame = SetAngleCompact { angle: 482 }.into_frame().unwrap();
+        assert_eq!(compact.data(), [0xE2, 0x01]);
+
+        let full = CanMessage::from_frame(full).unwrap().set_angle().unwrap();
+        let compact = CanMessage::from_frame(compact)
+            .unwrap()
+            .set_angle()
+            .unwrap();
+        assert!((full.angle - compact.angle).abs() < 1e-4);
+
+        let feedback: MockFrame = GetAngleCompact { angle: -125 }.into_frame().unwrap();
+        let feedback = CanMessage::from_frame(feedback).unwrap();
+        assert_eq!(feedback.get_angle(), Some(GetAngle { angle: -1.25 }));
+        assert_eq!(feedback.set_angle(), None);
+    }
+}
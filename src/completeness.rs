@@ -0,0 +1,105 @@
+//! Checks that every message is in every table describing messages, naming any that is missing.
+//!
+//! [`CanMessage::direction`] has no wildcard, so a new message does not compile until it is
+//! classified. The rest are checked at runtime for every ID in [`ALL_IDS`], with the golden
+//! vectors giving a message of each kind to check.
+
+use crate::testing::{Golden, GOLDEN};
+use crate::*;
+
+fn name(id: u32) -> &'static str {
+    MESSAGES
+        .iter()
+        .find(|m| m.id == id)
+        .map_or("an unnamed message", |m| m.name)
+}
+
+fn golden(id: u32) -> impl Iterator<Item = &'static Golden> + Clone {
+    GOLDEN.iter().filter(move |g| g.id == id)
+}
+
+#[test]
+fn test_ids() {
+    assert!(
+        ALL_IDS.iter().eq(MESSAGES.iter().map(|m| &m.id)),
+        "ALL_IDS and MESSAGES disagree"
+    );
+    for id in ALL_IDS {
+        assert!(REGISTRY.get(id).is_some(), "{} is not registered", name(id));
+    }
+    assert_eq!(REGISTRY.entries().len(), ALL_IDS.len());
+}
+
+#[test]
+fn test_described() {
+    for info in &MESSAGES {
+        assert!(!info.name.is_empty(), "{:#x} has no name", info.id);
+        assert!(
+            !info.description.is_empty(),
+            "{} has no description",
+            info.name
+        );
+        assert!(!info.description.starts_with(' '), "{}", info.name);
+    }
+
+    let info = MESSAGES.iter().find(|m| m.name == "TrainingMode").unwrap();
+    assert_eq!(
+        info.description,
+        "Engages training mode. Any node that receives this should begin to relay data on the \
+         CAN bus for data collection, if applicable. There is no way to exit training mode, \
+         rather you power cycle CAN."
+    );
+
+    // Only the first paragraph
+    let info = MESSAGES.iter().find(|m| m.name == "AutonDisable").unwrap();
+    assert!(info
+        .description
+        .ends_with("toggle auton via a physical switch."));
+}
+
+#[test]
+fn test_golden() {
+    for id in ALL_IDS {
+        assert!(
+            golden(id).next().is_some(),
+            "{} has no golden vector",
+            name(id)
+        );
+
+        let dlc = REGISTRY.get(id).map(|e| e.dlc);
+        for g in golden(id) {
+            assert_eq!(Some(g.data.len()), dlc, "{} golden vector length", name(id));
+        }
+    }
+}
+
+#[test]
+fn test_round_trip() {
+    for g in GOLDEN {
+        let Ok(msg) = CanMessage::from_parts(g.id, g.data) else {
+            panic!("{} does not decode", name(g.id));
+        };
+        let (id, data, len) = msg.to_parts().unwrap();
+        assert_eq!(id.as_raw(), g.id, "{} encodes with another ID", name(g.id));
+        assert_eq!(&data[..len], g.data, "{} does not encode back", name(g.id));
+    }
+}
+
+#[test]
+fn test_direction() {
+    for id in ALL_IDS {
+        let mut golden = golden(id).map(|g| g.msg.direction());
+        let Some(direction) = golden.next() else {
+            panic!("{} has no golden vector to classify", name(id));
+        };
+        assert!(
+            golden.all(|d| d == direction),
+            "{} has two directions",
+            name(id)
+        );
+
+        if let Direction::Telemetry { stale_after_ms } = direction {
+            assert!(stale_after_ms > 0, "{} is stale at once", name(id));
+        }
+    }
+}
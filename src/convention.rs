@@ -0,0 +1,195 @@
+use crate::{
+    wheel_angles_with, AckermannGeometry, GetAngle, SetAngle, SteeringCalibration,
+    SteeringSlewLimiter, WheelAngles,
+};
+
+/// How steering angles on the wire relate to the angles used by the rest of the system.
+///
+/// The convention is applied at the API boundary only, so the wire always carries the angle the
+/// steering motor itself uses. Everything that takes angles without a convention assumes
+/// [`SignConvention::Normal`].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum SignConvention {
+    /// Left is negative, and right is positive, on the wire.
+    #[default]
+    Normal,
+    /// The motor is mounted mirrored, so left is positive, and right is negative, on the wire.
+    Inverted,
+}
+
+impl SignConvention {
+    /// Converts between a wire angle and a system angle. This is its own inverse.
+    pub const fn apply(self, angle: f32) -> f32 {
+        match self {
+            SignConvention::Normal => angle,
+            SignConvention::Inverted => -angle,
+        }
+    }
+}
+
+impl SetAngle {
+    /// Creates a command from an angle in the system convention, where left is negative.
+    pub const fn with_convention(angle: f32, convention: SignConvention) -> Self {
+        Self {
+            angle: convention.apply(angle),
+        }
+    }
+
+    /// Commanded angle in the system convention, where left is negative.
+    pub const fn angle_with_convention(&self, convention: SignConvention) -> f32 {
+        convention.apply(self.angle)
+    }
+}
+
+impl GetAngle {
+    /// Creates feedback from an angle in the system convention, where left is negative.
+    pub const fn with_convention(angle: f32, convention: SignConvention) -> Self {
+        Self {
+            angle: convention.apply(angle),
+        }
+    }
+
+    /// Current angle in the system convention, where left is negative.
+    pub const fn angle_with_convention(&self, convention: SignConvention) -> f32 {
+        convention.apply(self.angle)
+    }
+
+    /// Converts the steering angle to ackermann wheel angle with the given calibration, where
+    /// left is negative.
+    pub fn ackermann_angle_with_convention(
+        &self,
+        convention: SignConvention,
+        cal: &SteeringCalibration,
+    ) -> f32 {
+        GetAngle {
+            angle: self.angle_with_convention(convention),
+        }
+        .ackermann_angle_with(cal)
+    }
+}
+
+/// Computes the front wheel angles, where left is negative, for a steering motor using the given
+/// convention and calibration. See [`wheel_angles_with`].
+pub fn wheel_angles_with_convention(
+    steer: &GetAngle,
+    geom: &AckermannGeometry,
+    convention: SignConvention,
+    cal: &SteeringCalibration,
+) -> WheelAngles {
+    let steer = GetAngle {
+        angle: steer.angle_with_convention(convention),
+    };
+    wheel_angles_with(&steer, geom, cal)
+}
+
+impl SteeringSlewLimiter {
+    /// Like [`SteeringSlewLimiter::next`], but with a target in the system convention.
+    ///
+    /// The limiter itself works in wire angles, so the returned command can be sent as is.
+    pub fn next_with_convention(
+        &mut self,
+        target: f32,
+        dt_s: f32,
+        convention: SignConvention,
+    ) -> SetAngle {
+        self.next(&SetAngle::with_convention(target, convention), dt_s)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::MockFrame;
+    use crate::{CanMessage, IscFrame};
+
+    /// Commands an angle, and reads it back the way the PC would, returning the wire angle and
+    /// the ackermann angle the PC computes.
+    fn command_feedback_chain(angle: f32, convention: SignConvention) -> (f32, f32) {
+        let cmd = SetAngle::with_convention(angle, convention);
+        let frame: MockFrame = cmd.into_frame().unwrap();
+
+        // The steering node reports back the same motor angle it was commanded
+        let wire = match CanMessage::from_frame(frame).unwrap() {
+            CanMessage::SetAngle(s) => s.angle,
+            _ => panic!(),
+        };
+        let feedback = GetAngle { angle: wire };
+
+        assert_eq!(feedback.angle_with_convention(convention), angle);
+        let cal = SteeringCalibration::DEFAULT;
+        (
+            wire,
+            feedback.ackermann_angle_with_convention(convention, &cal),
+        )
+    }
+
+    #[test]
+    fn test_default_is_normal() {
+        assert_eq!(SignConvention::default(), SignConvention::Normal);
+
+        let get = GetAngle { angle: 4.818 };
+        assert_eq!(
+            get.ackermann_angle_with_convention(
+                SignConvention::default(),
+                &SteeringCalibration::DEFAULT
+            ),
+            get.ackermann_angle()
+        );
+    }
+
+    #[test]
+    fn test_single_inversion() {
+        for angle in [-12.5, 0.0, 3.25] {
+            let (normal_wire, normal_ack) = command_feedback_chain(angle, SignConvention::Normal);
+            let (inv_wire, inv_ack) = command_feedback_chain(angle, SignConvention::Inverted);
+
+            // Flipped once on the wire, and flipped back once at the other end
+            assert_eq!(normal_wire, angle);
+            assert_eq!(inv_wire, -angle);
+            assert_eq!(normal_ack, inv_ack);
+        }
+
+        let geom = AckermannGeometry {
+            wheelbase_m: 1.05,
+            track_m: 0.9,
+        };
+        let inverted = GetAngle::with_convention(8.0, SignConvention::Inverted);
+        let cal = SteeringCalibration::DEFAULT;
+        assert_eq!(
+            wheel_angles_with_convention(&inverted, &geom, SignConvention::Inverted, &cal),
+            crate::wheel_angles(&GetAngle { angle: 8.0 }, &geom)
+        );
+    }
+
+    #[test]
+    fn test_calibration_with_convention() {
+        let rebuilt = SteeringCalibration {
+            gain: -2.4,
+            offset: 1.5,
+        };
+        let geom = AckermannGeometry {
+            wheelbase_m: 1.05,
+            track_m: 0.9,
+        };
+        let normal = GetAngle { angle: 8.0 };
+        let inverted = GetAngle::with_convention(8.0, SignConvention::Inverted);
+
+        let ack = inverted.ackermann_angle_with_convention(SignConvention::Inverted, &rebuilt);
+        assert_eq!(ack, rebuilt.wheel_angle(8.0));
+        assert_ne!(ack, normal.ackermann_angle());
+
+        let wheels =
+            wheel_angles_with_convention(&inverted, &geom, SignConvention::Inverted, &rebuilt);
+        assert_eq!(wheels, wheel_angles_with(&normal, &geom, &rebuilt));
+        assert_ne!(wheels, crate::wheel_angles(&normal, &geom));
+    }
+
+    #[test]
+    fn test_slew_with_convention() {
+        let mut limiter = SteeringSlewLimiter::new(10.0);
+        let cmd = limiter.next_with_convention(5.0, 0.1, SignConvention::Inverted);
+
+        assert_eq!(cmd.angle, -1.0);
+        assert_eq!(cmd.angle_with_convention(SignConvention::Inverted), 1.0);
+    }
+}
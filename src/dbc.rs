@@ -0,0 +1,299 @@
+use crate::{ByteOrder, IscFrame, Multiplex, Signal, SignalKind};
+use core::fmt::{self, Write};
+
+/// Description of one message, for exporting the bus to other tools.
+///
+/// [`MESSAGES`](crate::MESSAGES) describes every message in this crate.
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+pub struct MessageInfo {
+    pub name: &'static str,
+    /// First paragraph of the message's docs on one line, empty if it has none.
+    pub description: &'static str,
+    pub id: u32,
+    /// Payload length in bytes, the longest page for multiplexed messages.
+    pub dlc: usize,
+    /// Payload length of its legacy layout, which also decodes, if it has one.
+    pub legacy_dlc: Option<usize>,
+    pub signals: &'static [Signal],
+}
+
+impl MessageInfo {
+    pub const fn of<T: IscFrame>() -> Self {
+        Self {
+            name: T::NAME,
+            description: "",
+            id: T::ID,
+            dlc: T::DLC,
+            legacy_dlc: T::LEGACY_DLC,
+            signals: T::SIGNALS,
+        }
+    }
+
+    /// Sets the description to the first line of `doc`, without leading spaces. A doc comment
+    /// can be joined onto one line first with [`DocParagraph`].
+    pub const fn with_doc(self, doc: &'static str) -> Self {
+        let bytes = doc.as_bytes();
+        let mut start = 0;
+        while start < bytes.len() && bytes[start] == b' ' {
+            start += 1;
+        }
+        let mut end = start;
+        while end < bytes.len() && bytes[end] != b'\n' {
+            end += 1;
+        }
+
+        let (line, _) = bytes.split_at(end);
+        let (_, line) = line.split_at(start);
+        let description = match core::str::from_utf8(line) {
+            Ok(line) => line,
+            Err(_) => "",
+        };
+        Self {
+            description,
+            ..self
+        }
+    }
+}
+
+/// The first paragraph of a doc comment, joined onto one line in a buffer as long as the doc,
+/// so it can be made in a const for [`MessageInfo::with_doc`].
+///
+/// Each line loses its leading and trailing spaces, and lines are joined by a space up to the
+/// first blank line.
+#[derive(Copy, Clone, Debug)]
+pub struct DocParagraph<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> DocParagraph<N> {
+    pub const fn new(doc: &str) -> Self {
+        let doc = doc.as_bytes();
+        let mut buf = [0; N];
+        let mut len = 0;
+        let mut at = 0;
+
+        while at < doc.len() {
+            let mut start = at;
+            while start < doc.len() && doc[start] == b' ' {
+                start += 1;
+            }
+            let mut end = start;
+            while end < doc.len() && doc[end] != b'\n' {
+                end += 1;
+            }
+            at = end + 1;
+            while end > start && doc[end - 1] == b' ' {
+                end -= 1;
+            }
+
+            if start == end {
+                // Blank lines before the paragraph are skipped, and the first after ends it
+                if len > 0 {
+                    break;
+                }
+                continue;
+            }
+            if len > 0 && len < N {
+                buf[len] = b' ';
+                len += 1;
+            }
+            while start < end && len < N {
+                buf[len] = doc[start];
+                len += 1;
+                start += 1;
+            }
+        }
+        Self { buf, len }
+    }
+
+    /// The paragraph, empty if cut inside a character, which only happens when `N` is
+    /// shorter than the doc.
+    pub const fn as_str(&self) -> &str {
+        let (text, _) = self.buf.split_at(self.len);
+        match core::str::from_utf8(text) {
+            Ok(text) => text,
+            Err(_) => "",
+        }
+    }
+}
+
+/// Writes messages as a DBC file, the bus description read by most CAN tools.
+///
+/// Every message is an extended frame sent by no particular node. Multiplexed messages mark
+/// their selector with `M`, and each page's signals with `m` and the selector value.
+pub fn write_dbc<W: Write>(out: &mut W, messages: &[MessageInfo]) -> fmt::Result {
+    writeln!(out, "VERSION \"\"")?;
+    writeln!(out)?;
+    writeln!(out, "NS_ :")?;
+    writeln!(out)?;
+    writeln!(out, "BS_:")?;
+    writeln!(out)?;
+    writeln!(out, "BU_:")?;
+
+    for msg in messages {
+        writeln!(out)?;
+        writeln!(
+            out,
+            "BO_ {} {}: {} Vector__XXX",
+            dbc_id(msg.id),
+            msg.name,
+            msg.dlc
+        )?;
+
+        for signal in msg.signals {
+            write_signal(out, signal)?;
+        }
+    }
+
+    // Signals are integers unless marked otherwise
+    let mut floats = messages.iter().flat_map(|m| {
+        m.signals
+            .iter()
+            .filter(|s| s.kind == SignalKind::Float)
+            .map(move |s| (m.id, s))
+    });
+    if let Some(first) = floats.next() {
+        writeln!(out)?;
+        for (id, signal) in core::iter::once(first).chain(floats) {
+            let valtype = if signal.size == 8 { 2 } else { 1 };
+            writeln!(
+                out,
+                "SIG_VALTYPE_ {} {} : {};",
+                dbc_id(id),
+                signal.name,
+                valtype
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// DBC marks extended IDs with the top bit.
+fn dbc_id(id: u32) -> u32 {
+    id | 0x8000_0000
+}
+
+fn write_signal<W: Write>(out: &mut W, signal: &Signal) -> fmt::Result {
+    write!(out, " SG_ {} ", signal.name)?;
+    match signal.mux {
+        Multiplex::Plain => {}
+        Multiplex::Selector => write!(out, "M ")?,
+        Multiplex::Page(page) => write!(out, "m{page} ")?,
+    }
+
+    // Big endian signals start at their most significant bit
+    let (start_bit, order) = match signal.order {
+        ByteOrder::LittleEndian => (signal.start * 8, 1),
+        ByteOrder::BigEndian => (signal.start * 8 + 7, 0),
+    };
+    let sign = match signal.kind {
+        SignalKind::Signed | SignalKind::Float => '-',
+        SignalKind::Unsigned => '+',
+    };
+
+    writeln!(
+        out,
+        ": {}|{}@{}{} ({},{}) [0|0] \"{}\" Vector__XXX",
+        start_bit,
+        signal.size * 8,
+        order,
+        sign,
+        signal.scale,
+        signal.offset,
+        signal.unit
+    )
+}
+
+#[cfg(all(test, feature = "msgs-all"))]
+mod test {
+    use super::*;
+    use crate::*;
+    extern crate std;
+    use std::string::String;
+
+    fn dbc(messages: &[MessageInfo]) -> String {
+        let mut out = String::new();
+        write_dbc(&mut out, messages).unwrap();
+        out
+    }
+
+    #[test]
+    fn test_doc_paragraph() {
+        const DOC: &str = "\n  Sets the brake, \n as a percent.\n\n Not this.\n";
+        const PARAGRAPH: DocParagraph<{ DOC.len() }> = DocParagraph::new(DOC);
+        assert_eq!(PARAGRAPH.as_str(), "Sets the brake, as a percent.");
+
+        assert_eq!(DocParagraph::<8>::new(" One line").as_str(), "One line");
+        assert_eq!(DocParagraph::<4>::new(" Cut short").as_str(), "Cut ");
+        assert_eq!(DocParagraph::<8>::new("\n \n").as_str(), "");
+    }
+
+    #[test]
+    fn test_plain() {
+        let out = dbc(&[
+            MessageInfo::of::<EncoderCount>(),
+            MessageInfo::of::<MotorTemperature>(),
+        ]);
+
+        assert_eq!(
+            out,
+            "VERSION \"\"\n\
+             \n\
+             NS_ :\n\
+             \n\
+             BS_:\n\
+             \n\
+             BU_:\n\
+             \n\
+             BO_ 2147483655 EncoderCount: 6 Vector__XXX\n \
+             SG_ count : 0|16@1+ (1,0) [0|0] \"\" Vector__XXX\n \
+             SG_ velocity : 16|32@1- (1,0) [0|0] \"m/s\" Vector__XXX\n\
+             \n\
+             BO_ 2147483657 MotorTemperature: 2 Vector__XXX\n \
+             SG_ temp : 0|16@1- (0.1,0) [0|0] \"degC\" Vector__XXX\n\
+             \n\
+             SIG_VALTYPE_ 2147483655 velocity : 1;\n"
+        );
+    }
+
+    #[test]
+    fn test_multiplexed() {
+        let out = dbc(&[MessageInfo::of::<CellVoltages>()]);
+
+        assert_eq!(
+            out,
+            "VERSION \"\"\n\
+             \n\
+             NS_ :\n\
+             \n\
+             BS_:\n\
+             \n\
+             BU_:\n\
+             \n\
+             BO_ 2147483660 CellVoltages: 7 Vector__XXX\n \
+             SG_ page M : 0|8@1+ (1,0) [0|0] \"\" Vector__XXX\n \
+             SG_ cell_1 m0 : 8|16@1+ (0.001,0) [0|0] \"V\" Vector__XXX\n \
+             SG_ cell_2 m0 : 24|16@1+ (0.001,0) [0|0] \"V\" Vector__XXX\n \
+             SG_ cell_3 m0 : 40|16@1+ (0.001,0) [0|0] \"V\" Vector__XXX\n \
+             SG_ cell_4 m1 : 8|16@1+ (0.001,0) [0|0] \"V\" Vector__XXX\n \
+             SG_ cell_5 m1 : 24|16@1+ (0.001,0) [0|0] \"V\" Vector__XXX\n \
+             SG_ cell_6 m1 : 40|16@1+ (0.001,0) [0|0] \"V\" Vector__XXX\n"
+        );
+    }
+
+    #[test]
+    fn test_every_message() {
+        let out = dbc(&MESSAGES);
+
+        assert_eq!(out.matches("BO_ ").count(), REGISTRY.entries().len());
+        for msg in &MESSAGES {
+            assert!(
+                out.contains(&std::format!(" {}: ", msg.name)),
+                "{}",
+                msg.name
+            );
+        }
+    }
+}
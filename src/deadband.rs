@@ -0,0 +1,194 @@
+use crate::GetAngle;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+enum State {
+    /// Output is held until the input leaves the threshold.
+    Holding,
+    /// Output follows the input, which is moving in this direction.
+    Tracking { rising: bool },
+}
+
+/// Suppresses jitter in steering angle feedback, while still tracking real motion.
+///
+/// While holding, the reported angle does not change until the input moves more than
+/// `threshold` from it. Once released the output follows the input exactly for as long as it
+/// keeps moving in the same direction, so slow continuous motion is tracked without bias after
+/// the initial threshold is crossed. If the input then reverses by no more than `hysteresis`,
+/// the output is held again. A larger reversal keeps tracking in the new direction.
+///
+/// Hysteresis should be larger than the peak to peak jitter at rest, and no larger than the
+/// threshold.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Deadband {
+    threshold: f32,
+    hysteresis: f32,
+    state: State,
+    output: Option<f32>,
+    changed: bool,
+}
+
+impl Deadband {
+    /// Creates a deadband, with both bands in degrees.
+    pub const fn new(threshold: f32, hysteresis: f32) -> Self {
+        Self {
+            threshold,
+            hysteresis,
+            state: State::Holding,
+            output: None,
+            changed: false,
+        }
+    }
+
+    /// Filters a new feedback message, returning the angle to report.
+    ///
+    /// The first finite input is reported as is. Non-finite inputs are ignored, reporting the
+    /// held angle, or straight ahead at zero if there is none yet.
+    pub fn filter(&mut self, raw: &GetAngle) -> GetAngle {
+        let input = raw.angle;
+        self.changed = false;
+
+        let Some(out) = self.output else {
+            if !input.is_finite() {
+                return GetAngle { angle: 0.0 };
+            }
+            self.output = Some(input);
+            self.changed = true;
+            return GetAngle { angle: input };
+        };
+
+        if !input.is_finite() {
+            return GetAngle { angle: out };
+        }
+
+        let delta = input - out;
+        let next = match self.state {
+            State::Holding if delta.abs() > self.threshold => Some(State::Tracking {
+                rising: delta > 0.0,
+            }),
+            State::Holding => None,
+            State::Tracking { rising } => {
+                let forward = if rising { delta } else { -delta };
+
+                if forward >= 0.0 {
+                    Some(State::Tracking { rising })
+                } else if -forward > self.hysteresis {
+                    Some(State::Tracking { rising: !rising })
+                } else {
+                    self.state = State::Holding;
+                    None
+                }
+            }
+        };
+
+        if let Some(state) = next {
+            self.state = state;
+            self.changed = input != out;
+            self.output = Some(input);
+        }
+
+        GetAngle {
+            angle: self.output.unwrap_or(input),
+        }
+    }
+
+    /// True if the last call to [`Deadband::filter`] changed the reported angle.
+    pub fn changed(&self) -> bool {
+        self.changed
+    }
+
+    /// Forgets the held angle, so the next input is reported as is.
+    pub fn reset(&mut self) {
+        *self = Self::new(self.threshold, self.hysteresis);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn feed(deadband: &mut Deadband, angle: f32) -> f32 {
+        deadband.filter(&GetAngle { angle }).angle
+    }
+
+    #[test]
+    fn test_jitter_suppressed() {
+        let mut deadband = Deadband::new(0.6, 0.5);
+        assert_eq!(feed(&mut deadband, 3.0), 3.0);
+        assert!(deadband.changed());
+
+        for jitter in [0.2, -0.1, 0.15, -0.2, 0.05, 0.0, -0.15] {
+            assert_eq!(feed(&mut deadband, 3.0 + jitter), 3.0);
+            assert!(!deadband.changed());
+        }
+
+        assert_eq!(feed(&mut deadband, f32::NAN), 3.0);
+    }
+
+    #[test]
+    fn test_non_finite_first() {
+        let mut deadband = Deadband::new(0.6, 0.5);
+        for angle in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            assert_eq!(feed(&mut deadband, angle), 0.0);
+            assert!(!deadband.changed());
+        }
+
+        // Still nothing held, so the first finite input is reported as is
+        assert_eq!(feed(&mut deadband, 3.0), 3.0);
+        assert!(deadband.changed());
+        assert_eq!(feed(&mut deadband, f32::NAN), 3.0);
+    }
+
+    #[test]
+    fn test_ramp_tracking() {
+        let mut deadband = Deadband::new(0.5, 0.3);
+        feed(&mut deadband, 0.0);
+
+        let mut released = false;
+        for i in 1..=200 {
+            let input = i as f32 * 0.05;
+            let out = feed(&mut deadband, input);
+
+            if input > 0.5 {
+                released = true;
+            }
+            if released {
+                // No lag once moving, the output is the input
+                assert_eq!(out, input);
+                assert!(deadband.changed());
+            } else {
+                assert_eq!(out, 0.0);
+            }
+        }
+
+        // Jitter after the ramp stops is held
+        let end = 200.0 * 0.05;
+        feed(&mut deadband, end + 0.1);
+        assert_eq!(feed(&mut deadband, end - 0.1), end + 0.1);
+        assert_eq!(feed(&mut deadband, end + 0.2), end + 0.1);
+    }
+
+    #[test]
+    fn test_hysteresis_crossing() {
+        for sign in [1.0, -1.0] {
+            let mut deadband = Deadband::new(1.0, 0.4);
+            feed(&mut deadband, 0.0);
+
+            // Released, moving away from zero
+            assert_eq!(feed(&mut deadband, sign * 1.5), sign * 1.5);
+            assert_eq!(feed(&mut deadband, sign * 2.0), sign * 2.0);
+
+            // Small reversal holds
+            assert_eq!(feed(&mut deadband, sign * 1.7), sign * 2.0);
+            assert!(!deadband.changed());
+
+            // Held until the full threshold is crossed
+            assert_eq!(feed(&mut deadband, sign * 1.1), sign * 2.0);
+            assert_eq!(feed(&mut deadband, sign * 0.9), sign * 0.9);
+            assert!(deadband.changed());
+
+            // A reversal beyond the hysteresis is tracked immediately
+            assert_eq!(feed(&mut deadband, sign * 0.5), sign * 0.5);
+            assert_eq!(feed(&mut deadband, sign * 1.0), sign * 1.0);
+        }
+    }
+}
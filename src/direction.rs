@@ -0,0 +1,61 @@
+use crate::CanMessage;
+
+/// Who sends a message, and what it is for.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Direction {
+    /// From ROS, through the interface board, to the node that acts on it.
+    Command,
+    /// From a node, reporting what it measured.
+    Telemetry {
+        /// How long a reading can be relied on, in ms. Older readings should be treated as
+        /// missing.
+        stale_after_ms: u32,
+    },
+    /// Changes the mode of the whole kart.
+    System,
+}
+
+impl CanMessage {
+    /// Who sends the message, and what it is for.
+    pub const fn direction(&self) -> Direction {
+        use Direction::*;
+
+        // No wildcard, so a new message must be classified here
+        match self {
+            CanMessage::EstopAll(_) | CanMessage::AutonDisable(_) | CanMessage::TrainingMode(_) => {
+                System
+            }
+            #[cfg(feature = "msgs-brake")]
+            CanMessage::SetBrake(_) | CanMessage::LockBrake(_) | CanMessage::UnlockBrake(_) => {
+                Command
+            }
+            #[cfg(feature = "msgs-steering")]
+            CanMessage::SetAngle(_) | CanMessage::SetAngleCompact(_) => Command,
+            #[cfg(feature = "msgs-steering")]
+            CanMessage::GetAngle(_) | CanMessage::GetAngleCompact(_) => Telemetry {
+                stale_after_ms: 100,
+            },
+            #[cfg(feature = "msgs-drive")]
+            CanMessage::SetSpeed(_) => Command,
+            #[cfg(feature = "msgs-drive")]
+            CanMessage::EncoderCount(_) => Telemetry {
+                stale_after_ms: 100,
+            },
+            #[cfg(feature = "msgs-drive")]
+            CanMessage::MotorTemperature(_) => Telemetry {
+                stale_after_ms: 1000,
+            },
+            #[cfg(feature = "msgs-drive")]
+            CanMessage::ImuRates(_) => Telemetry { stale_after_ms: 50 },
+            // Sent a page at a time, so each cell is only refreshed every other frame
+            #[cfg(feature = "msgs-power")]
+            CanMessage::CellVoltages(_) => Telemetry {
+                stale_after_ms: 2000,
+            },
+            // Three missed beats, so one dropped frame doesn't mark a node dead
+            CanMessage::Heartbeat(_) => Telemetry {
+                stale_after_ms: 300,
+            },
+        }
+    }
+}
@@ -0,0 +1,196 @@
+/// Defines a fieldless enum stored as an unsigned integer.
+///
+/// Each variant is given its value in the integer given by an encoding from
+/// [`codec`](crate::codec). Other values are unknown, and decoding them errors with
+/// [`ConvertErr::InvalidFrame`](crate::ConvertErr::InvalidFrame), so every value a message
+/// holds means something to both ends. Variants are written by name in compact text.
+///
+/// The type can be used as a message field encoding by path, like `Mode as self::Mode`.
+///
+/// ```
+/// phnx_candefs::isc_enum! {
+///     /// Drive mode.
+///     pub enum Mode: u8 as byte {
+///         Park = 0,
+///         Drive = 1,
+///         Reverse = 2,
+///     }
+/// }
+///
+/// assert_eq!(Mode::from_raw(2), Some(Mode::Reverse));
+/// assert_eq!(Mode::from_raw(3), None);
+/// assert_eq!(Mode::Drive.name(), "Drive");
+/// ```
+#[macro_export]
+macro_rules! isc_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident: $raw:ident as $codec:ident {
+            $(
+                $(#[$vmeta:meta])*
+                $variant:ident = $value:literal
+            ),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+        #[repr($raw)]
+        $vis enum $name {
+            $(
+                $(#[$vmeta])*
+                $variant = $value,
+            )+
+        }
+
+        #[allow(dead_code)]
+        impl $name {
+            /// Every variant, in declaration order.
+            pub const ALL: &'static [Self] = &[$(Self::$variant),+];
+
+            /// The variant with a raw value, if any.
+            pub const fn from_raw(raw: $raw) -> Option<Self> {
+                match raw {
+                    $($value => Some(Self::$variant),)+
+                    _ => None,
+                }
+            }
+
+            pub const fn raw(self) -> $raw {
+                self as $raw
+            }
+
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $(Self::$variant => stringify!($variant),)+
+                }
+            }
+
+            // Used as a field encoding, like the modules in codec
+
+            #[doc(hidden)]
+            pub const SIZE: usize = $crate::codec::$codec::SIZE;
+            #[doc(hidden)]
+            pub const KIND: $crate::SignalKind = $crate::SignalKind::Unsigned;
+            #[doc(hidden)]
+            pub const ORDER: $crate::ByteOrder = $crate::codec::$codec::ORDER;
+
+            #[doc(hidden)]
+            pub const fn encode(v: &Self, buf: &mut [u8; 8], at: usize) {
+                $crate::codec::$codec::encode(&v.raw(), buf, at)
+            }
+
+            #[doc(hidden)]
+            pub fn decode(data: &[u8], at: usize) -> Result<Self, $crate::ConvertErr> {
+                let raw = $crate::codec::$codec::decode(data, at)?;
+                Self::from_raw(raw).ok_or($crate::ConvertErr::InvalidFrame)
+            }
+
+            #[doc(hidden)]
+            pub fn decode_with(
+                data: &[u8],
+                at: usize,
+                _: $crate::DecodePolicy,
+            ) -> Result<Self, $crate::ConvertErr> {
+                Self::decode(data, at)
+            }
+
+            /// Reads a field already checked by `decode`, so never falls back to the first
+            /// variant in practice.
+            #[doc(hidden)]
+            pub fn read(data: &[u8], at: usize) -> Self {
+                Self::from_raw($crate::codec::$codec::read(data, at)).unwrap_or(Self::ALL[0])
+            }
+
+            #[doc(hidden)]
+            pub const COMPACT_LEN: usize = {
+                let mut longest = 0;
+                $(if stringify!($variant).len() > longest {
+                    longest = stringify!($variant).len();
+                })+
+                longest
+            };
+
+            #[doc(hidden)]
+            pub fn write_compact<W: core::fmt::Write>(v: &Self, out: &mut W) -> core::fmt::Result {
+                out.write_str(v.name())
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ConvertErr, IscFrame, IscView};
+    extern crate std;
+
+    crate::isc_enum! {
+        pub enum Gear: u8 as byte {
+            Neutral = 0,
+            Low = 1,
+            High = 2,
+        }
+    }
+
+    crate::isc_enum! {
+        pub enum Zone: u16 as u16_le {
+            Paddock = 0,
+            PitLane = 0x100,
+            Track = 0xFFFF,
+        }
+    }
+
+    #[derive(IscFrame, Copy, Clone, Debug, PartialEq)]
+    #[isc(id = 0x1F1)]
+    struct Position {
+        #[isc(wire = self::Gear)]
+        gear: Gear,
+        #[isc(wire = self::Zone)]
+        zone: Zone,
+    }
+
+    #[test]
+    fn test_variants() {
+        for &gear in Gear::ALL {
+            for &zone in Zone::ALL {
+                let position = Position { gear, zone };
+                let (data, len) = position.encode();
+                assert_eq!(data[0], gear.raw());
+                assert_eq!(Position::decode(&data[..len]).unwrap(), position);
+                assert_eq!(Gear::from_raw(gear.raw()), Some(gear));
+            }
+        }
+        assert_eq!(
+            Position {
+                gear: Gear::High,
+                zone: Zone::PitLane,
+            }
+            .encode(),
+            ([2, 0x00, 0x01, 0, 0, 0, 0, 0], 3)
+        );
+
+        // By name
+        let mut text = std::string::String::new();
+        Position {
+            gear: Gear::Low,
+            zone: Zone::Track,
+        }
+        .write_compact(&mut text)
+        .unwrap();
+        assert_eq!(text, "Position gear=Low zone=Track");
+        assert_eq!(
+            Position::COMPACT_LEN,
+            "Position gear=Neutral zone=Paddock".len()
+        );
+    }
+
+    #[test]
+    fn test_unknown_rejected() {
+        for data in [[3, 0, 0], [0xFF, 0, 0], [0, 1, 0], [0, 0xFE, 0xFF]] {
+            assert!(matches!(
+                Position::decode(&data),
+                Err(ConvertErr::InvalidFrame)
+            ));
+            assert!(Position::view(&data).is_err());
+        }
+    }
+}
@@ -0,0 +1,46 @@
+crate::isc_enum! {
+    /// What sent an [`EstopAll`](crate::EstopAll), for logs after an incident.
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+    pub enum EstopSource: u8 as byte {
+        /// The remote kill switch, through the interface board.
+        RemoteKillSwitch = 0,
+        Ros = 1,
+        /// A node's watchdog, after commands stopped coming in time.
+        FirmwareWatchdog = 2,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{CanMessage, ConvertErr, EstopAll, IscFrame};
+
+    #[test]
+    fn test_sources() {
+        for &source in EstopSource::ALL {
+            let msg = EstopAll { source };
+            let (data, len) = msg.encode();
+            assert_eq!(data[..len], [source.raw()]);
+            assert_eq!(EstopAll::decode(&data[..len]).unwrap(), msg);
+        }
+        assert_eq!(EstopAll::ID, 0);
+    }
+
+    #[test]
+    fn test_unknown_source() {
+        for raw in [3, 0x80, 0xFF] {
+            assert!(matches!(
+                CanMessage::from_parts(EstopAll::ID, &[raw]),
+                Err(ConvertErr::InvalidFrame)
+            ));
+        }
+        assert!(matches!(
+            CanMessage::from_parts(EstopAll::ID, &[]),
+            Err(ConvertErr::WrongLength {
+                expected: 1,
+                got: 0
+            })
+        ));
+    }
+}
@@ -0,0 +1,283 @@
+/// Defines a set of named bit flags, packed into an unsigned integer.
+///
+/// Each flag is a bit index in the integer given by an encoding from [`codec`](crate::codec).
+/// Bits that are not named are undefined, and are always preserved: decoding keeps them, and
+/// encoding writes them back unchanged, so a node can forward flags from newer firmware without
+/// losing them. Flags built only from named flags never set them, and `undefined` reports any
+/// that are set.
+///
+/// The type can be used as a message field encoding by path, like `Lights as self::Lights`.
+///
+/// ```
+/// phnx_candefs::isc_flags! {
+///     /// Lights on the kart.
+///     pub struct Lights: u8 as byte {
+///         HEADLIGHTS = 0,
+///         BRAKE = 1,
+///     }
+/// }
+///
+/// let lights = Lights::HEADLIGHTS | Lights::BRAKE;
+/// assert!(lights.contains(Lights::BRAKE));
+/// assert_eq!(lights.bits(), 0b11);
+/// assert_eq!(Lights::from_bits(0b1000_0001).undefined(), 0b1000_0000);
+/// ```
+#[macro_export]
+macro_rules! isc_flags {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident: $raw:ty as $codec:ident {
+            $(
+                $(#[$fmeta:meta])*
+                $flag:ident = $bit:literal
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+        $vis struct $name($raw);
+
+        #[allow(dead_code)]
+        impl $name {
+            $(
+                $(#[$fmeta])*
+                pub const $flag: Self = Self(1 << $bit);
+            )*
+
+            /// Every named flag, with its name.
+            pub const ALL: &'static [(&'static str, Self)] = &[$((stringify!($flag), Self::$flag)),*];
+
+            /// Mask of the named bits.
+            pub const DEFINED: $raw = 0 $(| (1 << $bit))*;
+
+            /// No flags set.
+            pub const fn empty() -> Self {
+                Self(0)
+            }
+
+            /// Flags from raw bits, preserving undefined bits.
+            pub const fn from_bits(bits: $raw) -> Self {
+                Self(bits)
+            }
+
+            /// Raw bits, including undefined bits.
+            pub const fn bits(self) -> $raw {
+                self.0
+            }
+
+            /// Undefined bits that are set.
+            pub const fn undefined(self) -> $raw {
+                self.0 & !Self::DEFINED
+            }
+
+            /// True if every flag in `other` is set.
+            pub const fn contains(self, other: Self) -> bool {
+                self.0 & other.0 == other.0
+            }
+
+            pub const fn union(self, other: Self) -> Self {
+                Self(self.0 | other.0)
+            }
+
+            pub const fn difference(self, other: Self) -> Self {
+                Self(self.0 & !other.0)
+            }
+
+            /// Sets or clears every flag in `other`.
+            pub fn set(&mut self, other: Self, on: bool) {
+                *self = if on {
+                    self.union(other)
+                } else {
+                    self.difference(other)
+                };
+            }
+
+            // Used as a field encoding, like the modules in codec
+
+            #[doc(hidden)]
+            pub const SIZE: usize = $crate::codec::$codec::SIZE;
+            #[doc(hidden)]
+            pub const KIND: $crate::SignalKind = $crate::SignalKind::Unsigned;
+            #[doc(hidden)]
+            pub const ORDER: $crate::ByteOrder = $crate::codec::$codec::ORDER;
+
+            #[doc(hidden)]
+            pub const fn encode(v: &Self, buf: &mut [u8; 8], at: usize) {
+                $crate::codec::$codec::encode(&v.0, buf, at)
+            }
+
+            #[doc(hidden)]
+            pub fn decode(data: &[u8], at: usize) -> Result<Self, $crate::ConvertErr> {
+                $crate::codec::$codec::decode(data, at).map(Self)
+            }
+
+            #[doc(hidden)]
+            pub fn decode_with(
+                data: &[u8],
+                at: usize,
+                policy: $crate::DecodePolicy,
+            ) -> Result<Self, $crate::ConvertErr> {
+                $crate::codec::$codec::decode_with(data, at, policy).map(Self)
+            }
+
+            #[doc(hidden)]
+            pub fn read(data: &[u8], at: usize) -> Self {
+                Self($crate::codec::$codec::read(data, at))
+            }
+
+            #[doc(hidden)]
+            pub const COMPACT_LEN: usize = $crate::codec::$codec::COMPACT_LEN;
+
+            #[doc(hidden)]
+            pub fn write_compact<W: core::fmt::Write>(v: &Self, out: &mut W) -> core::fmt::Result {
+                $crate::codec::$codec::write_compact(&v.0, out)
+            }
+        }
+
+        impl core::ops::BitOr for $name {
+            type Output = Self;
+
+            fn bitor(self, rhs: Self) -> Self {
+                self.union(rhs)
+            }
+        }
+
+        impl core::ops::BitOrAssign for $name {
+            fn bitor_assign(&mut self, rhs: Self) {
+                *self = self.union(rhs);
+            }
+        }
+
+        const _: () = {
+            $(assert!(
+                ($bit as u32) < <$raw>::BITS,
+                concat!(stringify!($name), "::", stringify!($flag), " is out of range")
+            );)*
+            assert!(
+                <$name>::DEFINED.count_ones() as usize == <$name>::ALL.len(),
+                concat!(stringify!($name), " has two flags on the same bit")
+            );
+        };
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ConvertErr, IscFrame};
+    extern crate std;
+
+    crate::isc_flags! {
+        /// Status lights.
+        pub struct Lights: u8 as byte {
+            HEADLIGHTS = 0,
+            BRAKE = 1,
+            LEFT_TURN = 2,
+            RIGHT_TURN = 3,
+            HAZARD = 7,
+        }
+    }
+
+    crate::isc_flags! {
+        pub struct Switches: u16 as u16_le {
+            STEER_LEFT_LIMIT = 0,
+            STEER_RIGHT_LIMIT = 1,
+            CONTACTOR = 9,
+        }
+    }
+
+    #[derive(IscFrame, Copy, Clone, Debug, PartialEq)]
+    #[isc(id = 0x1F0)]
+    struct Status {
+        #[isc(wire = self::Lights)]
+        lights: Lights,
+        #[isc(wire = "self::Switches")]
+        switches: Switches,
+    }
+
+    fn round_trip(status: Status) -> Result<Status, ConvertErr> {
+        let (data, len) = status.encode();
+        Status::decode(&data[..len])
+    }
+
+    /// Checks that each named flag round trips alone, in the bit it names.
+    macro_rules! check_named_bits {
+        ($flags:ident, $field:ident) => {
+            for &(name, flag) in $flags::ALL {
+                let mut status = Status {
+                    lights: Lights::empty(),
+                    switches: Switches::empty(),
+                };
+                status.$field = flag;
+
+                let back = round_trip(status).unwrap();
+                assert_eq!(back, status, "{name}");
+                assert_eq!(back.$field.bits().count_ones(), 1, "{name}");
+                assert_eq!(back.$field.undefined(), 0, "{name}");
+
+                for &(other, other_flag) in $flags::ALL {
+                    assert_eq!(back.$field.contains(other_flag), other == name);
+                }
+            }
+        };
+    }
+
+    #[test]
+    fn test_named_bits() {
+        check_named_bits!(Lights, lights);
+        check_named_bits!(Switches, switches);
+
+        let status = Status {
+            lights: Lights::BRAKE | Lights::HAZARD,
+            switches: Switches::CONTACTOR,
+        };
+        assert_eq!(
+            status.encode(),
+            ([0b1000_0010, 0x00, 0x02, 0, 0, 0, 0, 0], 3)
+        );
+        assert_eq!(Lights::DEFINED, 0b1000_1111);
+
+        // Raw bits as text
+        let mut text = std::string::String::new();
+        status.write_compact(&mut text).unwrap();
+        assert_eq!(text, "Status lights=130 switches=512");
+        assert_eq!(
+            Status::COMPACT_LEN,
+            "Status lights=255 switches=65535".len()
+        );
+    }
+
+    #[test]
+    fn test_undefined_preserved() {
+        let data = [0b0111_0001, 0xFF, 0xFF];
+        let status = Status::decode(&data).unwrap();
+
+        assert_eq!(status.lights.undefined(), 0b0111_0000);
+        assert!(status.lights.contains(Lights::HEADLIGHTS));
+        assert_eq!(status.switches.undefined(), !Switches::DEFINED);
+
+        let (out, len) = status.encode();
+        assert_eq!(out[..len], data);
+
+        // Built from named flags only, so never set
+        let mut lights = Lights::empty();
+        for &(_, flag) in Lights::ALL {
+            lights |= flag;
+        }
+        assert_eq!(lights.undefined(), 0);
+        lights.set(Lights::BRAKE | Lights::HAZARD, false);
+        assert_eq!(
+            lights,
+            Lights::HEADLIGHTS | Lights::LEFT_TURN | Lights::RIGHT_TURN
+        );
+    }
+
+    #[test]
+    fn test_signal() {
+        let [lights, switches] = Status::SIGNALS else {
+            panic!()
+        };
+        assert_eq!((lights.start, lights.size), (0, 1));
+        assert_eq!((switches.start, switches.size), (1, 2));
+        assert!(Status::decode(&[0, 0]).is_err());
+    }
+}
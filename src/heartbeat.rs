@@ -0,0 +1,89 @@
+use crate::Heartbeat;
+
+crate::isc_enum! {
+    /// A node that sends a [`Heartbeat`].
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+    pub enum NodeId: u8 as byte {
+        InterfaceBoard = 0,
+        Steering = 1,
+        Brake = 2,
+        Drive = 3,
+        Encoder = 4,
+    }
+}
+
+crate::isc_flags! {
+    /// Faults reported in a [`Heartbeat`]. A node with none set is healthy.
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+    pub struct Faults: u8 as byte {
+        /// A motor is commanded but not moving.
+        MOTOR_STALL = 0,
+        OVER_TEMPERATURE = 1,
+        UNDER_VOLTAGE = 2,
+        /// The node saw CAN errors, or went bus off since its last heartbeat.
+        BUS_ERROR = 3,
+        /// Commands stopped coming in time, so the node went to its safe state.
+        WATCHDOG = 4,
+        /// A sensor reads out of range, or not at all.
+        SENSOR = 5,
+    }
+}
+
+impl Heartbeat {
+    /// True if the node reports `fault`, or every fault in it if several.
+    pub const fn has_fault(&self, fault: Faults) -> bool {
+        self.faults.contains(fault)
+    }
+
+    /// True if the node reports no faults, including undefined ones.
+    pub const fn is_healthy(&self) -> bool {
+        self.faults.bits() == 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{CanMessage, ConvertErr, IscFrame};
+
+    #[test]
+    fn test_node_id() {
+        for &node in NodeId::ALL {
+            assert_eq!(NodeId::from_raw(node.raw()), Some(node));
+        }
+        assert_eq!(NodeId::COMPACT_LEN, "InterfaceBoard".len());
+        assert_eq!(NodeId::from_raw(5), None);
+        assert_eq!(NodeId::from_raw(0xFF), None);
+    }
+
+    #[test]
+    fn test_faults() {
+        let beat = Heartbeat {
+            node: NodeId::Brake,
+            faults: Faults::MOTOR_STALL | Faults::WATCHDOG,
+            uptime: 12,
+        };
+        assert!(beat.has_fault(Faults::MOTOR_STALL));
+        assert!(beat.has_fault(Faults::MOTOR_STALL | Faults::WATCHDOG));
+        assert!(!beat.has_fault(Faults::SENSOR));
+        assert!(!beat.is_healthy());
+        assert_eq!(beat.encode(), ([2, 0b1_0001, 12, 0, 0, 0, 0, 0], 4));
+
+        // Undefined bits from newer firmware are kept, and still unhealthy
+        let newer = Heartbeat::decode(&[1, 0x80, 0, 0]).unwrap();
+        assert_eq!(newer.faults.undefined(), 0x80);
+        assert!(!newer.is_healthy());
+    }
+
+    #[test]
+    fn test_unknown_node() {
+        for raw in [5, 0x80, 0xFF] {
+            assert!(matches!(
+                CanMessage::from_parts(Heartbeat::ID, &[raw, 0, 0, 0]),
+                Err(ConvertErr::InvalidFrame)
+            ));
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! Older layouts of messages, still decoded so nodes on older firmware are understood. Each is
+//! given to its message with `#[isc(legacy = ...)]`, and is not registered itself.
+
+use crate::{ConvertErr, IscFrame};
+use embedded_hal::can::Frame;
+
+/// [`SetBrake`](crate::SetBrake) as older firmware sends it, in whole percents.
+#[cfg(feature = "msgs-brake")]
+#[derive(IscFrame, Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[isc(id = 0x0000001)]
+pub struct SetBrakeLegacy {
+    #[isc(wire = byte, max = crate::MAX_PERCENT)]
+    pub percent: u8,
+}
+
+#[cfg(feature = "msgs-brake")]
+impl From<SetBrakeLegacy> for crate::SetBrake {
+    fn from(legacy: SetBrakeLegacy) -> Self {
+        Self {
+            centi_percent: legacy.percent as u16 * 100,
+        }
+    }
+}
+
+/// Rounds to the nearest whole percent.
+#[cfg(feature = "msgs-brake")]
+impl From<crate::SetBrake> for SetBrakeLegacy {
+    fn from(msg: crate::SetBrake) -> Self {
+        Self {
+            percent: whole_percent(msg.centi_percent),
+        }
+    }
+}
+
+#[cfg(feature = "msgs-brake")]
+impl crate::SetBrake {
+    /// Converts self into a CAN frame in the legacy layout, for nodes on older firmware. This
+    /// rounds to the nearest whole percent.
+    pub fn into_frame_legacy<T: Frame>(self) -> Result<T, ConvertErr> {
+        SetBrakeLegacy::from(self).into_frame()
+    }
+}
+
+/// [`SetSpeed`](crate::SetSpeed) as older firmware sends it, in whole percents.
+#[cfg(feature = "msgs-drive")]
+#[derive(IscFrame, Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[isc(id = 0x0000006)]
+pub struct SetSpeedLegacy {
+    #[isc(wire = byte, max = crate::MAX_PERCENT)]
+    pub percent: u8,
+}
+
+#[cfg(feature = "msgs-drive")]
+impl From<SetSpeedLegacy> for crate::SetSpeed {
+    fn from(legacy: SetSpeedLegacy) -> Self {
+        Self {
+            centi_percent: legacy.percent as u16 * 100,
+        }
+    }
+}
+
+/// Rounds to the nearest whole percent.
+#[cfg(feature = "msgs-drive")]
+impl From<crate::SetSpeed> for SetSpeedLegacy {
+    fn from(msg: crate::SetSpeed) -> Self {
+        Self {
+            percent: whole_percent(msg.centi_percent),
+        }
+    }
+}
+
+#[cfg(feature = "msgs-drive")]
+impl crate::SetSpeed {
+    /// Converts self into a CAN frame in the legacy layout, for nodes on older firmware. This
+    /// rounds to the nearest whole percent.
+    pub fn into_frame_legacy<T: Frame>(self) -> Result<T, ConvertErr> {
+        SetSpeedLegacy::from(self).into_frame()
+    }
+}
+
+fn whole_percent(centi_percent: u16) -> u8 {
+    ((centi_percent as u32 + 50) / 100).min(u8::MAX as u32) as u8
+}
+
+#[cfg(all(test, feature = "msgs-all"))]
+mod test {
+    use super::*;
+    use crate::testing::{MockFrame, GOLDEN, LEGACY_GOLDEN};
+    use crate::*;
+
+    #[test]
+    fn test_legacy_vectors() {
+        for golden in LEGACY_GOLDEN {
+            let frame = MockFrame::extended(golden.id, golden.data);
+            let parts = golden.msg.to_parts().unwrap();
+            let owned = CanMessage::from_frame(frame).unwrap();
+            let viewed = CanMessageRef::from_frame(&frame).unwrap().into_owned();
+            assert_eq!(owned.to_parts().unwrap(), parts, "{golden:?}");
+            assert_eq!(viewed.to_parts().unwrap(), parts, "{golden:?}");
+
+            // The same percent as the whole percent sent
+            let percent = match owned {
+                CanMessage::SetBrake(b) => b.percent(),
+                CanMessage::SetSpeed(s) => s.percent(),
+                _ => panic!("{golden:?}"),
+            };
+            assert_eq!(percent, golden.data[0] as f32, "{golden:?}");
+        }
+    }
+
+    #[test]
+    fn test_both_lengths() {
+        let frame: MockFrame = SetBrake {
+            centi_percent: 4250,
+        }
+        .into_frame()
+        .unwrap();
+        assert_eq!(frame.data(), [0x9A, 0x10]);
+        let legacy: MockFrame = SetBrake {
+            centi_percent: 4250,
+        }
+        .into_frame_legacy()
+        .unwrap();
+        assert_eq!(legacy.data(), [43]);
+
+        let view = SetBrakeRef::new(legacy.data()).unwrap();
+        assert_eq!(view.centi_percent(), 4300);
+        assert_eq!(SetBrake::decode(frame.data()).unwrap().percent(), 42.5);
+        assert_eq!(SetBrake::decode(legacy.data()).unwrap().percent(), 43.0);
+
+        // Rounded to the nearest whole percent
+        for (centi_percent, percent) in [(0, 0), (49, 0), (50, 1), (9_950, 100), (u16::MAX, 255)] {
+            assert_eq!(
+                SetSpeedLegacy::from(SetSpeed { centi_percent }).percent,
+                percent
+            );
+        }
+
+        // Other lengths are still errors, reporting the current DLC
+        assert!(matches!(
+            SetSpeed::decode(&[0; 3]),
+            Err(ConvertErr::WrongLength {
+                expected: 2,
+                got: 3
+            })
+        ));
+        assert_eq!(SetSpeed::LEGACY_DLC, Some(1));
+        assert_eq!(SetBrakeLegacy::LEGACY_DLC, None);
+    }
+
+    #[test]
+    fn test_max() {
+        let max = SetSpeed {
+            centi_percent: MAX_CENTI_PERCENT,
+        };
+        assert_eq!(max.encode(), ([0x10, 0x27, 0, 0, 0, 0, 0, 0], 2));
+        assert_eq!(max.percent(), 100.0);
+        assert_eq!(SetSpeed::decode(&[0x10, 0x27]).unwrap(), max);
+        assert_eq!(SetSpeed::decode(&[100]).unwrap(), max);
+        let legacy: MockFrame = max.into_frame_legacy().unwrap();
+        assert_eq!(legacy.data(), [100]);
+
+        // Past it, in either layout
+        for data in [&[0x11, 0x27][..], &[0xFF, 0xFF], &[101], &[0xFF]] {
+            assert!(matches!(
+                SetSpeed::decode(data),
+                Err(ConvertErr::OutOfRange {
+                    field: "centi_percent" | "percent"
+                })
+            ));
+        }
+        let raw = SetBrake::decode_with(&[0xFF], DecodePolicy::Raw).unwrap();
+        assert_eq!(raw.centi_percent, 25_500);
+    }
+
+    #[test]
+    fn test_current_golden() {
+        // Every current vector goes back to whole percents, to the nearest
+        for golden in GOLDEN {
+            let legacy = match golden.msg {
+                CanMessage::SetBrake(b) => SetBrakeLegacy::from(b).percent,
+                CanMessage::SetSpeed(s) => SetSpeedLegacy::from(s).percent,
+                _ => continue,
+            };
+            let percent = match golden.msg {
+                CanMessage::SetBrake(b) => b.percent(),
+                CanMessage::SetSpeed(s) => s.percent(),
+                _ => unreachable!(),
+            };
+            assert!((legacy as f32 - percent).abs() <= 0.5, "{golden:?}");
+        }
+    }
+}
@@ -1,7 +1,108 @@
 #![doc = include_str!("../README.md")]
-
 #![no_std]
 
+// Lets derives refer to this crate by name from inside it
+extern crate self as phnx_candefs;
+
+#[cfg(feature = "msgs-steering")]
+mod ackermann;
+#[cfg(all(
+    feature = "msgs-steering",
+    feature = "msgs-drive",
+    feature = "msgs-brake"
+))]
+mod arbiter;
+mod auton;
+mod batch;
+#[cfg(feature = "msgs-brake")]
+mod brake_gate;
+pub mod codec;
+#[cfg(feature = "msgs-steering")]
+mod compact;
+#[cfg(test)]
+mod completeness;
+#[cfg(feature = "msgs-steering")]
+mod convention;
+mod dbc;
+#[cfg(feature = "msgs-steering")]
+mod deadband;
+mod direction;
+mod enums;
+mod estop;
+mod flags;
+mod heartbeat;
+#[cfg(any(feature = "msgs-brake", feature = "msgs-drive"))]
+mod legacy;
+mod macros;
+#[cfg(all(feature = "msgs-steering", feature = "msgs-drive"))]
+mod maneuver;
 mod messages;
+mod mux;
+mod policy;
+mod range;
+mod registry;
+#[cfg(all(feature = "msgs-drive", feature = "msgs-brake"))]
+mod safe_stop;
+mod signal;
+#[cfg(feature = "msgs-steering")]
+mod slew;
+#[cfg(feature = "socketcan")]
+mod socketcan;
+#[cfg(all(feature = "msgs-drive", feature = "msgs-brake"))]
+mod stopping;
+mod table;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+#[cfg(feature = "msgs-drive")]
+mod ticks;
+#[cfg(feature = "msgs-drive")]
+mod velocity;
+#[cfg(all(any(test, kani), feature = "msgs-all"))]
+mod verify;
+
+#[cfg(all(feature = "uom", feature = "msgs-steering", feature = "msgs-drive"))]
+mod units;
 
+#[cfg(feature = "msgs-steering")]
+pub use ackermann::*;
+#[cfg(all(
+    feature = "msgs-steering",
+    feature = "msgs-drive",
+    feature = "msgs-brake"
+))]
+pub use arbiter::*;
+pub use auton::*;
+pub use batch::*;
+#[cfg(feature = "msgs-brake")]
+pub use brake_gate::*;
+#[cfg(feature = "msgs-steering")]
+pub use compact::*;
+#[cfg(feature = "msgs-steering")]
+pub use convention::*;
+pub use dbc::*;
+#[cfg(feature = "msgs-steering")]
+pub use deadband::*;
+pub use direction::*;
+pub use estop::*;
+pub use heartbeat::*;
+#[cfg(any(feature = "msgs-brake", feature = "msgs-drive"))]
+pub use legacy::*;
+#[cfg(all(feature = "msgs-steering", feature = "msgs-drive"))]
+pub use maneuver::*;
 pub use messages::*;
+pub use mux::*;
+pub use policy::*;
+pub use range::*;
+pub use registry::*;
+#[cfg(all(feature = "msgs-drive", feature = "msgs-brake"))]
+pub use safe_stop::*;
+pub use signal::*;
+#[cfg(feature = "msgs-steering")]
+pub use slew::*;
+#[cfg(all(feature = "msgs-drive", feature = "msgs-brake"))]
+pub use stopping::*;
+pub use table::*;
+#[cfg(feature = "msgs-drive")]
+pub use ticks::*;
+#[cfg(feature = "msgs-drive")]
+pub use velocity::*;
@@ -0,0 +1,338 @@
+/// Defines every message from a single list.
+///
+/// Each entry gives the docs, derives, name, ID, and fields of a message, with the encoding of
+/// each field from [`codec`](crate::codec), or a path to another such as an
+/// [`isc_flags!`](crate::isc_flags) type. Fields are packed in order from the start of the
+/// payload, and may have further `#[isc(...)]` attributes for scaling. Message level
+/// `#[isc(...)]` attributes, such as `legacy`, go after the derives. This expands to the
+/// structs with a derived [`IscFrame`](crate::IscFrame), the [`CanMessage`](crate::CanMessage)
+/// enum, its `From` impls, the [`REGISTRY`](crate::REGISTRY) used to decode them, the
+/// [`CanMessageRef`](crate::CanMessageRef) views, the [`MessageKind`](crate::MessageKind) of
+/// each, and the [`MESSAGES`](crate::MESSAGES) descriptions, so none of these can disagree.
+/// Entries can be listed in any order.
+///
+/// Messages defined elsewhere, such as by `isc_mux!`, are listed by name in a trailing
+/// `extern { ... }` block with their docs, and are registered the same way.
+///
+/// An entry may start with a `#[cfg(...)]`, which gates the message everywhere it appears, so
+/// a disabled message is an unknown ID to the registry, and naming it does not compile.
+///
+/// Duplicate IDs are rejected at compile time by the registry, as are IDs that do not fit in an
+/// extended frame and payloads over 8 bytes by the derive.
+macro_rules! isc_message {
+    (
+        $(
+            $(#[cfg($cfg:meta)])?
+            $(#[doc = $doc:literal])+
+            #[derive($($derive:ident),*)]
+            $(#[isc($($isc:tt)*)])*
+            $name:ident = $id:literal {
+                $(
+                    $(#[$fmeta:meta])*
+                    pub $field:ident: $ty:ty as $codec:path
+                ),* $(,)?
+            }
+        ),+ $(,)?
+        $(extern {
+            $(
+                $(#[cfg($ecfg:meta)])?
+                $(#[doc = $edoc:literal])+
+                $ename:ident
+            ),* $(,)?
+        })?
+    ) => {
+        $(
+            $(#[cfg($cfg)])?
+            $(#[doc = $doc])+
+            #[derive($($derive),*)]
+            #[derive($crate::IscFrame)]
+            #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+            #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+            #[isc(id = $id, display)]
+            $(#[isc($($isc)*)])*
+            pub struct $name {
+                $(
+                    $(#[$fmeta])*
+                    #[isc(wire = $codec)]
+                    pub $field: $ty,
+                )*
+            }
+        )+
+
+        $crate::macros::isc_message!(@registry
+            $($(#[cfg($cfg)])? $(#[doc = $doc])+ $name,)+
+            $($($(#[cfg($ecfg)])? $(#[doc = $edoc])+ $ename,)*)?
+        );
+    };
+
+    (@registry $($(#[cfg($cfg:meta)])? $(#[doc = $doc:literal])+ $name:ident,)+) => {
+        $(
+            $(#[cfg($cfg)])?
+            impl From<$name> for $crate::CanMessage {
+                fn from(msg: $name) -> Self {
+                    $crate::CanMessage::$name(msg)
+                }
+            }
+        )+
+
+        /// All messages used in Phoenix. With the `serde` feature, serialized tagged by message
+        /// name, like `{"SetAngle":{"angle":4.8}}`.
+        #[derive(Copy, Clone, Debug)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+        pub enum CanMessage {
+            $(
+                $(#[cfg($cfg)])?
+                $(#[doc = $doc])+
+                $name($name),
+            )+
+        }
+
+        /// Writes the message like its own `Display`, such as `SetAngle(angle=4.82°)`.
+        impl core::fmt::Display for CanMessage {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                match self {
+                    $(
+                        $(#[cfg($cfg)])?
+                        CanMessage::$name(msg) => core::fmt::Display::fmt(msg, f),
+                    )+
+                }
+            }
+        }
+
+        impl CanMessage {
+            /// Longest output of [`write_compact`](CanMessage::write_compact) over every
+            /// message, for sizing buffers. Each message's own is its `IscFrame::COMPACT_LEN`.
+            pub const COMPACT_LEN: usize = $crate::mux::max(&[$(
+                $(#[cfg($cfg)])?
+                <$name as $crate::IscFrame>::COMPACT_LEN,
+            )+]);
+
+            /// Writes the message as short text, like `IscFrame::write_compact`.
+            pub fn write_compact<W: core::fmt::Write>(&self, out: &mut W) -> core::fmt::Result {
+                match self {
+                    $(
+                        $(#[cfg($cfg)])?
+                        CanMessage::$name(msg) => $crate::IscFrame::write_compact(msg, out),
+                    )+
+                }
+            }
+
+            /// Converts an ID and payload like `from_parts`, with floats read under `policy`.
+            pub fn from_parts_with_policy(
+                id: u32,
+                data: &[u8],
+                policy: $crate::DecodePolicy,
+            ) -> Result<Self, $crate::ConvertErr> {
+                use $crate::IscFrame;
+
+                match id {
+                    $(
+                        $(#[cfg($cfg)])?
+                        <$name as IscFrame>::ID => {
+                            $name::decode_with(data, policy).map(CanMessage::$name)
+                        }
+                    )+
+                    _ => Err($crate::ConvertErr::UnknownId { id }),
+                }
+            }
+
+            /// Checks the message is in range, like `IscFrame::check_range`.
+            pub fn check_range(&self) -> Result<(), $crate::RangeErr> {
+                match self {
+                    $(
+                        $(#[cfg($cfg)])?
+                        CanMessage::$name(msg) => $crate::IscFrame::check_range(msg),
+                    )+
+                }
+            }
+
+            /// Which message this is, without its fields.
+            pub const fn kind(&self) -> MessageKind {
+                match self {
+                    $(
+                        $(#[cfg($cfg)])?
+                        CanMessage::$name(_) => MessageKind::$name,
+                    )+
+                }
+            }
+
+            /// The ID of the message, like `IscFrame::ID`.
+            pub fn id(&self) -> u32 {
+                match self {
+                    $(
+                        $(#[cfg($cfg)])?
+                        CanMessage::$name(_) => <$name as $crate::IscFrame>::ID,
+                    )+
+                }
+            }
+
+            /// Encodes the message with its ID, like `IscFrame::to_parts`.
+            pub fn to_parts(self) -> Result<$crate::FrameParts, $crate::ConvertErr> {
+                match self {
+                    $(
+                        $(#[cfg($cfg)])?
+                        CanMessage::$name(msg) => $crate::IscFrame::to_parts(&msg),
+                    )+
+                }
+            }
+
+            /// Converts the message into a CAN frame, like `IscFrame::into_frame`.
+            #[inline]
+            pub fn into_frame<T: embedded_hal::can::Frame>(self) -> Result<T, $crate::ConvertErr> {
+                $crate::frame_from_parts(self.to_parts()?)
+            }
+        }
+
+        /// Which message a frame holds, without decoding it, for firmware that only handles a
+        /// few. Each message can then be decoded alone, with `IscFrame::try_from_frame`.
+        #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+        pub enum MessageKind {
+            $(
+                $(#[cfg($cfg)])?
+                $(#[doc = $doc])+
+                $name,
+            )+
+        }
+
+        impl MessageKind {
+            /// Every message compiled in, in the order they are defined.
+            pub const ALL: [MessageKind; COUNT] = [$($(#[cfg($cfg)])? MessageKind::$name),+];
+
+            /// The message with an ID, if it is defined and compiled in.
+            pub const fn from_id(id: u32) -> Option<Self> {
+                match id {
+                    $(
+                        $(#[cfg($cfg)])?
+                        <$name as $crate::IscFrame>::ID => Some(MessageKind::$name),
+                    )+
+                    _ => None,
+                }
+            }
+
+            /// The ID of the message, like `IscFrame::ID`.
+            pub const fn id(self) -> u32 {
+                match self {
+                    $(
+                        $(#[cfg($cfg)])?
+                        MessageKind::$name => <$name as $crate::IscFrame>::ID,
+                    )+
+                }
+            }
+
+            /// The name of the message, like `IscFrame::NAME`.
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $(
+                        $(#[cfg($cfg)])?
+                        MessageKind::$name => <$name as $crate::IscFrame>::NAME,
+                    )+
+                }
+            }
+        }
+
+        /// Borrowed views of all messages used in Phoenix.
+        #[derive(Copy, Clone, Debug)]
+        pub enum CanMessageRef<'a> {
+            $(
+                $(#[cfg($cfg)])?
+                $(#[doc = $doc])+
+                $name(<$name as $crate::IscView>::Ref<'a>),
+            )+
+        }
+
+        impl<'a> CanMessageRef<'a> {
+            /// Views a payload with an ID without copying it. Errors like
+            /// `CanMessage::from_frame`.
+            pub fn from_parts(id: u32, data: &'a [u8]) -> Result<Self, $crate::ConvertErr> {
+                use $crate::{IscFrame, IscView};
+
+                match id {
+                    $(
+                        $(#[cfg($cfg)])?
+                        <$name as IscFrame>::ID => $name::view(data).map(CanMessageRef::$name),
+                    )+
+                    _ => Err($crate::ConvertErr::UnknownId { id }),
+                }
+            }
+
+            /// Views a CAN frame without copying its payload.
+            pub fn from_frame<F: embedded_hal::can::Frame>(
+                frame: &'a F,
+            ) -> Result<Self, $crate::ConvertErr> {
+                match frame.id() {
+                    embedded_hal::can::Id::Extended(id) => Self::from_parts(id.as_raw(), frame.data()),
+                    _ => Err($crate::ConvertErr::InvalidFrame),
+                }
+            }
+
+            /// Which message this views.
+            pub const fn kind(&self) -> MessageKind {
+                match self {
+                    $(
+                        $(#[cfg($cfg)])?
+                        CanMessageRef::$name(_) => MessageKind::$name,
+                    )+
+                }
+            }
+
+            /// The payload this views.
+            pub fn as_bytes(&self) -> &'a [u8] {
+                match self {
+                    $(
+                        $(#[cfg($cfg)])?
+                        CanMessageRef::$name(view) => view.as_bytes(),
+                    )+
+                }
+            }
+
+            /// Decodes the full message.
+            pub fn into_owned(self) -> CanMessage {
+                match self {
+                    $(
+                        $(#[cfg($cfg)])?
+                        CanMessageRef::$name(view) => CanMessage::$name(view.into()),
+                    )+
+                }
+            }
+        }
+
+        /// Number of messages compiled in.
+        const COUNT: usize = [$($(#[cfg($cfg)])? stringify!($name)),+].len();
+
+        const ENTRIES: [$crate::DecodeEntry<CanMessage>; COUNT] =
+            $crate::sort_entries([$(
+                $(#[cfg($cfg)])?
+                $crate::DecodeEntry {
+                    id: <$name as $crate::IscFrame>::ID,
+                    dlc: <$name as $crate::IscFrame>::DLC,
+                    decode: |data| {
+                        <$name as $crate::IscFrame>::decode(data).map(CanMessage::$name)
+                    },
+                },
+            )+]);
+
+        /// Decoders for every message, used by `CanMessage::from_frame`.
+        pub static REGISTRY: $crate::Registry<'static, CanMessage> =
+            $crate::Registry::new(&ENTRIES);
+
+        /// Descriptions of every message, in the order they are defined, for
+        /// [`write_dbc`](crate::write_dbc).
+        pub static MESSAGES: [$crate::MessageInfo; COUNT] = [$(
+            $(#[cfg($cfg)])?
+            $crate::MessageInfo::of::<$name>().with_doc({
+                const DOC: &str = concat!($($doc, "\n"),+);
+                const PARAGRAPH: &$crate::DocParagraph<{ DOC.len() }> =
+                    &$crate::DocParagraph::new(DOC);
+                PARAGRAPH.as_str()
+            }),
+        )+];
+
+        /// ID of every message, in the order they are defined.
+        pub const ALL_IDS: [u32; COUNT] = [$($(#[cfg($cfg)])? <$name as $crate::IscFrame>::ID),+];
+    };
+}
+
+pub(crate) use isc_message;
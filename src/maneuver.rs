@@ -0,0 +1,345 @@
+use crate::{CanMessage, SetAngle, SetSpeed, SteeringSlewLimiter, MAX_ANGLE, MAX_CENTI_PERCENT};
+
+/// One part of a [`Maneuver`].
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+pub enum Segment {
+    /// Hold a steering angle in degrees and a speed percent. Angles past [`MAX_ANGLE`] either
+    /// way are sent as it, and speeds over 100 as 100.
+    Hold {
+        angle: f32,
+        speed: u8,
+        duration_ms: u32,
+    },
+    /// Linearly ramp from the end of the previous segment to these values.
+    /// The first segment ramps from zero angle and zero speed.
+    Ramp {
+        angle: f32,
+        speed: u8,
+        duration_ms: u32,
+    },
+}
+
+/// Rate limits applied when sampling a [`Maneuver`].
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+pub struct ManeuverLimits {
+    pub max_steer_deg_per_s: f32,
+    pub max_speed_pct_per_s: f32,
+}
+
+/// An open loop test maneuver, described as a sequence of segments.
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+pub struct Maneuver<'a> {
+    pub segments: &'a [Segment],
+    pub limits: ManeuverLimits,
+}
+
+impl Segment {
+    fn end(&self) -> (f32, f32) {
+        match *self {
+            Segment::Hold { angle, speed, .. } | Segment::Ramp { angle, speed, .. } => {
+                (angle, speed as f32)
+            }
+        }
+    }
+
+    fn duration_ms(&self) -> u32 {
+        match *self {
+            Segment::Hold { duration_ms, .. } | Segment::Ramp { duration_ms, .. } => duration_ms,
+        }
+    }
+}
+
+impl<'a> Maneuver<'a> {
+    /// Total duration of all segments, in ms.
+    pub fn duration_ms(&self) -> u32 {
+        self.segments
+            .iter()
+            .fold(0u32, |acc, s| acc.saturating_add(s.duration_ms()))
+    }
+
+    /// Generates the commands for this maneuver, sampled every `period_ms`.
+    ///
+    /// Each sample yields a SetAngle followed by a SetSpeed, both tagged with the sample time in
+    /// ms since the start. Samples are taken from zero until the end of the last segment, with
+    /// steering slew and throttle ramp limits applied starting from rest. The output only depends
+    /// on the maneuver and period, so it is identical every time.
+    pub fn sample(&self, period_ms: u32) -> impl Iterator<Item = (u32, CanMessage)> + 'a {
+        let maneuver = *self;
+        let dt_s = period_ms as f32 / 1000.0;
+        let mut slew = SteeringSlewLimiter::new(self.limits.max_steer_deg_per_s);
+        let mut speed = 0.0f32;
+
+        let times = core::iter::successors(Some(0u32), move |t| t.checked_add(period_ms))
+            .take_while(move |&t| period_ms > 0 && t < maneuver.duration_ms());
+
+        times.flat_map(move |t| {
+            let (target_angle, target_speed) = maneuver.target_at(t);
+            let dt_s = if t == 0 { 0.0 } else { dt_s };
+
+            // Within what SetAngle allows, like the speed below, with NaN as straight ahead
+            let target_angle = if target_angle.is_nan() {
+                0.0
+            } else {
+                target_angle.clamp(-MAX_ANGLE, MAX_ANGLE)
+            };
+
+            let angle = slew.next(
+                &SetAngle {
+                    angle: target_angle,
+                },
+                dt_s,
+            );
+
+            // A zero dt holds speed, even with an infinite rate
+            let max_step = if dt_s > 0.0 {
+                maneuver.limits.max_speed_pct_per_s.max(0.0) * dt_s
+            } else {
+                0.0
+            };
+            speed += (target_speed - speed).max(-max_step).min(max_step);
+            // Within what SetSpeed allows, so receivers never reject a sample
+            let centi_percent = (speed * 100.0 + 0.5).clamp(0.0, MAX_CENTI_PERCENT as f32) as u16;
+
+            [
+                (t, CanMessage::SetAngle(angle)),
+                (t, CanMessage::SetSpeed(SetSpeed { centi_percent })),
+            ]
+        })
+    }
+
+    /// Unlimited angle and speed targets at a time.
+    fn target_at(&self, t: u32) -> (f32, f32) {
+        let mut start = 0u32;
+        let mut from = (0.0, 0.0);
+
+        for seg in self.segments {
+            let end = start.saturating_add(seg.duration_ms());
+
+            if t < end {
+                return match *seg {
+                    Segment::Hold { .. } => seg.end(),
+                    Segment::Ramp { duration_ms, .. } => {
+                        let frac = (t - start) as f32 / duration_ms as f32;
+                        let to = seg.end();
+                        (
+                            from.0 + (to.0 - from.0) * frac,
+                            from.1 + (to.1 - from.1) * frac,
+                        )
+                    }
+                };
+            }
+
+            from = seg.end();
+            start = end;
+        }
+
+        from
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const LANE_CHANGE: [Segment; 4] = [
+        Segment::Hold {
+            angle: 0.0,
+            speed: 20,
+            duration_ms: 300,
+        },
+        Segment::Ramp {
+            angle: 4.0,
+            speed: 20,
+            duration_ms: 400,
+        },
+        Segment::Ramp {
+            angle: -4.0,
+            speed: 20,
+            duration_ms: 400,
+        },
+        Segment::Ramp {
+            angle: 0.0,
+            speed: 20,
+            duration_ms: 400,
+        },
+    ];
+
+    const LIMITS: ManeuverLimits = ManeuverLimits {
+        max_steer_deg_per_s: 15.0,
+        max_speed_pct_per_s: 75.0,
+    };
+
+    fn unpack<'a>(
+        maneuver: &Maneuver<'a>,
+        period_ms: u32,
+    ) -> impl Iterator<Item = (u32, f32, u16)> + 'a {
+        let mut out = maneuver.sample(period_ms);
+        core::iter::from_fn(move || {
+            let (t, angle) = out.next()?;
+            let (t2, speed) = out.next()?;
+            assert_eq!(t, t2);
+
+            match (angle, speed) {
+                (CanMessage::SetAngle(a), CanMessage::SetSpeed(s)) => {
+                    Some((t, a.angle, s.centi_percent))
+                }
+                _ => panic!("{angle:?} {speed:?}"),
+            }
+        })
+    }
+
+    #[test]
+    fn test_lane_change_pinned() {
+        let maneuver = Maneuver {
+            segments: &LANE_CHANGE,
+            limits: LIMITS,
+        };
+        assert_eq!(maneuver.duration_ms(), 1500);
+
+        let expected: [(u32, f32, u16); 15] = [
+            (0, 0.0, 0),
+            (100, 0.0, 750),
+            (200, 0.0, 1500),
+            (300, 0.0, 2000),
+            (400, 1.0, 2000),
+            (500, 2.0, 2000),
+            (600, 3.0, 2000),
+            (700, 4.0, 2000),
+            (800, 2.5, 2000),
+            (900, 1.0, 2000),
+            (1000, -0.5, 2000),
+            (1100, -2.0, 2000),
+            (1200, -3.0, 2000),
+            (1300, -2.0, 2000),
+            (1400, -1.0, 2000),
+        ];
+
+        for ((t, angle, speed), (et, ea, es)) in unpack(&maneuver, 100).zip(expected) {
+            assert_eq!(t, et);
+            assert!((angle - ea).abs() < 1e-4, "{t}: {angle} != {ea}");
+            assert_eq!(speed, es, "{t}");
+        }
+        assert_eq!(unpack(&maneuver, 100).count(), expected.len());
+
+        // Identical each time
+        assert!(unpack(&maneuver, 100).eq(unpack(&maneuver, 100)));
+    }
+
+    #[test]
+    fn test_limits_applied() {
+        let step = [Segment::Hold {
+            angle: 20.0,
+            speed: 100,
+            duration_ms: 2000,
+        }];
+        let maneuver = Maneuver {
+            segments: &step,
+            limits: LIMITS,
+        };
+
+        let mut last = (0.0, 0);
+        for (_, angle, speed) in unpack(&maneuver, 20) {
+            assert!(angle - last.0 <= 15.0 * 0.02 + 1e-4);
+            assert!(speed as f32 - last.1 as f32 <= 7500.0 * 0.02 + 1.0);
+            last = (angle, speed);
+        }
+        assert_eq!(last, (20.0, 10_000));
+
+        assert_eq!(maneuver.sample(0).count(), 0);
+    }
+
+    #[test]
+    fn test_speed_clamped() {
+        let over = [
+            Segment::Ramp {
+                angle: 0.0,
+                speed: 255,
+                duration_ms: 500,
+            },
+            Segment::Hold {
+                angle: 0.0,
+                speed: 101,
+                duration_ms: 500,
+            },
+        ];
+        let maneuver = Maneuver {
+            segments: &over,
+            limits: ManeuverLimits {
+                max_steer_deg_per_s: f32::INFINITY,
+                max_speed_pct_per_s: f32::INFINITY,
+            },
+        };
+
+        let mut count = 0;
+        for (t, msg) in maneuver.sample(50) {
+            if let CanMessage::SetSpeed(s) = msg {
+                assert!(s.centi_percent <= MAX_CENTI_PERCENT, "{t}: {s:?}");
+                assert!(CanMessage::SetSpeed(s).check_range().is_ok());
+                count += 1;
+            }
+        }
+        assert_eq!(count, 20);
+        assert_eq!(unpack(&maneuver, 50).last().unwrap().2, MAX_CENTI_PERCENT);
+    }
+
+    #[test]
+    fn test_angle_clamped() {
+        let over = [
+            Segment::Ramp {
+                angle: MAX_ANGLE * 3.0,
+                speed: 20,
+                duration_ms: 500,
+            },
+            Segment::Hold {
+                angle: -MAX_ANGLE - 1.0,
+                speed: 20,
+                duration_ms: 500,
+            },
+            Segment::Hold {
+                angle: f32::NAN,
+                speed: 20,
+                duration_ms: 500,
+            },
+        ];
+        let maneuver = Maneuver {
+            segments: &over,
+            limits: ManeuverLimits {
+                max_steer_deg_per_s: f32::INFINITY,
+                max_speed_pct_per_s: f32::INFINITY,
+            },
+        };
+
+        let mut out = unpack(&maneuver, 50);
+        let angles: [f32; 30] = core::array::from_fn(|_| out.next().unwrap().1);
+        assert!(out.next().is_none());
+        for (t, msg) in maneuver.sample(50) {
+            assert!(msg.check_range().is_ok(), "{t}: {msg:?}");
+        }
+        assert_eq!(angles[9], MAX_ANGLE);
+        assert_eq!(angles[19], -MAX_ANGLE);
+        assert_eq!(angles[29], 0.0);
+    }
+
+    #[test]
+    fn test_unlimited_rate() {
+        let step = [Segment::Hold {
+            angle: 20.0,
+            speed: 100,
+            duration_ms: 300,
+        }];
+        let maneuver = Maneuver {
+            segments: &step,
+            limits: ManeuverLimits {
+                max_steer_deg_per_s: f32::INFINITY,
+                max_speed_pct_per_s: f32::INFINITY,
+            },
+        };
+
+        // Still starts from rest, then jumps
+        let samples: [_; 3] = core::array::from_fn({
+            let mut out = unpack(&maneuver, 100);
+            move |_| out.next().map(|(_, _, speed)| speed)
+        });
+        assert_eq!(samples, [Some(0), Some(10_000), Some(10_000)]);
+    }
+}
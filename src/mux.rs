@@ -0,0 +1,409 @@
+use crate::{ConvertErr, IscFrame, Signal};
+
+/// A multiplexed message, where the first byte selects which page the rest of the payload is.
+///
+/// Each page is its own struct deriving [`IscFrame`] with `#[isc(mux = ...)]`, and the message
+/// is an enum over them. Pages are numbered from zero.
+pub trait Mux: IscFrame + Copy {
+    /// Number of pages.
+    const PAGES: usize;
+
+    /// Selector of this page.
+    fn page(&self) -> u8;
+}
+
+/// Defines a multiplexed message from its pages.
+///
+/// This expands to each page struct, the message enum over them, and its [`IscFrame`] and
+/// [`Mux`] impls. The message still needs listing as `extern` in `isc_message!`. Pages must be
+/// numbered from zero in order, and signal names must be unique across pages.
+#[cfg_attr(not(feature = "msgs-power"), allow(unused_macros))]
+macro_rules! isc_mux {
+    (
+        $(#[doc = $doc:literal])+
+        #[derive($($derive:ident),*)]
+        $name:ident = $id:literal {
+            $(
+                $(#[doc = $pdoc:literal])+
+                #[derive($($pderive:ident),*)]
+                $page:ident = $sel:literal {
+                    $(
+                        $(#[$fmeta:meta])*
+                        pub $field:ident: $ty:ty as $codec:path
+                    ),* $(,)?
+                }
+            ),+ $(,)?
+        }
+    ) => {
+        $(
+            $(#[doc = $pdoc])+
+            #[derive($($pderive),*)]
+            #[derive($crate::IscFrame)]
+            #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+            #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+            #[isc(id = $id, mux = $sel, display)]
+            pub struct $page {
+                $(
+                    $(#[$fmeta])*
+                    #[isc(wire = $codec)]
+                    pub $field: $ty,
+                )*
+            }
+
+            impl From<$page> for $name {
+                fn from(page: $page) -> Self {
+                    $name::$page(page)
+                }
+            }
+        )+
+
+        $(#[doc = $doc])+
+        #[derive($($derive),*)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+        pub enum $name {
+            $(
+                $(#[doc = $pdoc])+
+                $page($page),
+            )+
+        }
+
+        impl $crate::IscFrame for $name {
+            const NAME: &'static str = stringify!($name);
+            const ID: u32 = $id;
+            /// The longest page.
+            const DLC: usize = $crate::mux::max(&[$(<$page as $crate::IscFrame>::DLC),+]);
+            const SIGNALS: &'static [$crate::Signal] =
+                &$crate::mux::concat_signals::<{ 1 $(+ <$page as $crate::IscFrame>::SIGNALS.len())+ }>(
+                    &[&[$crate::Signal::SELECTOR], $(<$page as $crate::IscFrame>::SIGNALS),+],
+                );
+
+            fn encode(&self) -> ([u8; 8], usize) {
+                match self {
+                    $($name::$page(page) => $crate::IscFrame::encode(page),)+
+                }
+            }
+
+            fn decode(data: &[u8]) -> Result<Self, $crate::ConvertErr> {
+                Self::decode_with(data, $crate::DecodePolicy::Normalize)
+            }
+
+            fn decode_with(
+                data: &[u8],
+                policy: $crate::DecodePolicy,
+            ) -> Result<Self, $crate::ConvertErr> {
+                match data.first() {
+                    $(Some(&$sel) => {
+                        <$page as $crate::IscFrame>::decode_with(data, policy).map($name::$page)
+                    })+
+                    Some(_) => Err($crate::ConvertErr::InvalidFrame),
+                    None => Err($crate::ConvertErr::WrongLength {
+                        expected: <Self as $crate::IscFrame>::DLC,
+                        got: 0,
+                    }),
+                }
+            }
+
+            /// The longest page, which is written by name.
+            const COMPACT_LEN: usize =
+                $crate::mux::max(&[$(<$page as $crate::IscFrame>::COMPACT_LEN),+]);
+
+            fn write_compact<W: core::fmt::Write>(&self, out: &mut W) -> core::fmt::Result {
+                match self {
+                    $($name::$page(page) => $crate::IscFrame::write_compact(page, out),)+
+                }
+            }
+
+            fn check_range(&self) -> Result<(), $crate::RangeErr> {
+                match self {
+                    $($name::$page(page) => $crate::IscFrame::check_range(page),)+
+                }
+            }
+        }
+
+        /// Writes the page, like `CellVoltages0(cell_1=3.700 V, ...)`.
+        impl core::fmt::Display for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                match self {
+                    $($name::$page(page) => core::fmt::Display::fmt(page, f),)+
+                }
+            }
+        }
+
+        impl $crate::Mux for $name {
+            const PAGES: usize = [$($sel),+].len();
+
+            fn page(&self) -> u8 {
+                match self {
+                    $($name::$page(_) => $sel,)+
+                }
+            }
+        }
+
+        impl $crate::IscView for $name {
+            type Ref<'a> = $crate::MuxRef<'a, $name>;
+
+            fn view(data: &[u8]) -> Result<Self::Ref<'_>, $crate::ConvertErr> {
+                $crate::MuxRef::new(data)
+            }
+        }
+
+        impl From<$crate::MuxRef<'_, $name>> for $name {
+            fn from(view: $crate::MuxRef<'_, $name>) -> Self {
+                view.into_owned()
+            }
+        }
+
+        const _: () = {
+            let pages = [$($sel),+];
+            let mut i = 0;
+            while i < pages.len() {
+                assert!(
+                    pages[i] as usize == i,
+                    concat!(stringify!($name), " pages must be numbered from zero in order")
+                );
+                i += 1;
+            }
+        };
+    };
+}
+
+#[cfg(feature = "msgs-power")]
+pub(crate) use isc_mux;
+
+/// Largest value, such as the DLC of a message over its pages.
+pub(crate) const fn max(values: &[usize]) -> usize {
+    let mut out = 0;
+    let mut i = 0;
+    while i < values.len() {
+        if values[i] > out {
+            out = values[i];
+        }
+        i += 1;
+    }
+    out
+}
+
+/// Joins the signals of each page into one list of `N` signals.
+#[cfg_attr(not(feature = "msgs-power"), allow(dead_code))]
+pub(crate) const fn concat_signals<const N: usize>(parts: &[&[Signal]]) -> [Signal; N] {
+    let mut out = [Signal::SELECTOR; N];
+    let mut n = 0;
+    let mut i = 0;
+    while i < parts.len() {
+        let mut j = 0;
+        while j < parts[i].len() {
+            out[n] = parts[i][j];
+            n += 1;
+            j += 1;
+        }
+        i += 1;
+    }
+    assert!(n == N, "signal count does not match");
+    out
+}
+
+/// View of a multiplexed message payload.
+///
+/// Unlike the views of other messages, the page is decoded in full on construction, since the
+/// selector must be read first.
+#[derive(Copy, Clone, Debug)]
+pub struct MuxRef<'a, M> {
+    data: &'a [u8],
+    msg: M,
+}
+
+impl<'a, M: Mux> MuxRef<'a, M> {
+    /// Creates a view over a payload, decoding it.
+    pub fn new(data: &'a [u8]) -> Result<Self, ConvertErr> {
+        Ok(Self {
+            data,
+            msg: M::decode(data)?,
+        })
+    }
+
+    /// The payload this views.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Selector of the viewed page.
+    pub fn page(&self) -> u8 {
+        self.msg.page()
+    }
+
+    /// The decoded message.
+    pub fn into_owned(self) -> M {
+        self.msg
+    }
+}
+
+/// Collects the pages of a multiplexed message into a complete snapshot.
+///
+/// Each page is kept with the time it was received, and is fresh for `max_age_ms` after that.
+/// `N` must be the number of pages.
+#[derive(Copy, Clone, Debug)]
+pub struct Assembler<M, const N: usize> {
+    pages: [Option<(M, u32)>; N],
+    max_age_ms: u32,
+}
+
+impl<M: Mux, const N: usize> Assembler<M, N> {
+    pub const fn new(max_age_ms: u32) -> Self {
+        const {
+            assert!(
+                N == M::PAGES,
+                "assembler size does not match the page count"
+            )
+        };
+
+        Self {
+            pages: [None; N],
+            max_age_ms,
+        }
+    }
+
+    /// Stores a received page, replacing any older copy of it.
+    pub fn push(&mut self, msg: M, now_ms: u32) {
+        if let Some(slot) = self.pages.get_mut(msg.page() as usize) {
+            *slot = Some((msg, now_ms));
+        }
+    }
+
+    /// Time since a page was received, if it ever was.
+    pub fn age_ms(&self, page: u8, now_ms: u32) -> Option<u32> {
+        let (_, at) = self.pages.get(page as usize)?.as_ref()?;
+        Some(now_ms.wrapping_sub(*at))
+    }
+
+    /// A page, if it is fresh.
+    pub fn page(&self, page: u8, now_ms: u32) -> Option<M> {
+        let (msg, at) = (*self.pages.get(page as usize)?)?;
+        (now_ms.wrapping_sub(at) <= self.max_age_ms).then_some(msg)
+    }
+
+    /// Every fresh page, by selector.
+    pub fn partial(&self, now_ms: u32) -> [Option<M>; N] {
+        core::array::from_fn(|i| self.page(i as u8, now_ms))
+    }
+
+    /// Every page, if all are fresh.
+    pub fn snapshot(&self, now_ms: u32) -> Option<[M; N]> {
+        let partial = self.partial(now_ms);
+
+        // Filled from the first page, then each overwritten in turn
+        let mut out = [(*partial.first()?)?; N];
+        for (slot, page) in out.iter_mut().zip(partial) {
+            *slot = page?;
+        }
+        Some(out)
+    }
+
+    /// Forgets every page.
+    pub fn clear(&mut self) {
+        self.pages = [None; N];
+    }
+}
+
+#[cfg(all(test, feature = "msgs-power"))]
+mod test {
+    use super::*;
+    use crate::*;
+
+    const FIRST: CellVoltages0 = CellVoltages0 {
+        cell_1: 3700,
+        cell_2: 3701,
+        cell_3: 3702,
+    };
+    const SECOND: CellVoltages1 = CellVoltages1 {
+        cell_4: 3703,
+        cell_5: 3704,
+        cell_6: 3705,
+    };
+
+    fn round_trip(msg: CellVoltages) -> Result<CellVoltages, ConvertErr> {
+        let (data, len) = msg.encode();
+        CellVoltages::decode(&data[..len])
+    }
+
+    #[test]
+    fn test_pages() {
+        assert_eq!(CellVoltages::PAGES, 2);
+        assert_eq!(CellVoltages::DLC, 7);
+        assert_eq!(CellVoltages::SIGNALS.len(), 7);
+        assert_eq!(CellVoltages::SIGNALS[0], Signal::SELECTOR);
+        assert_eq!(CellVoltages::SIGNALS[4].mux, Multiplex::Page(1));
+        assert_eq!(CellVoltages::SIGNALS[4].start, 1);
+
+        assert_eq!(round_trip(FIRST.into()).unwrap(), FIRST.into());
+        assert_eq!(round_trip(SECOND.into()).unwrap(), SECOND.into());
+        assert_eq!(CellVoltages::from(SECOND).page(), 1);
+        assert!((SECOND.cell_4_scaled() - 3.703).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_invalid_selector() {
+        let (mut data, len) = CellVoltages::from(FIRST).encode();
+
+        data[0] = 2;
+        assert!(CellVoltages::decode(&data[..len]).is_err());
+        assert!(CanMessageRef::from_parts(CellVoltages::ID, &data[..len]).is_err());
+        assert!(CellVoltages::decode(&[]).is_err());
+
+        // A page only decodes its own selector
+        data[0] = 1;
+        assert!(CellVoltages0::decode(&data[..len]).is_err());
+        assert!(CellVoltages1::decode(&data[..len]).is_ok());
+        assert!(CellVoltages::decode(&data[..len - 1]).is_err());
+    }
+
+    #[test]
+    fn test_out_of_order() {
+        let mut asm = Assembler::<CellVoltages, 2>::new(100);
+
+        asm.push(SECOND.into(), 10);
+        assert_eq!(asm.snapshot(10), None);
+        asm.push(FIRST.into(), 20);
+        assert_eq!(asm.snapshot(20), Some([FIRST.into(), SECOND.into()]));
+
+        // Newer copies replace older ones
+        let newer = CellVoltages1 {
+            cell_4: 0,
+            ..SECOND
+        };
+        asm.push(newer.into(), 30);
+        assert_eq!(asm.snapshot(30), Some([FIRST.into(), newer.into()]));
+    }
+
+    #[test]
+    fn test_partial() {
+        let mut asm = Assembler::<CellVoltages, 2>::new(100);
+        assert_eq!(asm.partial(0), [None, None]);
+
+        asm.push(FIRST.into(), u32::MAX - 10);
+        assert_eq!(asm.partial(50), [Some(FIRST.into()), None]);
+        assert_eq!(asm.snapshot(50), None);
+
+        // Stale pages drop out, with ages measured across wrapping
+        asm.push(SECOND.into(), 60);
+        assert_eq!(asm.age_ms(0, 20), Some(31));
+        assert_eq!(asm.page(0, 89), Some(FIRST.into()));
+        assert_eq!(asm.snapshot(89), Some([FIRST.into(), SECOND.into()]));
+        assert_eq!(asm.partial(90), [None, Some(SECOND.into())]);
+        assert_eq!(asm.snapshot(90), None);
+        assert_eq!(asm.page(2, 0), None);
+
+        asm.clear();
+        assert_eq!(asm.age_ms(1, 0), None);
+    }
+
+    #[test]
+    fn test_view() {
+        let (data, len) = CellVoltages::from(SECOND).encode();
+        let view = CellVoltages::view(&data[..len]).unwrap();
+
+        assert_eq!(view.page(), 1);
+        assert!(core::ptr::eq(view.as_bytes(), &data[..len]));
+        assert_eq!(view.into_owned(), SECOND.into());
+    }
+}
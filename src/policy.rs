@@ -0,0 +1,60 @@
+/// How float fields are read from a payload.
+///
+/// Decoding uses [`Normalize`](DecodePolicy::Normalize) unless told otherwise, so helpers like the Ackermann
+/// conversion and the slew limiter only ever see finite values with one zero. Pass
+/// [`Raw`](DecodePolicy::Raw) to
+/// [`CanMessage::from_frame_with_policy`](crate::CanMessage::from_frame_with_policy) or
+/// [`IscFrame::decode_with`](crate::IscFrame::decode_with) to keep the bits as sent, like a data
+/// logger would. Views always normalize.
+///
+/// `Normalize` also rejects fields outside the range their message allows, like a `SetAngle`
+/// past `MAX_ANGLE`, which `Raw` keeps. Other magnitudes are not limited, so an `f32::MAX`
+/// velocity decodes as is, and it is up to the receiver to clamp it to a physical range.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum DecodePolicy {
+    /// Rejects NaN and infinities, and reads subnormals and -0.0 as 0.0, so that the value
+    /// encodes back to the same bytes it decodes from.
+    #[default]
+    Normalize,
+    /// Keeps every value, including NaN, infinities, and values out of range.
+    Raw,
+}
+
+impl DecodePolicy {
+    /// Applies the policy to one float, giving `None` if it is rejected.
+    pub fn float(self, v: f32) -> Option<f32> {
+        match self {
+            DecodePolicy::Normalize if !v.is_finite() => None,
+            DecodePolicy::Normalize if !v.is_normal() => Some(0.0),
+            _ => Some(v),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        let policy = DecodePolicy::Normalize;
+        assert_eq!(policy, DecodePolicy::default());
+        for v in [f32::NAN, -f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            assert_eq!(policy.float(v), None);
+        }
+        for v in [-0.0, f32::from_bits(1), -f32::from_bits(0x007F_FFFF)] {
+            assert_eq!(policy.float(v).map(f32::to_bits), Some(0));
+        }
+        for v in [f32::MIN_POSITIVE, -1.5, f32::MAX, f32::MIN] {
+            assert_eq!(policy.float(v), Some(v));
+        }
+    }
+
+    #[test]
+    fn test_raw() {
+        for bits in [0x8000_0000, 1, 0x7F80_0000, 0xFFC0_0001] {
+            let v = f32::from_bits(bits);
+            assert_eq!(DecodePolicy::Raw.float(v).map(f32::to_bits), Some(bits));
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use crate::ConvertErr;
+
+/// A field is outside the range its message allows, like a brake percent over 100.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RangeErr {
+    /// Name of the field, as in [`Signal::name`](crate::Signal::name).
+    pub field: &'static str,
+}
+
+/// Writes the error like `centi_percent out of range`, the same as its [`ConvertErr`].
+impl core::fmt::Display for RangeErr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} out of range", self.field)
+    }
+}
+
+impl From<RangeErr> for ConvertErr {
+    fn from(err: RangeErr) -> Self {
+        ConvertErr::OutOfRange { field: err.field }
+    }
+}
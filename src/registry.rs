@@ -0,0 +1,298 @@
+use crate::ConvertErr;
+#[cfg(feature = "aux-registry")]
+use crate::{CanMessage, REGISTRY};
+#[cfg(feature = "aux-registry")]
+use embedded_hal::can::{Frame, Id};
+
+/// How to decode one message ID.
+pub struct DecodeEntry<M> {
+    pub id: u32,
+    /// Payload length in bytes.
+    pub dlc: usize,
+    pub decode: fn(&[u8]) -> Result<M, ConvertErr>,
+}
+
+// Manual impls, since derives would require M: Clone
+impl<M> Clone for DecodeEntry<M> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<M> Copy for DecodeEntry<M> {}
+
+impl<M> core::fmt::Debug for DecodeEntry<M> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("DecodeEntry")
+            .field("id", &self.id)
+            .field("dlc", &self.dlc)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A table of decoders sorted by ID, looked up by index when IDs are dense from zero, and by
+/// binary search otherwise.
+///
+/// [`REGISTRY`](crate::REGISTRY) holds every message in this crate. Other crates can build
+/// their own for project specific messages, decoding into their own type.
+pub struct Registry<'a, M> {
+    entries: &'a [DecodeEntry<M>],
+}
+
+impl<M> Clone for Registry<'_, M> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<M> Copy for Registry<'_, M> {}
+
+impl<M> core::fmt::Debug for Registry<'_, M> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.entries).finish()
+    }
+}
+
+impl<'a, M> Registry<'a, M> {
+    /// Creates a registry from entries sorted by ID, such as from [`sort_entries`].
+    ///
+    /// Panics if the IDs are not strictly increasing, which also rejects duplicates. Creating
+    /// the registry in a const makes this a compile error.
+    pub const fn new(entries: &'a [DecodeEntry<M>]) -> Self {
+        let mut i = 1;
+        while i < entries.len() {
+            assert!(
+                entries[i - 1].id < entries[i].id,
+                "registry IDs must be unique and sorted"
+            );
+            i += 1;
+        }
+        Self { entries }
+    }
+
+    /// The entry for an ID, if registered.
+    #[inline]
+    pub fn get(&self, id: u32) -> Option<&'a DecodeEntry<M>> {
+        // IDs are usually dense from zero, so try indexing directly first
+        if let Some(e) = self.entries.get(id as usize) {
+            if e.id == id {
+                return Some(e);
+            }
+        }
+
+        self.entries
+            .binary_search_by_key(&id, |e| e.id)
+            .ok()
+            .and_then(|i| self.entries.get(i))
+    }
+
+    /// Decodes a payload, or None if the ID is not registered.
+    #[inline]
+    pub fn decode(&self, id: u32, data: &[u8]) -> Option<Result<M, ConvertErr>> {
+        self.get(id).map(|e| (e.decode)(data))
+    }
+
+    /// All entries, in ID order.
+    pub fn entries(&self) -> &'a [DecodeEntry<M>] {
+        self.entries
+    }
+}
+
+/// Sorts entries by ID, so they can be listed in any order in a const.
+pub const fn sort_entries<M, const N: usize>(
+    mut entries: [DecodeEntry<M>; N],
+) -> [DecodeEntry<M>; N] {
+    let mut i = 1;
+    while i < N {
+        let mut j = i;
+        while j > 0 && entries[j - 1].id > entries[j].id {
+            entries.swap(j - 1, j);
+            j -= 1;
+        }
+        i += 1;
+    }
+    entries
+}
+
+/// A frame decoded by [`CanMessage::from_frame_with`].
+#[cfg(feature = "aux-registry")]
+#[derive(Copy, Clone, Debug)]
+pub enum Decoded<M> {
+    /// A message defined in this crate.
+    Core(CanMessage),
+    /// A message from the auxiliary registry.
+    Aux(M),
+}
+
+#[cfg(feature = "aux-registry")]
+impl CanMessage {
+    /// Converts a CAN frame, decoding IDs this crate does not define with `aux`.
+    ///
+    /// IDs defined here always decode as core messages, even if `aux` also has them.
+    pub fn from_frame_with<M>(
+        value: impl Frame,
+        aux: &Registry<'_, M>,
+    ) -> Result<Decoded<M>, ConvertErr> {
+        let Id::Extended(id) = value.id() else {
+            return Err(ConvertErr::InvalidFrame);
+        };
+        let id = id.as_raw();
+
+        if let Some(core) = REGISTRY.decode(id, value.data()) {
+            core.map(Decoded::Core)
+        } else if let Some(aux) = aux.decode(id, value.data()) {
+            aux.map(Decoded::Aux)
+        } else {
+            Err(ConvertErr::UnknownId { id })
+        }
+    }
+}
+
+#[cfg(all(test, feature = "msgs-all"))]
+mod test {
+    use super::*;
+    use crate::testing::MockFrame;
+    use crate::*;
+    extern crate std;
+    use std::format;
+    use std::string::String;
+
+    /// The match `CanMessage::from_frame` used before the registry.
+    fn match_decode(id: u32, data: &[u8]) -> Result<CanMessage, ConvertErr> {
+        match id {
+            EstopAll::ID => EstopAll::decode(data).map(CanMessage::EstopAll),
+            SetBrake::ID => SetBrake::decode(data).map(CanMessage::SetBrake),
+            LockBrake::ID => LockBrake::decode(data).map(CanMessage::LockBrake),
+            UnlockBrake::ID => UnlockBrake::decode(data).map(CanMessage::UnlockBrake),
+            SetAngle::ID => SetAngle::decode(data).map(CanMessage::SetAngle),
+            GetAngle::ID => GetAngle::decode(data).map(CanMessage::GetAngle),
+            SetSpeed::ID => SetSpeed::decode(data).map(CanMessage::SetSpeed),
+            EncoderCount::ID => EncoderCount::decode(data).map(CanMessage::EncoderCount),
+            TrainingMode::ID => TrainingMode::decode(data).map(CanMessage::TrainingMode),
+            MotorTemperature::ID => {
+                MotorTemperature::decode(data).map(CanMessage::MotorTemperature)
+            }
+            SetAngleCompact::ID => SetAngleCompact::decode(data).map(CanMessage::SetAngleCompact),
+            GetAngleCompact::ID => GetAngleCompact::decode(data).map(CanMessage::GetAngleCompact),
+            CellVoltages::ID => CellVoltages::decode(data).map(CanMessage::CellVoltages),
+            ImuRates::ID => ImuRates::decode(data).map(CanMessage::ImuRates),
+            Heartbeat::ID => Heartbeat::decode(data).map(CanMessage::Heartbeat),
+            AutonDisable::ID => AutonDisable::decode(data).map(CanMessage::AutonDisable),
+            _ => Err(ConvertErr::UnknownId { id }),
+        }
+    }
+
+    fn debug<T: core::fmt::Debug>(v: T) -> String {
+        format!("{v:?}")
+    }
+
+    #[test]
+    fn test_matches_reference() {
+        // The second starts with a valid page selector
+        let payloads = [
+            [0x34, 0x12, 0x33, 0x33, 0x23, 0x41, 0xFF, 0x00],
+            [0x01, 0x12, 0x33, 0x33, 0x23, 0x41, 0xFF, 0x00],
+        ];
+
+        for (id, payload) in (0..0x40)
+            .chain([0x1FFF_FFFF])
+            .flat_map(|id| payloads.iter().map(move |p| (id, p)))
+        {
+            for len in 0..=8 {
+                let frame = MockFrame::extended(id, &payload[..len]);
+                assert_eq!(
+                    debug(CanMessage::from_frame(frame)),
+                    debug(match_decode(id, &payload[..len])),
+                    "{id:#x} {len}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_core_registry() {
+        let ids: std::vec::Vec<u32> = REGISTRY.entries().iter().map(|e| e.id).collect();
+        assert_eq!(ids, (0x0..=0xF).collect::<std::vec::Vec<u32>>());
+
+        let enc = REGISTRY.get(EncoderCount::ID).unwrap();
+        assert_eq!(enc.dlc, EncoderCount::DLC);
+        assert!(REGISTRY.get(0x10).is_none());
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Custom(u8);
+
+    const CUSTOM: [DecodeEntry<Custom>; 3] = sort_entries([
+        DecodeEntry {
+            id: 0x300,
+            dlc: 1,
+            decode: |d| {
+                d.first()
+                    .map(|b| Custom(*b))
+                    .ok_or(ConvertErr::InvalidFrame)
+            },
+        },
+        DecodeEntry {
+            id: 0x100,
+            dlc: 0,
+            decode: |_| Ok(Custom(0)),
+        },
+        DecodeEntry {
+            id: 0x200,
+            dlc: 0,
+            decode: |_| Ok(Custom(1)),
+        },
+    ]);
+
+    #[test]
+    fn test_custom_registry() {
+        const REG: Registry<'static, Custom> = Registry::new(&CUSTOM);
+
+        let ids: std::vec::Vec<u32> = REG.entries().iter().map(|e| e.id).collect();
+        assert_eq!(ids, [0x100, 0x200, 0x300]);
+
+        assert_eq!(REG.decode(0x200, &[]).unwrap().unwrap(), Custom(1));
+        assert_eq!(REG.decode(0x300, &[7]).unwrap().unwrap(), Custom(7));
+        assert!(REG.decode(0x300, &[]).unwrap().is_err());
+        assert!(REG.decode(0x400, &[]).is_none());
+    }
+
+    #[cfg(feature = "aux-registry")]
+    #[test]
+    fn test_aux_registry() {
+        const REG: Registry<'static, Custom> = Registry::new(&CUSTOM);
+        let frame = MockFrame::extended;
+
+        assert!(matches!(
+            CanMessage::from_frame_with(frame(0x300, &[9]), &REG),
+            Ok(Decoded::Aux(Custom(9)))
+        ));
+        assert!(matches!(
+            CanMessage::from_frame_with(frame(SetSpeed::ID, &[9]), &REG),
+            Ok(Decoded::Core(CanMessage::SetSpeed(SetSpeed {
+                centi_percent: 900
+            })))
+        ));
+        assert!(CanMessage::from_frame_with(frame(0x300, &[]), &REG).is_err());
+        assert!(CanMessage::from_frame_with(frame(0x301, &[]), &REG).is_err());
+
+        // Core IDs win over the aux registry
+        let shadow = [DecodeEntry {
+            id: SetSpeed::ID,
+            dlc: 1,
+            decode: |_| Ok(Custom(0)),
+        }];
+        assert!(matches!(
+            CanMessage::from_frame_with(frame(SetSpeed::ID, &[9]), &Registry::new(&shadow)),
+            Ok(Decoded::Core(_))
+        ));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_unsorted_rejected() {
+        let mut entries = CUSTOM;
+        entries[2].id = 0x100;
+        Registry::new(&entries);
+    }
+}
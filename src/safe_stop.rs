@@ -0,0 +1,145 @@
+use crate::{CanMessage, SetBrake, SetSpeed};
+
+/// How [`safe_stop_sequence`] should bring the kart to a stop.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum StopProfile {
+    /// Zero speed, then full brake at once.
+    Immediate,
+    /// Zero speed, then ramp the brake up to full.
+    Controlled {
+        /// Brake ramp rate, in percent per second.
+        rate_pct_per_s: u16,
+        /// Interval the SetBrake steps will be sent at, in ms.
+        interval_ms: u16,
+    },
+}
+
+impl StopProfile {
+    /// Brake percent added per SetBrake step. Always at least one, so the ramp terminates.
+    pub const fn brake_step(&self) -> u8 {
+        match *self {
+            StopProfile::Immediate => 100,
+            StopProfile::Controlled {
+                rate_pct_per_s,
+                interval_ms,
+            } => {
+                let step = rate_pct_per_s as u32 * interval_ms as u32 / 1000;
+                if step == 0 {
+                    1
+                } else if step > 100 {
+                    100
+                } else {
+                    step as u8
+                }
+            }
+        }
+    }
+
+    /// Number of SetBrake messages in the sequence.
+    pub const fn brake_steps(&self) -> usize {
+        let step = self.brake_step() as usize;
+        100usize.div_ceil(step)
+    }
+}
+
+/// Returns the ordered messages to transmit to bring the kart to a safe stop.
+///
+/// The sequence always starts with SetSpeed 0, and ends with SetBrake 100. For the controlled
+/// profile, each SetBrake after the first should be sent `interval_ms` after the previous one.
+pub fn safe_stop_sequence(profile: StopProfile) -> impl Iterator<Item = CanMessage> {
+    let step = profile.brake_step();
+
+    let speed = core::iter::once(CanMessage::SetSpeed(SetSpeed { centi_percent: 0 }));
+    let brakes = (1..=profile.brake_steps()).map(move |i| {
+        let percent = i.saturating_mul(step as usize).min(100) as u16;
+        CanMessage::SetBrake(SetBrake {
+            centi_percent: percent * 100,
+        })
+    });
+
+    speed.chain(brakes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn brake_percents(profile: StopProfile) -> impl Iterator<Item = u8> {
+        safe_stop_sequence(profile).skip(1).map(|m| match m {
+            CanMessage::SetBrake(b) => (b.centi_percent / 100) as u8,
+            _ => panic!("{m:?}"),
+        })
+    }
+
+    #[test]
+    fn test_immediate() {
+        let mut seq = safe_stop_sequence(StopProfile::Immediate);
+
+        assert!(matches!(
+            seq.next(),
+            Some(CanMessage::SetSpeed(SetSpeed { centi_percent: 0 }))
+        ));
+        assert!(matches!(
+            seq.next(),
+            Some(CanMessage::SetBrake(SetBrake {
+                centi_percent: 10_000
+            }))
+        ));
+        assert!(seq.next().is_none());
+    }
+
+    #[test]
+    fn test_controlled() {
+        // 200%/s at 100ms is 20% per step
+        let profile = StopProfile::Controlled {
+            rate_pct_per_s: 200,
+            interval_ms: 100,
+        };
+        assert_eq!(profile.brake_steps(), 5);
+        assert!(brake_percents(profile).eq([20, 40, 60, 80, 100]));
+
+        // Uneven steps are clamped at 100
+        let profile = StopProfile::Controlled {
+            rate_pct_per_s: 300,
+            interval_ms: 100,
+        };
+        assert_eq!(profile.brake_steps(), 4);
+        assert!(brake_percents(profile).eq([30, 60, 90, 100]));
+
+        // Too slow to make progress still terminates
+        let profile = StopProfile::Controlled {
+            rate_pct_per_s: 1,
+            interval_ms: 10,
+        };
+        assert_eq!(profile.brake_steps(), 100);
+        assert_eq!(brake_percents(profile).last(), Some(100));
+    }
+
+    #[test]
+    fn test_speed_precedes_brake() {
+        let profiles = [
+            StopProfile::Immediate,
+            StopProfile::Controlled {
+                rate_pct_per_s: 50,
+                interval_ms: 20,
+            },
+            StopProfile::Controlled {
+                rate_pct_per_s: u16::MAX,
+                interval_ms: u16::MAX,
+            },
+        ];
+
+        for profile in profiles {
+            let mut seq = safe_stop_sequence(profile);
+            assert!(matches!(
+                seq.next(),
+                Some(CanMessage::SetSpeed(SetSpeed { centi_percent: 0 }))
+            ));
+            assert!(seq.all(|m| matches!(m, CanMessage::SetBrake(_))));
+            assert_eq!(
+                safe_stop_sequence(profile).count(),
+                profile.brake_steps() + 1
+            );
+        }
+    }
+}
@@ -0,0 +1,61 @@
+/// How the raw value of a [`Signal`] is represented.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum SignalKind {
+    Unsigned,
+    Signed,
+    /// IEEE 754 float.
+    Float,
+}
+
+/// Byte order of a multi-byte [`Signal`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum ByteOrder {
+    LittleEndian,
+    BigEndian,
+}
+
+/// Whether a [`Signal`] is part of a multiplexed message.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum Multiplex {
+    /// Present in every frame with this ID.
+    Plain,
+    /// Selects which page the rest of the payload is.
+    Selector,
+    /// Only present in the page with this selector.
+    Page(u8),
+}
+
+/// Layout and scaling of one field in a message payload, used to describe the bus to other
+/// tools.
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+pub struct Signal {
+    /// Field name.
+    pub name: &'static str,
+    /// Byte offset in the payload.
+    pub start: usize,
+    /// Size in bytes.
+    pub size: usize,
+    pub kind: SignalKind,
+    pub order: ByteOrder,
+    /// The engineering value is `raw * scale + offset`.
+    pub scale: f32,
+    pub offset: f32,
+    /// Unit of the engineering value, empty if unitless.
+    pub unit: &'static str,
+    pub mux: Multiplex,
+}
+
+impl Signal {
+    /// The page selector of a multiplexed message, always the first byte.
+    pub const SELECTOR: Signal = Signal {
+        name: "page",
+        start: 0,
+        size: 1,
+        kind: SignalKind::Unsigned,
+        order: ByteOrder::LittleEndian,
+        scale: 1.0,
+        offset: 0.0,
+        unit: "",
+        mux: Multiplex::Selector,
+    };
+}
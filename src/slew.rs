@@ -0,0 +1,126 @@
+use crate::{GetAngle, SetAngle};
+
+/// Limits how fast [`SetAngle`] commands move the steering, so large steps don't slam the motor.
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+pub struct SteeringSlewLimiter {
+    /// Maximum rate of change, in degrees per second.
+    max_rate: f32,
+    /// Current setpoint in degrees. Starts centered until snapped.
+    setpoint: f32,
+}
+
+impl SteeringSlewLimiter {
+    /// Creates a limiter centered at zero degrees. A negative or NaN rate is treated as zero.
+    pub fn new(max_deg_per_s: f32) -> Self {
+        Self {
+            max_rate: if max_deg_per_s > 0.0 {
+                max_deg_per_s
+            } else {
+                0.0
+            },
+            setpoint: 0.0,
+        }
+    }
+
+    /// Moves the setpoint toward `target` by at most the limited rate over `dt_s` seconds,
+    /// returning the command to send.
+    ///
+    /// A non-finite target, or a non-positive or non-finite `dt_s`, holds the current setpoint.
+    pub fn next(&mut self, target: &SetAngle, dt_s: f32) -> SetAngle {
+        if target.angle.is_finite() && dt_s > 0.0 && dt_s.is_finite() {
+            let max_step = self.max_rate * dt_s;
+            // Not clamp, which panics on NaN bounds
+            let delta = (target.angle - self.setpoint).max(-max_step).min(max_step);
+            self.setpoint += delta;
+        }
+
+        SetAngle {
+            angle: self.setpoint,
+        }
+    }
+
+    /// Jumps the setpoint directly to the measured angle, for initialization from feedback.
+    ///
+    /// A non-finite angle is ignored.
+    pub fn snap(&mut self, angle: &GetAngle) {
+        if angle.angle.is_finite() {
+            self.setpoint = angle.angle;
+        }
+    }
+
+    /// Current setpoint, in degrees.
+    pub fn setpoint(&self) -> f32 {
+        self.setpoint
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Steps the limiter until it reaches the target, returning the number of steps taken.
+    fn steps_to(limiter: &mut SteeringSlewLimiter, target: f32, dt_s: f32) -> usize {
+        let target = SetAngle { angle: target };
+        let mut steps = 0;
+        while limiter.next(&target, dt_s).angle != target.angle {
+            steps += 1;
+            assert!(steps < 10_000);
+        }
+        steps + 1
+    }
+
+    #[test]
+    fn test_convergence_time() {
+        let mut limiter = SteeringSlewLimiter::new(32.0);
+        limiter.snap(&GetAngle { angle: -20.0 });
+
+        // 40 degrees at 32 deg/s is 1.25 seconds, or 80 steps of 1/64 s
+        assert_eq!(steps_to(&mut limiter, 20.0, 1.0 / 64.0), 80);
+    }
+
+    #[test]
+    fn test_symmetric() {
+        let mut up = SteeringSlewLimiter::new(25.0);
+        let mut down = SteeringSlewLimiter::new(25.0);
+
+        for _ in 0..20 {
+            let a = up.next(&SetAngle { angle: 15.0 }, 0.02).angle;
+            let b = down.next(&SetAngle { angle: -15.0 }, 0.02).angle;
+            assert_eq!(a, -b);
+        }
+    }
+
+    #[test]
+    fn test_retarget_mid_slew() {
+        let mut limiter = SteeringSlewLimiter::new(10.0);
+
+        for _ in 0..5 {
+            limiter.next(&SetAngle { angle: 20.0 }, 0.1);
+        }
+        assert!((limiter.setpoint() - 5.0).abs() < 1e-4);
+
+        // Reversing direction starts moving back immediately, without overshoot
+        let out = limiter.next(&SetAngle { angle: -20.0 }, 0.1);
+        assert!((out.angle - 4.0).abs() < 1e-4);
+
+        // Targets within a step are reached exactly
+        let out = limiter.next(&SetAngle { angle: 3.5 }, 0.1);
+        assert_eq!(out.angle, 3.5);
+    }
+
+    #[test]
+    fn test_nan_rejected() {
+        let mut limiter = SteeringSlewLimiter::new(10.0);
+        limiter.snap(&GetAngle { angle: 2.0 });
+
+        assert_eq!(limiter.next(&SetAngle { angle: f32::NAN }, 0.1).angle, 2.0);
+        let inf = SetAngle {
+            angle: f32::INFINITY,
+        };
+        assert_eq!(limiter.next(&inf, 0.1).angle, 2.0);
+        assert_eq!(limiter.next(&SetAngle { angle: 5.0 }, f32::NAN).angle, 2.0);
+
+        limiter.snap(&GetAngle { angle: f32::NAN });
+        assert_eq!(limiter.setpoint(), 2.0);
+    }
+}
@@ -0,0 +1,67 @@
+//! Conversions with socketcan's frames, enabled by the `socketcan` feature, for Linux hosts on
+//! the bus. socketcan implements `embedded_can::Frame` rather than embedded-hal's, so these
+//! are used instead of [`CanMessage::from_frame`] and [`CanMessage::into_frame`].
+
+use crate::{CanMessage, ConvertErr};
+use ::socketcan::{CanDataFrame, CanFrame, CanRemoteFrame, EmbeddedFrame, ExtendedId, Id};
+
+/// Decodes like [`CanMessage::from_frame`], so remote frames have no data, and only empty
+/// messages decode from them. Error frames carry no message, so are rejected with
+/// [`ConvertErr::InvalidFrame`].
+impl TryFrom<CanFrame> for CanMessage {
+    type Error = ConvertErr;
+
+    fn try_from(frame: CanFrame) -> Result<Self, ConvertErr> {
+        match frame {
+            CanFrame::Data(frame) => Self::try_from(frame),
+            CanFrame::Remote(frame) => Self::try_from(frame),
+            CanFrame::Error(_) => Err(ConvertErr::InvalidFrame),
+        }
+    }
+}
+
+/// Decodes like [`CanMessage::from_frame`], so standard IDs are rejected.
+impl TryFrom<CanDataFrame> for CanMessage {
+    type Error = ConvertErr;
+
+    fn try_from(frame: CanDataFrame) -> Result<Self, ConvertErr> {
+        from_embedded(&frame)
+    }
+}
+
+/// Decodes like [`CanMessage::from_frame`], where only empty messages decode.
+impl TryFrom<CanRemoteFrame> for CanMessage {
+    type Error = ConvertErr;
+
+    fn try_from(frame: CanRemoteFrame) -> Result<Self, ConvertErr> {
+        from_embedded(&frame)
+    }
+}
+
+fn from_embedded(frame: &impl EmbeddedFrame) -> Result<CanMessage, ConvertErr> {
+    match frame.id() {
+        Id::Extended(id) => CanMessage::from_parts(id.as_raw(), frame.data()),
+        Id::Standard(_) => Err(ConvertErr::InvalidFrame),
+    }
+}
+
+/// Encodes as a data frame, like [`CanMessage::into_frame`].
+impl TryFrom<CanMessage> for CanDataFrame {
+    type Error = ConvertErr;
+
+    fn try_from(msg: CanMessage) -> Result<Self, ConvertErr> {
+        let (id, data, len) = msg.to_parts()?;
+        let id = ExtendedId::new(id.as_raw()).ok_or(ConvertErr::InvalidFrame)?;
+        let data = data.get(..len).ok_or(ConvertErr::InvalidFrame)?;
+        CanDataFrame::new(id, data).ok_or(ConvertErr::InvalidFrame)
+    }
+}
+
+/// Encodes as a data frame, like [`CanMessage::into_frame`].
+impl TryFrom<CanMessage> for CanFrame {
+    type Error = ConvertErr;
+
+    fn try_from(msg: CanMessage) -> Result<Self, ConvertErr> {
+        CanDataFrame::try_from(msg).map(CanFrame::Data)
+    }
+}
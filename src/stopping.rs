@@ -0,0 +1,131 @@
+use crate::{EncoderCount, LinearTable, SetBrake, MAX_CENTI_PERCENT};
+use libm::sqrtf;
+
+/// Brake calibration, mapping [`SetBrake`] percent to deceleration in m/s².
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct BrakeCurve<const N: usize> {
+    pub table: LinearTable<N>,
+}
+
+impl<const N: usize> BrakeCurve<N> {
+    /// Deceleration for a brake command, in m/s².
+    pub fn decel(&self, cmd: &SetBrake) -> f32 {
+        self.table.interpolate(cmd.percent())
+    }
+
+    /// Deceleration at full brake, in m/s².
+    pub fn max_decel(&self) -> f32 {
+        self.decel(&SetBrake {
+            centi_percent: MAX_CENTI_PERCENT,
+        })
+    }
+}
+
+/// Distance needed to stop from the current velocity at full brake, in meters.
+///
+/// The kart is assumed to continue at the current velocity for `system_latency_ms` before the
+/// brake engages. Zero or negative velocity needs no distance. Unknown velocity, or a curve that
+/// does not decelerate at full brake, can never be assumed to stop, and returns infinity.
+pub fn stopping_distance<const N: usize>(
+    velocity: &EncoderCount,
+    brake: &BrakeCurve<N>,
+    system_latency_ms: u32,
+) -> f32 {
+    let v = velocity.velocity;
+    let decel = brake.max_decel();
+
+    if v.is_nan() || decel.is_nan() || decel <= 0.0 {
+        return f32::INFINITY;
+    }
+    if v <= 0.0 {
+        return 0.0;
+    }
+
+    let latency_s = system_latency_ms as f32 / 1000.0;
+    v * latency_s + v * v / (2.0 * decel)
+}
+
+/// Highest velocity that can stop within `distance_m` at full brake, in m/s.
+///
+/// This is the inverse of [`stopping_distance`], for deriving speed limits near obstacles.
+/// Zero or unknown distance, or a curve that does not decelerate at full brake, returns zero.
+pub fn max_safe_speed<const N: usize>(
+    distance_m: f32,
+    brake: &BrakeCurve<N>,
+    system_latency_ms: u32,
+) -> f32 {
+    let decel = brake.max_decel();
+
+    if distance_m.is_nan() || distance_m <= 0.0 || decel.is_nan() || decel <= 0.0 {
+        return 0.0;
+    }
+
+    // Positive root of v^2 / 2a + v t - d = 0
+    let at = decel * system_latency_ms as f32 / 1000.0;
+    sqrtf(at * at + 2.0 * decel * distance_m) - at
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn curve(max_decel: f32) -> BrakeCurve<3> {
+        BrakeCurve {
+            table: LinearTable::new([0.0, 50.0, 100.0], [0.0, max_decel / 4.0, max_decel]).unwrap(),
+        }
+    }
+
+    fn enc(velocity: f32) -> EncoderCount {
+        EncoderCount { count: 0, velocity }
+    }
+
+    #[test]
+    fn test_hand_computed() {
+        // 10 m/s for 200ms is 2m, then 100 / (2 * 5) is 10m
+        assert!((stopping_distance(&enc(10.0), &curve(5.0), 200) - 12.0).abs() < 1e-4);
+        // No latency, 4 m/s at 8 m/s^2 is 1m
+        assert!((stopping_distance(&enc(4.0), &curve(8.0), 0) - 1.0).abs() < 1e-4);
+
+        assert!((max_safe_speed(12.0, &curve(5.0), 200) - 10.0).abs() < 1e-4);
+        assert!((max_safe_speed(1.0, &curve(8.0), 0) - 4.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_inverse_consistent() {
+        let brake = curve(6.5);
+
+        for latency in [0, 50, 150, 500] {
+            for i in 0..50 {
+                let v = i as f32 * 0.5;
+                let d = stopping_distance(&enc(v), &brake, latency);
+                let back = max_safe_speed(d, &brake, latency);
+                assert!((back - v).abs() < 1e-3, "{v} {latency} {back}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_degenerate() {
+        assert_eq!(stopping_distance(&enc(0.0), &curve(5.0), 100), 0.0);
+        assert_eq!(stopping_distance(&enc(-3.0), &curve(5.0), 100), 0.0);
+        assert_eq!(
+            stopping_distance(&enc(f32::NAN), &curve(5.0), 100),
+            f32::INFINITY
+        );
+        assert_eq!(
+            stopping_distance(&enc(3.0), &curve(0.0), 100),
+            f32::INFINITY
+        );
+        assert_eq!(
+            stopping_distance(&enc(3.0), &curve(-1.0), 100),
+            f32::INFINITY
+        );
+
+        assert_eq!(max_safe_speed(0.0, &curve(5.0), 100), 0.0);
+        assert_eq!(max_safe_speed(-2.0, &curve(5.0), 100), 0.0);
+        assert_eq!(max_safe_speed(f32::NAN, &curve(5.0), 100), 0.0);
+        assert_eq!(max_safe_speed(10.0, &curve(0.0), 100), 0.0);
+    }
+}
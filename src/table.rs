@@ -0,0 +1,332 @@
+#[cfg(feature = "msgs-brake")]
+use crate::SetBrake;
+#[cfg(feature = "msgs-drive")]
+use crate::SetSpeed;
+
+/// Errors caused by constructing a [`LinearTable`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum TableErr {
+    /// Tables need at least two knots to interpolate between.
+    TooFewPoints,
+    /// A knot was NaN or infinite.
+    NonFinite,
+    /// Knot x values were not strictly increasing.
+    NonMonotonic,
+}
+
+/// Piecewise linear lookup table with N knots.
+///
+/// Knot x values are strictly increasing, while y values are arbitrary. Lookups outside the
+/// range of the knots are clamped to the first or last y value.
+///
+/// With the `serde` feature this serializes as a sequence of `[x, y]` pairs, and is validated
+/// on deserialization.
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+pub struct LinearTable<const N: usize> {
+    x: [f32; N],
+    y: [f32; N],
+}
+
+impl<const N: usize> LinearTable<N> {
+    pub fn new(x: [f32; N], y: [f32; N]) -> Result<Self, TableErr> {
+        if N < 2 {
+            return Err(TableErr::TooFewPoints);
+        }
+
+        if x.iter().chain(y.iter()).any(|v| !v.is_finite()) {
+            return Err(TableErr::NonFinite);
+        }
+
+        if x.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(TableErr::NonMonotonic);
+        }
+
+        Ok(Self { x, y })
+    }
+
+    /// Looks up y for an x. NaN inputs return NaN.
+    pub fn interpolate(&self, x: f32) -> f32 {
+        lerp_table(&self.x, &self.y, x)
+    }
+
+    /// Looks up x for a y, if y values are strictly monotonic and y is within their range.
+    pub fn inverse(&self, y: f32) -> Option<f32> {
+        let increasing = self.y.windows(2).all(|w| w[0] < w[1]);
+        let decreasing = self.y.windows(2).all(|w| w[0] > w[1]);
+
+        let (&first, &last) = (self.y.first()?, self.y.last()?);
+        let (lo, hi) = (first.min(last), first.max(last));
+        if !(lo..=hi).contains(&y) {
+            return None;
+        }
+
+        if increasing {
+            Some(lerp_table(&self.y, &self.x, y))
+        } else if decreasing {
+            // Search from the other end, so y is increasing
+            let mut x = self.x;
+            let mut ys = self.y;
+            x.reverse();
+            ys.reverse();
+            Some(lerp_table(&ys, &x, y))
+        } else {
+            None
+        }
+    }
+
+    /// Knot x values.
+    pub fn x(&self) -> &[f32; N] {
+        &self.x
+    }
+
+    /// Knot y values.
+    pub fn y(&self) -> &[f32; N] {
+        &self.y
+    }
+}
+
+/// Interpolates `at` along knots `xs`, which must be increasing, clamping outside their range.
+fn lerp_table(xs: &[f32], ys: &[f32], at: f32) -> f32 {
+    if at.is_nan() {
+        return f32::NAN;
+    }
+
+    let (Some(&x_first), Some(&x_last), Some(&y_first), Some(&y_last)) =
+        (xs.first(), xs.last(), ys.first(), ys.last())
+    else {
+        return f32::NAN;
+    };
+    if at <= x_first {
+        return y_first;
+    }
+    if at >= x_last {
+        return y_last;
+    }
+
+    // First knot above at, which exists given the clamping above
+    let i = xs.partition_point(|&x| x <= at);
+    let knots = i
+        .checked_sub(1)
+        .and_then(|j| Some((xs.get(j..=i)?, ys.get(j..=i)?)));
+    match knots {
+        Some((&[x0, x1], &[y0, y1])) => {
+            let t = (at - x0) / (x1 - x0);
+            y0 + t * (y1 - y0)
+        }
+        _ => y_last,
+    }
+}
+
+/// Maps [`SetSpeed`] percent to motor controller duty cycle.
+#[cfg(feature = "msgs-drive")]
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct SpeedMap<const N: usize> {
+    pub table: LinearTable<N>,
+}
+
+#[cfg(feature = "msgs-drive")]
+impl<const N: usize> SpeedMap<N> {
+    /// Duty cycle for a speed command.
+    pub fn duty(&self, cmd: &SetSpeed) -> f32 {
+        self.table.interpolate(cmd.percent())
+    }
+
+    /// Nearest speed command producing a duty cycle, if the map is invertible there.
+    pub fn command_for(&self, duty: f32) -> Option<SetSpeed> {
+        self.table.inverse(duty).map(|p| SetSpeed {
+            centi_percent: centi_percent_from_f32(p),
+        })
+    }
+}
+
+/// Maps [`SetBrake`] percent to brake actuator output.
+#[cfg(feature = "msgs-brake")]
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct BrakeMap<const N: usize> {
+    pub table: LinearTable<N>,
+}
+
+#[cfg(feature = "msgs-brake")]
+impl<const N: usize> BrakeMap<N> {
+    /// Actuator output for a brake command.
+    pub fn output(&self, cmd: &SetBrake) -> f32 {
+        self.table.interpolate(cmd.percent())
+    }
+
+    /// Nearest brake command producing an actuator output, if the map is invertible there.
+    pub fn command_for(&self, output: f32) -> Option<SetBrake> {
+        self.table.inverse(output).map(|p| SetBrake {
+            centi_percent: centi_percent_from_f32(p),
+        })
+    }
+}
+
+#[cfg(any(feature = "msgs-drive", feature = "msgs-brake"))]
+fn centi_percent_from_f32(percent: f32) -> u16 {
+    (percent * 100.0 + 0.5).clamp(0.0, crate::MAX_CENTI_PERCENT as f32) as u16
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::LinearTable;
+    use core::fmt;
+    use serde::de::{self, SeqAccess, Visitor};
+    use serde::ser::SerializeTuple;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    impl<const N: usize> Serialize for LinearTable<N> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut tup = serializer.serialize_tuple(N)?;
+            for (x, y) in self.x.iter().zip(self.y.iter()) {
+                tup.serialize_element(&(x, y))?;
+            }
+            tup.end()
+        }
+    }
+
+    struct TableVisitor<const N: usize>;
+
+    impl<'de, const N: usize> Visitor<'de> for TableVisitor<N> {
+        type Value = LinearTable<N>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{N} [x, y] pairs")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut x = [0.0; N];
+            let mut y = [0.0; N];
+
+            for i in 0..N {
+                let (kx, ky): (f32, f32) = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+                x[i] = kx;
+                y[i] = ky;
+            }
+
+            if seq.next_element::<(f32, f32)>()?.is_some() {
+                return Err(de::Error::invalid_length(N + 1, &self));
+            }
+
+            LinearTable::new(x, y).map_err(|e| de::Error::custom(format_args!("{e:?}")))
+        }
+    }
+
+    impl<'de, const N: usize> Deserialize<'de> for LinearTable<N> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_tuple(N, TableVisitor::<N>)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "msgs-all"))]
+mod test {
+    use super::*;
+
+    fn table() -> LinearTable<4> {
+        LinearTable::new([0.0, 10.0, 50.0, 100.0], [0.0, 0.2, 0.5, 1.0]).unwrap()
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let t = table();
+
+        // At knots
+        assert_eq!(t.interpolate(0.0), 0.0);
+        assert_eq!(t.interpolate(10.0), 0.2);
+        assert_eq!(t.interpolate(100.0), 1.0);
+
+        // Between knots
+        assert!((t.interpolate(5.0) - 0.1).abs() < 1e-6);
+        assert!((t.interpolate(30.0) - 0.35).abs() < 1e-6);
+
+        // Outside knots
+        assert_eq!(t.interpolate(-5.0), 0.0);
+        assert_eq!(t.interpolate(150.0), 1.0);
+        assert!(t.interpolate(f32::NAN).is_nan());
+    }
+
+    #[test]
+    fn test_inverse() {
+        let t = table();
+        assert!((t.inverse(0.35).unwrap() - 30.0).abs() < 1e-4);
+        assert_eq!(t.inverse(0.2), Some(10.0));
+        assert_eq!(t.inverse(1.5), None);
+
+        let dec = LinearTable::new([0.0, 1.0, 2.0], [10.0, 5.0, 0.0]).unwrap();
+        assert!((dec.inverse(7.5).unwrap() - 0.5).abs() < 1e-6);
+
+        let non_monotonic = LinearTable::new([0.0, 1.0, 2.0], [0.0, 5.0, 0.0]).unwrap();
+        assert_eq!(non_monotonic.inverse(2.5), None);
+    }
+
+    #[test]
+    fn test_validation() {
+        assert_eq!(
+            LinearTable::new([0.0, 2.0, 1.0], [0.0; 3]),
+            Err(TableErr::NonMonotonic)
+        );
+        assert_eq!(
+            LinearTable::new([0.0, 1.0, 1.0], [0.0; 3]),
+            Err(TableErr::NonMonotonic)
+        );
+        assert_eq!(
+            LinearTable::new([0.0, f32::NAN], [0.0; 2]),
+            Err(TableErr::NonFinite)
+        );
+        assert_eq!(LinearTable::new([0.0], [0.0]), Err(TableErr::TooFewPoints));
+    }
+
+    #[test]
+    fn test_maps() {
+        let speed = SpeedMap { table: table() };
+        assert_eq!(
+            speed.duty(&SetSpeed {
+                centi_percent: 5000
+            }),
+            0.5
+        );
+        assert_eq!(
+            speed.command_for(0.35),
+            Some(SetSpeed {
+                centi_percent: 3000
+            })
+        );
+
+        let brake = BrakeMap { table: table() };
+        assert_eq!(
+            brake.output(&SetBrake {
+                centi_percent: 1000
+            }),
+            0.2
+        );
+        assert_eq!(
+            brake.command_for(1.0),
+            Some(SetBrake {
+                centi_percent: 10_000
+            })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let t = table();
+        let json = serde_json::to_string(&t).unwrap();
+        assert_eq!(json, "[[0.0,0.0],[10.0,0.2],[50.0,0.5],[100.0,1.0]]");
+        assert_eq!(serde_json::from_str::<LinearTable<4>>(&json).unwrap(), t);
+
+        let map: SpeedMap<4> = serde_json::from_str(&json).unwrap();
+        assert_eq!(map.table, t);
+
+        // Validation and length are enforced
+        assert!(serde_json::from_str::<LinearTable<2>>("[[1.0,0.0],[0.0,1.0]]").is_err());
+        assert!(serde_json::from_str::<LinearTable<2>>("[[0.0,0.0]]").is_err());
+        assert!(serde_json::from_str::<LinearTable<2>>("[[0.0,0.0],[1.0,0.0],[2.0,0.0]]").is_err());
+    }
+}
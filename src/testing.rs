@@ -0,0 +1,512 @@
+//! Test helpers, enabled by the `testing` feature. Those that need std, like [`SimBus`], are
+//! also enabled by the `std` feature.
+//!
+//! These are for tests on a host, and panic on misuse rather than erroring, so are not covered
+//! by the no panic guarantee.
+
+use crate::*;
+use embedded_hal::can::{ExtendedId, Frame, Id, StandardId};
+
+mod corrupt;
+pub use corrupt::*;
+#[cfg(any(test, feature = "std"))]
+mod sim;
+#[cfg(any(test, feature = "std"))]
+pub use sim::*;
+#[cfg(any(test, feature = "std"))]
+mod sequence;
+#[cfg(any(test, feature = "std"))]
+pub use sequence::*;
+#[cfg(feature = "msgs-all")]
+mod stream;
+#[cfg(feature = "msgs-all")]
+pub use stream::*;
+#[cfg(any(test, feature = "std"))]
+pub mod vectors;
+
+/// A [`Frame`] with every field set directly, so tests can build frames a real driver never
+/// would, such as a DLC that disagrees with the data.
+///
+/// Frames from [`Frame::new`] and [`Frame::new_remote`] follow the embedded-hal contract: data
+/// frames have a DLC equal to their data length, remote frames have no data, and either is
+/// rejected over 8 bytes.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct MockFrame {
+    pub id: Id,
+    pub remote: bool,
+    /// Reported by `dlc`, independently of `data`.
+    pub dlc: usize,
+    /// Reported by `data`, up to `len` bytes.
+    pub data: [u8; 8],
+    pub len: usize,
+}
+
+impl MockFrame {
+    /// A data frame with an extended ID.
+    ///
+    /// # Panics
+    ///
+    /// If the ID is over 29 bits, or the data over 8 bytes.
+    pub fn extended(id: u32, data: &[u8]) -> Self {
+        Self::new(ExtendedId::new(id).expect("ID over 29 bits"), data).expect("data over 8 bytes")
+    }
+
+    /// A data frame with a standard ID.
+    ///
+    /// # Panics
+    ///
+    /// If the ID is over 11 bits, or the data over 8 bytes.
+    pub fn standard(id: u16, data: &[u8]) -> Self {
+        Self::new(StandardId::new(id).expect("ID over 11 bits"), data).expect("data over 8 bytes")
+    }
+
+    /// Reports a different DLC, leaving the data as is.
+    pub fn with_dlc(self, dlc: usize) -> Self {
+        Self { dlc, ..self }
+    }
+
+    /// Marks the frame as remote or not, leaving the data as is.
+    pub fn with_remote(self, remote: bool) -> Self {
+        Self { remote, ..self }
+    }
+
+    /// The raw ID, whichever kind it is.
+    pub fn raw_id(&self) -> u32 {
+        match self.id {
+            Id::Standard(id) => id.as_raw() as u32,
+            Id::Extended(id) => id.as_raw(),
+        }
+    }
+}
+
+impl Frame for MockFrame {
+    fn new(id: impl Into<Id>, data: &[u8]) -> Option<Self> {
+        let mut buf = [0; 8];
+        buf.get_mut(..data.len())?.copy_from_slice(data);
+        Some(Self {
+            id: id.into(),
+            remote: false,
+            dlc: data.len(),
+            data: buf,
+            len: data.len(),
+        })
+    }
+
+    fn new_remote(id: impl Into<Id>, dlc: usize) -> Option<Self> {
+        (dlc <= 8).then(|| Self {
+            id: id.into(),
+            remote: true,
+            dlc,
+            data: [0; 8],
+            len: 0,
+        })
+    }
+
+    fn is_extended(&self) -> bool {
+        matches!(self.id, Id::Extended(_))
+    }
+
+    fn is_remote_frame(&self) -> bool {
+        self.remote
+    }
+
+    fn id(&self) -> Id {
+        self.id
+    }
+
+    fn dlc(&self) -> usize {
+        self.dlc
+    }
+
+    fn data(&self) -> &[u8] {
+        &self.data[..self.len.min(8)]
+    }
+}
+
+/// Sends a message through a [`MockFrame`] with [`IscFrame::into_frame`], and decodes it again
+/// with [`CanMessage::from_frame`], as a receiver would.
+///
+/// # Panics
+///
+/// If the message does not encode, or its frame does not decode, like a NaN angle.
+pub fn roundtrip<M: IscFrame>(msg: M) -> CanMessage {
+    let frame: MockFrame = msg.into_frame().expect("message does not encode");
+    CanMessage::from_frame(frame).expect("frame does not decode")
+}
+
+/// A message with its exact encoding, pinning the wire format.
+#[derive(Copy, Clone, Debug)]
+pub struct Golden {
+    pub msg: CanMessage,
+    pub id: u32,
+    /// The whole payload, so also the DLC.
+    pub data: &'static [u8],
+}
+
+/// Golden vectors for the messages compiled in, with edge values. Every message has at least
+/// one, which the crate's tests check, so a new message needs one here.
+pub const GOLDEN: &[Golden] = &[
+    Golden {
+        msg: CanMessage::EstopAll(EstopAll {
+            source: EstopSource::RemoteKillSwitch,
+        }),
+        id: 0x0,
+        data: &[0x00],
+    },
+    Golden {
+        msg: CanMessage::EstopAll(EstopAll {
+            source: EstopSource::FirmwareWatchdog,
+        }),
+        id: 0x0,
+        data: &[0x02],
+    },
+    #[cfg(feature = "msgs-brake")]
+    Golden {
+        msg: CanMessage::SetBrake(SetBrake {
+            centi_percent: 7500,
+        }),
+        id: 0x1,
+        data: &[0x4C, 0x1D],
+    },
+    #[cfg(feature = "msgs-brake")]
+    Golden {
+        msg: CanMessage::SetBrake(SetBrake {
+            centi_percent: 1234,
+        }),
+        id: 0x1,
+        data: &[0xD2, 0x04],
+    },
+    #[cfg(feature = "msgs-brake")]
+    Golden {
+        msg: CanMessage::SetBrake(SetBrake { centi_percent: 0 }),
+        id: 0x1,
+        data: &[0x00, 0x00],
+    },
+    #[cfg(feature = "msgs-brake")]
+    Golden {
+        msg: CanMessage::SetBrake(SetBrake {
+            centi_percent: 10_000,
+        }),
+        id: 0x1,
+        data: &[0x10, 0x27],
+    },
+    #[cfg(feature = "msgs-brake")]
+    Golden {
+        msg: CanMessage::LockBrake(LockBrake {}),
+        id: 0x2,
+        data: &[],
+    },
+    #[cfg(feature = "msgs-brake")]
+    Golden {
+        msg: CanMessage::UnlockBrake(UnlockBrake {}),
+        id: 0x3,
+        data: &[],
+    },
+    #[cfg(feature = "msgs-steering")]
+    Golden {
+        msg: CanMessage::SetAngle(SetAngle { angle: 1.5 }),
+        id: 0x4,
+        data: &[0x00, 0x00, 0xC0, 0x3F],
+    },
+    #[cfg(feature = "msgs-steering")]
+    Golden {
+        msg: CanMessage::SetAngle(SetAngle { angle: -20.0 }),
+        id: 0x4,
+        data: &[0x00, 0x00, 0xA0, 0xC1],
+    },
+    #[cfg(feature = "msgs-steering")]
+    Golden {
+        msg: CanMessage::SetAngle(SetAngle { angle: 0.0 }),
+        id: 0x4,
+        data: &[0x00, 0x00, 0x00, 0x00],
+    },
+    #[cfg(feature = "msgs-steering")]
+    Golden {
+        msg: CanMessage::GetAngle(GetAngle { angle: -12.25 }),
+        id: 0x5,
+        data: &[0x00, 0x00, 0x44, 0xC1],
+    },
+    #[cfg(feature = "msgs-steering")]
+    Golden {
+        msg: CanMessage::GetAngle(GetAngle { angle: 20.0 }),
+        id: 0x5,
+        data: &[0x00, 0x00, 0xA0, 0x41],
+    },
+    #[cfg(feature = "msgs-drive")]
+    Golden {
+        msg: CanMessage::SetSpeed(SetSpeed {
+            centi_percent: 3000,
+        }),
+        id: 0x6,
+        data: &[0xB8, 0x0B],
+    },
+    #[cfg(feature = "msgs-drive")]
+    Golden {
+        msg: CanMessage::SetSpeed(SetSpeed { centi_percent: 1 }),
+        id: 0x6,
+        data: &[0x01, 0x00],
+    },
+    #[cfg(feature = "msgs-drive")]
+    Golden {
+        msg: CanMessage::SetSpeed(SetSpeed { centi_percent: 0 }),
+        id: 0x6,
+        data: &[0x00, 0x00],
+    },
+    #[cfg(feature = "msgs-drive")]
+    Golden {
+        msg: CanMessage::SetSpeed(SetSpeed {
+            centi_percent: 10_000,
+        }),
+        id: 0x6,
+        data: &[0x10, 0x27],
+    },
+    #[cfg(feature = "msgs-drive")]
+    Golden {
+        msg: CanMessage::EncoderCount(EncoderCount {
+            count: 0x1234,
+            velocity: 10.2,
+        }),
+        id: 0x7,
+        data: &[0x34, 0x12, 0x33, 0x33, 0x23, 0x41],
+    },
+    #[cfg(feature = "msgs-drive")]
+    Golden {
+        msg: CanMessage::EncoderCount(EncoderCount {
+            count: u16::MAX,
+            velocity: 0.0,
+        }),
+        id: 0x7,
+        data: &[0xFF, 0xFF, 0x00, 0x00, 0x00, 0x00],
+    },
+    Golden {
+        msg: CanMessage::TrainingMode(TrainingMode {}),
+        id: 0x8,
+        data: &[],
+    },
+    #[cfg(feature = "msgs-drive")]
+    Golden {
+        msg: CanMessage::MotorTemperature(MotorTemperature { temp: -125 }),
+        id: 0x9,
+        data: &[0x83, 0xFF],
+    },
+    #[cfg(feature = "msgs-drive")]
+    Golden {
+        msg: CanMessage::MotorTemperature(MotorTemperature { temp: i16::MAX }),
+        id: 0x9,
+        data: &[0xFF, 0x7F],
+    },
+    #[cfg(feature = "msgs-steering")]
+    Golden {
+        msg: CanMessage::SetAngleCompact(SetAngleCompact { angle: 482 }),
+        id: 0xA,
+        data: &[0xE2, 0x01],
+    },
+    #[cfg(feature = "msgs-steering")]
+    Golden {
+        msg: CanMessage::SetAngleCompact(SetAngleCompact { angle: -2000 }),
+        id: 0xA,
+        data: &[0x30, 0xF8],
+    },
+    #[cfg(feature = "msgs-steering")]
+    Golden {
+        msg: CanMessage::GetAngleCompact(GetAngleCompact { angle: -125 }),
+        id: 0xB,
+        data: &[0x83, 0xFF],
+    },
+    #[cfg(feature = "msgs-power")]
+    Golden {
+        msg: CanMessage::CellVoltages(CellVoltages::CellVoltages0(CellVoltages0 {
+            cell_1: 3700,
+            cell_2: 3712,
+            cell_3: 3698,
+        })),
+        id: 0xC,
+        data: &[0x00, 0x74, 0x0E, 0x80, 0x0E, 0x72, 0x0E],
+    },
+    #[cfg(feature = "msgs-power")]
+    Golden {
+        msg: CanMessage::CellVoltages(CellVoltages::CellVoltages1(CellVoltages1 {
+            cell_4: 3700,
+            cell_5: 3712,
+            cell_6: u16::MAX,
+        })),
+        id: 0xC,
+        data: &[0x01, 0x74, 0x0E, 0x80, 0x0E, 0xFF, 0xFF],
+    },
+    #[cfg(feature = "msgs-drive")]
+    Golden {
+        msg: CanMessage::ImuRates(ImuRates {
+            yaw_rate: -200,
+            lateral_accel: 3000,
+            sequence: 0x1234,
+        }),
+        id: 0xD,
+        data: &[0xFF, 0x38, 0x0B, 0xB8, 0x12, 0x34],
+    },
+    #[cfg(feature = "msgs-drive")]
+    Golden {
+        msg: CanMessage::ImuRates(ImuRates {
+            yaw_rate: i16::MIN,
+            lateral_accel: -1000,
+            sequence: u16::MAX,
+        }),
+        id: 0xD,
+        data: &[0x80, 0x00, 0xFC, 0x18, 0xFF, 0xFF],
+    },
+    Golden {
+        msg: CanMessage::Heartbeat(Heartbeat {
+            node: crate::NodeId::Steering,
+            faults: Faults::empty(),
+            uptime: 3600,
+        }),
+        id: 0xE,
+        data: &[0x01, 0x00, 0x10, 0x0E],
+    },
+    Golden {
+        msg: CanMessage::Heartbeat(Heartbeat {
+            node: crate::NodeId::Encoder,
+            faults: Faults::from_bits(Faults::DEFINED),
+            uptime: 0,
+        }),
+        id: 0xE,
+        data: &[0x04, 0x3F, 0x00, 0x00],
+    },
+    Golden {
+        msg: CanMessage::AutonDisable(AutonDisable {}),
+        id: 0xF,
+        data: &[],
+    },
+];
+
+/// Payloads in legacy layouts that still decode, with the message each decodes to. These are
+/// the golden vectors from before the layout changed, so must never change themselves.
+pub const LEGACY_GOLDEN: &[Golden] = &[
+    #[cfg(feature = "msgs-brake")]
+    Golden {
+        msg: CanMessage::SetBrake(SetBrake {
+            centi_percent: 7500,
+        }),
+        id: 0x1,
+        data: &[75],
+    },
+    #[cfg(feature = "msgs-brake")]
+    Golden {
+        msg: CanMessage::SetBrake(SetBrake { centi_percent: 0 }),
+        id: 0x1,
+        data: &[0],
+    },
+    #[cfg(feature = "msgs-brake")]
+    Golden {
+        msg: CanMessage::SetBrake(SetBrake {
+            centi_percent: 10_000,
+        }),
+        id: 0x1,
+        data: &[100],
+    },
+    #[cfg(feature = "msgs-drive")]
+    Golden {
+        msg: CanMessage::SetSpeed(SetSpeed {
+            centi_percent: 3000,
+        }),
+        id: 0x6,
+        data: &[30],
+    },
+    #[cfg(feature = "msgs-drive")]
+    Golden {
+        msg: CanMessage::SetSpeed(SetSpeed { centi_percent: 0 }),
+        id: 0x6,
+        data: &[0],
+    },
+    #[cfg(feature = "msgs-drive")]
+    Golden {
+        msg: CanMessage::SetSpeed(SetSpeed {
+            centi_percent: 10_000,
+        }),
+        id: 0x6,
+        data: &[100],
+    },
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_data_frame_contract() {
+        let id = ExtendedId::new(0x1234).unwrap();
+        let frame = MockFrame::new(id, &[1, 2, 3]).unwrap();
+
+        assert_eq!(frame.id(), Id::Extended(id));
+        assert!(frame.is_extended() && !frame.is_standard());
+        assert!(frame.is_data_frame() && !frame.is_remote_frame());
+        assert_eq!(frame.dlc(), 3);
+        assert_eq!(frame.data(), [1, 2, 3]);
+
+        assert!(MockFrame::new(id, &[0; 8]).is_some());
+        assert!(MockFrame::new(id, &[0; 9]).is_none());
+        assert_eq!(MockFrame::new(id, &[]).unwrap().dlc(), 0);
+
+        let frame = MockFrame::standard(0x7FF, &[4]);
+        assert!(frame.is_standard() && !frame.is_extended());
+        assert_eq!(frame.raw_id(), 0x7FF);
+    }
+
+    #[test]
+    fn test_remote_frame_contract() {
+        let id = StandardId::new(0x12).unwrap();
+        let frame = MockFrame::new_remote(id, 4).unwrap();
+
+        assert!(frame.is_remote_frame() && !frame.is_data_frame());
+        assert_eq!(frame.dlc(), 4);
+        assert!(frame.data().is_empty());
+
+        assert!(MockFrame::new_remote(id, 8).is_some());
+        assert!(MockFrame::new_remote(id, 9).is_none());
+    }
+
+    #[test]
+    fn test_inconsistent() {
+        let frame = MockFrame::extended(0x5, &[1, 2]).with_dlc(7);
+        assert_eq!((frame.dlc(), frame.data()), (7, &[1, 2][..]));
+
+        let frame = MockFrame::extended(0x5, &[1, 2]).with_remote(true);
+        assert!(frame.is_remote_frame());
+        assert_eq!(frame.data(), [1, 2]);
+
+        // A length past the buffer stops at its end
+        let frame = MockFrame { len: 12, ..frame };
+        assert_eq!(frame.data().len(), 8);
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let msg = roundtrip(AutonDisable {});
+        assert!(matches!(msg, CanMessage::AutonDisable(_)));
+        assert_eq!(msg.to_parts().unwrap().2, AutonDisable::DLC);
+    }
+
+    #[test]
+    fn test_golden_encode() {
+        for golden in GOLDEN {
+            let frame: MockFrame = golden.msg.to_parts().and_then(frame_from_parts).unwrap();
+            assert_eq!(frame.raw_id(), golden.id, "{golden:?}");
+            assert_eq!(frame.data(), golden.data, "{golden:?}");
+            assert_eq!(frame.dlc(), golden.data.len(), "{golden:?}");
+        }
+    }
+
+    #[test]
+    fn test_golden_decode() {
+        extern crate std;
+        use std::format;
+
+        for golden in GOLDEN {
+            let msg = CanMessage::from_frame(MockFrame::extended(golden.id, golden.data));
+            assert_eq!(
+                format!("{:?}", msg.unwrap()),
+                format!("{:?}", golden.msg),
+                "{golden:?}"
+            );
+        }
+    }
+}
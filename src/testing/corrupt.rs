@@ -0,0 +1,272 @@
+use super::MockFrame;
+use crate::{frame_from_parts, CanMessage};
+use embedded_hal::can::{ExtendedId, Frame, Id, StandardId};
+
+/// An extended ID no message uses.
+pub const UNKNOWN_ID: u32 = 0x1FFF_FFFF;
+
+/// How [`FrameCorruptor`] changed a frame, and how this crate treats the result.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Corruption {
+    /// The last `n` bytes removed. Rejected, unless that leaves the length of the message's
+    /// legacy layout, which is then decoded as one.
+    Truncated(usize),
+    /// `n` garbage bytes appended. Rejected, since the payload must be exactly the DLC.
+    Extended(usize),
+    /// One bit of the payload flipped. Accepted as the flipped value, unless that makes a float
+    /// NaN or infinite, or selects a page that does not exist. A float flipped to a subnormal or
+    /// -0.0 is read as 0.0, like [`DecodePolicy::Normalize`](crate::DecodePolicy) does.
+    BitFlip { byte: usize, bit: u8 },
+    /// The ID replaced with another. Rejected if no message uses it, like [`UNKNOWN_ID`].
+    UnknownId(u32),
+    /// The low 11 bits of the ID as a standard ID. Rejected, since every message is extended.
+    StandardId,
+    /// The RTR bit set, keeping the data. Decoded from the data as usual, since the bit is not
+    /// read. Real remote frames have no data, so only decode as messages with no payload.
+    Remote,
+    /// Every payload byte set to 0xFF. Treated like a bit flip.
+    AllOnes,
+}
+
+/// Makes labeled, systematically corrupted copies of a valid frame, for negative tests of
+/// decoding.
+#[derive(Copy, Clone, Debug)]
+pub struct FrameCorruptor {
+    frame: MockFrame,
+}
+
+impl FrameCorruptor {
+    /// Corrupts copies of `frame`.
+    pub fn new(frame: MockFrame) -> Self {
+        Self { frame }
+    }
+
+    /// Corrupts copies of the frame a message encodes to.
+    ///
+    /// # Panics
+    ///
+    /// If the message does not encode.
+    pub fn from_message(msg: impl Into<CanMessage>) -> Self {
+        let parts = msg.into().to_parts().expect("message does not encode");
+        Self::new(frame_from_parts(parts).expect("message does not fit a frame"))
+    }
+
+    /// The frame being corrupted.
+    pub fn frame(&self) -> MockFrame {
+        self.frame
+    }
+
+    /// Every corruption of the frame, in the order of the [`Corruption`] variants: each
+    /// truncation and extension up to 8 bytes, each bit flip, then one of each other kind.
+    /// Payloads of no bytes have no flips and no [`Corruption::AllOnes`].
+    pub fn corruptions(&self) -> impl Iterator<Item = (Corruption, MockFrame)> + '_ {
+        use Corruption::*;
+
+        let len = self.frame.data().len();
+        let flips = (0..len * 8).map(|i| BitFlip {
+            byte: i / 8,
+            bit: (i % 8) as u8,
+        });
+        (1..=len)
+            .map(Truncated)
+            .chain((1..=8 - len).map(Extended))
+            .chain(flips)
+            .chain([UnknownId(UNKNOWN_ID), StandardId, Remote])
+            .chain((len > 0).then_some(AllOnes))
+            .map(|c| (c, self.apply(c)))
+    }
+
+    /// The frame corrupted one way. Sizes are clamped to what fits a frame.
+    ///
+    /// # Panics
+    ///
+    /// If a flipped byte is past the payload, or a replacement ID over 29 bits.
+    pub fn apply(&self, corruption: Corruption) -> MockFrame {
+        let frame = self.frame;
+        let len = frame.data().len();
+        let with_data = |data: &[u8]| MockFrame {
+            remote: frame.remote,
+            ..MockFrame::new(frame.id, data).unwrap()
+        };
+
+        match corruption {
+            Corruption::Truncated(n) => with_data(&frame.data[..len - n.min(len)]),
+            Corruption::Extended(n) => {
+                let mut data = frame.data;
+                for (i, b) in data.iter_mut().enumerate().skip(len) {
+                    *b = 0xA5 ^ i as u8;
+                }
+                with_data(&data[..(len + n).min(8)])
+            }
+            Corruption::BitFlip { byte, bit } => {
+                assert!(byte < len, "bit flip past the payload");
+                let mut data = frame.data;
+                data[byte] ^= 1 << (bit % 8);
+                with_data(&data[..len])
+            }
+            Corruption::UnknownId(id) => MockFrame {
+                id: Id::Extended(ExtendedId::new(id).expect("ID over 29 bits")),
+                ..frame
+            },
+            Corruption::StandardId => {
+                let raw = match frame.id {
+                    Id::Standard(id) => id.as_raw(),
+                    Id::Extended(id) => (id.as_raw() & 0x7FF) as u16,
+                };
+                MockFrame {
+                    id: Id::Standard(StandardId::new(raw).unwrap()),
+                    ..frame
+                }
+            }
+            Corruption::Remote => frame.with_remote(true),
+            Corruption::AllOnes => with_data(&[0xFF; 8][..len]),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "msgs-all"))]
+mod test {
+    use super::*;
+    use crate::testing::GOLDEN;
+    use crate::*;
+
+    /// The payload a corrupted one is accepted as, with floats normalized, or `None` if it fails
+    /// the checks decoding makes beyond its length: finite floats, a page that exists, a known
+    /// node or estop source, and fields in range.
+    fn accepted_payload(info: &MessageInfo, data: &[u8]) -> Option<[u8; 8]> {
+        let known = match info.id {
+            Heartbeat::ID => NodeId::from_raw(data[0]).is_some(),
+            EstopAll::ID => EstopSource::from_raw(data[0]).is_some(),
+            _ => true,
+        };
+        if !known {
+            return None;
+        }
+        let mut pages = info.signals.iter().filter_map(|s| match s.mux {
+            Multiplex::Page(page) => Some(page),
+            _ => None,
+        });
+        if pages.clone().next().is_some() && !pages.any(|p| data.first() == Some(&p)) {
+            return None;
+        }
+
+        let mut out = [0; 8];
+        out[..data.len()].copy_from_slice(data);
+        for s in info.signals {
+            match (s.kind, s.mux) {
+                (_, Multiplex::Page(page)) if data.first() != Some(&page) => {}
+                (SignalKind::Float, _) => {
+                    let field = &mut out[s.start..s.start + 4];
+                    let v = f32::from_le_bytes(field.try_into().unwrap());
+                    let v = DecodePolicy::Normalize.float(v)?;
+                    field.copy_from_slice(&v.to_le_bytes());
+                }
+                _ => {}
+            }
+        }
+        let msg =
+            CanMessage::from_parts_with_policy(info.id, &out[..data.len()], DecodePolicy::Raw);
+        msg.ok()?.check_range().ok()?;
+        Some(out)
+    }
+
+    #[test]
+    fn test_corpus() {
+        for golden in GOLDEN {
+            let info = MESSAGES.iter().find(|m| m.id == golden.id).unwrap();
+            let corruptor = FrameCorruptor::from_message(golden.msg);
+            let original = corruptor.frame();
+            assert_eq!(original.data(), golden.data);
+
+            for (corruption, frame) in corruptor.corruptions() {
+                let decoded = CanMessage::from_frame(frame).map(|m| m.to_parts().unwrap());
+                let accepted_as = |data: &[u8]| {
+                    let (id, buf, len) = decoded.ok()?;
+                    (id.as_raw() == golden.id && &buf[..len] == data).then_some(())
+                };
+                let label = (info.name, corruption);
+
+                match corruption {
+                    Corruption::UnknownId(id) => {
+                        assert!(
+                            matches!(
+                                CanMessage::from_frame(frame),
+                                Err(ConvertErr::UnknownId { id: got }) if got == id
+                            ),
+                            "{label:?}"
+                        )
+                    }
+                    Corruption::StandardId => {
+                        assert!(
+                            matches!(CanMessage::from_frame(frame), Err(ConvertErr::InvalidFrame)),
+                            "{label:?}"
+                        )
+                    }
+                    Corruption::Truncated(_) if info.legacy_dlc == Some(frame.data().len()) => {
+                        // Read in the legacy layout, where the first byte may be out of range
+                        assert!(
+                            matches!(
+                                CanMessage::from_frame(frame),
+                                Ok(_) | Err(ConvertErr::OutOfRange { .. })
+                            ),
+                            "{label:?}"
+                        )
+                    }
+                    Corruption::Truncated(n) | Corruption::Extended(n) => {
+                        let got = frame.data().len();
+                        assert_eq!(got.abs_diff(golden.data.len()), n, "{label:?}");
+                        assert!(
+                            matches!(
+                                CanMessage::from_frame(frame),
+                                Err(ConvertErr::WrongLength { expected, got: g })
+                                    if expected == golden.data.len() && g == got
+                            ),
+                            "{label:?}"
+                        );
+                    }
+                    Corruption::Remote => {
+                        assert!(frame.is_remote_frame());
+                        assert!(accepted_as(golden.data).is_some(), "{label:?}");
+                    }
+                    Corruption::BitFlip { .. } | Corruption::AllOnes => {
+                        assert_ne!(frame.data(), golden.data, "{label:?}");
+                        match accepted_payload(info, frame.data()) {
+                            Some(data) => assert!(
+                                accepted_as(&data[..frame.data().len()]).is_some(),
+                                "{label:?}"
+                            ),
+                            None => assert!(decoded.is_err(), "{label:?}"),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_corruptions() {
+        let corruptor = FrameCorruptor::new(SetBrakeLegacy { percent: 75 }.into_frame().unwrap());
+        let kind = |i| corruptor.corruptions().nth(i).map(|(c, _)| c);
+        assert_eq!(corruptor.corruptions().count(), 1 + 7 + 8 + 4);
+        assert_eq!(kind(0), Some(Corruption::Truncated(1)));
+        assert_eq!(kind(8), Some(Corruption::BitFlip { byte: 0, bit: 0 }));
+        assert_eq!(kind(19), Some(Corruption::AllOnes));
+
+        let frame = corruptor.apply(Corruption::BitFlip { byte: 0, bit: 7 });
+        assert_eq!(frame.data(), [0x4B | 0x80]);
+        assert_eq!(frame.dlc(), 1);
+        let frame = corruptor.apply(Corruption::Extended(2));
+        assert_eq!(frame.data(), [0x4B, 0xA4, 0xA7]);
+        assert_eq!(corruptor.apply(Corruption::Extended(9)).data().len(), 8);
+        assert!(corruptor.apply(Corruption::Truncated(3)).data().is_empty());
+        assert_eq!(
+            corruptor.apply(Corruption::StandardId).id(),
+            Id::Standard(StandardId::new(1).unwrap())
+        );
+        assert!(REGISTRY.get(UNKNOWN_ID).is_none());
+
+        // Nothing to flip
+        let empty = FrameCorruptor::from_message(AutonDisable {});
+        assert_eq!(empty.corruptions().count(), 8 + 3);
+    }
+}
@@ -0,0 +1,398 @@
+extern crate std;
+
+use crate::{CanMessage, IscFrame};
+use core::fmt;
+use std::boxed::Box;
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+/// A check on the fields of a message.
+type Predicate = Box<dyn Fn(&CanMessage) -> bool>;
+
+/// One message of a [`SequenceExpectation`].
+struct Step {
+    id: u32,
+    name: &'static str,
+    predicate: Option<(&'static str, Predicate)>,
+    min_ms: Option<u32>,
+    max_ms: Option<u32>,
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name)?;
+        if let Some((desc, _)) = &self.predicate {
+            write!(f, " where {desc}")?;
+        }
+        match (self.min_ms, self.max_ms) {
+            (Some(min), Some(max)) => write!(f, ", {min} to {max} ms after the last"),
+            (Some(min), None) => write!(f, ", at least {min} ms after the last"),
+            (None, Some(max)) => write!(f, ", within {max} ms of the last"),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// How a recorded stream first differed from a [`SequenceExpectation`].
+///
+/// `step` is the index of the expected message in the pattern, and `index` that of the message
+/// received in the stream. The `Display` impl gives both, with what was expected.
+#[derive(Clone, Debug)]
+pub enum Deviation {
+    /// The stream ended before the step was seen.
+    Missing { step: usize, expected: String },
+    /// Another message of the pattern came first.
+    OutOfOrder {
+        step: usize,
+        index: usize,
+        expected: String,
+        actual: CanMessage,
+    },
+    /// The expected message came, but failed its predicate.
+    Mismatch {
+        step: usize,
+        index: usize,
+        expected: String,
+        actual: CanMessage,
+    },
+    /// The expected message came outside its timing bounds, `elapsed_ms` after the last step.
+    Timing {
+        step: usize,
+        index: usize,
+        expected: String,
+        elapsed_ms: u32,
+    },
+}
+
+impl fmt::Display for Deviation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Deviation::Missing { step, expected } => {
+                write!(f, "step {step}: expected {expected}, but the stream ended")
+            }
+            Deviation::OutOfOrder {
+                step,
+                index,
+                expected,
+                actual,
+            }
+            | Deviation::Mismatch {
+                step,
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "step {step}: expected {expected}, got {actual:?} at index {index}"
+            ),
+            Deviation::Timing {
+                step,
+                index,
+                expected,
+                elapsed_ms,
+            } => write!(
+                f,
+                "step {step}: expected {expected}, got it {elapsed_ms} ms after at index {index}"
+            ),
+        }
+    }
+}
+
+/// An expected pattern of messages, checked against a recorded stream of `(ms, message)`, like
+/// from [`SimBus::drain`](super::SimBus::drain) or a log of a real bus.
+///
+/// Each step is a message type, optionally with a predicate on its fields and bounds on its
+/// time since the step before. The stream must contain the steps in order. Messages of types not
+/// in the pattern are ignored, so other traffic may be interleaved, but one of a type in the
+/// pattern that is not the next step is a deviation.
+///
+/// ```
+/// # use phnx_candefs::*;
+/// # use phnx_candefs::testing::SequenceExpectation;
+/// let expected = SequenceExpectation::new()
+///     .then::<SetAngle>()
+///     .then::<GetAngle>()
+///     .matching("angle near 10", |m| {
+///         matches!(m, CanMessage::GetAngle(a) if (a.angle - 10.0).abs() < 0.5)
+///     })
+///     .within_ms(100);
+///
+/// let stream = [
+///     (0, SetAngle { angle: 10.0 }.into()),
+///     (40, GetAngle { angle: 9.8 }.into()),
+/// ];
+/// assert!(expected.check(stream).is_ok());
+/// ```
+#[derive(Default)]
+pub struct SequenceExpectation {
+    steps: Vec<Step>,
+}
+
+impl SequenceExpectation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expects a message of type `M` next.
+    pub fn then<M: IscFrame>(mut self) -> Self {
+        self.steps.push(Step {
+            id: M::ID,
+            name: M::NAME,
+            predicate: None,
+            min_ms: None,
+            max_ms: None,
+        });
+        self
+    }
+
+    /// Requires the last step to pass `predicate`, described by `desc` in reports.
+    ///
+    /// # Panics
+    ///
+    /// If there are no steps yet.
+    pub fn matching(
+        mut self,
+        desc: &'static str,
+        predicate: impl Fn(&CanMessage) -> bool + 'static,
+    ) -> Self {
+        self.last().predicate = Some((desc, Box::new(predicate)));
+        self
+    }
+
+    /// Requires the last step to come at most `ms` after the step before.
+    ///
+    /// # Panics
+    ///
+    /// If there are no steps yet.
+    pub fn within_ms(mut self, ms: u32) -> Self {
+        self.last().max_ms = Some(ms);
+        self
+    }
+
+    /// Requires the last step to come at least `ms` after the step before.
+    ///
+    /// # Panics
+    ///
+    /// If there are no steps yet.
+    pub fn after_ms(mut self, ms: u32) -> Self {
+        self.last().min_ms = Some(ms);
+        self
+    }
+
+    fn last(&mut self) -> &mut Step {
+        self.steps.last_mut().expect("no step to constrain")
+    }
+
+    /// Checks a stream against the pattern, returning the first deviation. Messages after the
+    /// last step are not checked.
+    pub fn check(
+        &self,
+        stream: impl IntoIterator<Item = (u32, CanMessage)>,
+    ) -> Result<(), Deviation> {
+        let mut steps = self.steps.iter().enumerate().peekable();
+        let mut last_ms = None;
+
+        for (index, (at_ms, msg)) in stream.into_iter().enumerate() {
+            let Some(&(step, expected)) = steps.peek() else {
+                return Ok(());
+            };
+            let id = msg.id();
+
+            if id != expected.id {
+                if self.steps.iter().any(|s| s.id == id) {
+                    return Err(Deviation::OutOfOrder {
+                        step,
+                        index,
+                        expected: expected.to_string(),
+                        actual: msg,
+                    });
+                }
+                continue;
+            }
+
+            if let Some((_, predicate)) = &expected.predicate {
+                if !predicate(&msg) {
+                    return Err(Deviation::Mismatch {
+                        step,
+                        index,
+                        expected: expected.to_string(),
+                        actual: msg,
+                    });
+                }
+            }
+            if let Some(last_ms) = last_ms {
+                let elapsed_ms = at_ms.saturating_sub(last_ms);
+                let early = expected.min_ms.is_some_and(|min| elapsed_ms < min);
+                let late = expected.max_ms.is_some_and(|max| elapsed_ms > max);
+                if early || late {
+                    return Err(Deviation::Timing {
+                        step,
+                        index,
+                        expected: expected.to_string(),
+                        elapsed_ms,
+                    });
+                }
+            }
+
+            last_ms = Some(at_ms);
+            steps.next();
+        }
+
+        match steps.next() {
+            Some((step, expected)) => Err(Deviation::Missing {
+                step,
+                expected: expected.to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl fmt::Debug for SequenceExpectation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.steps.iter().map(|s| s.to_string()))
+            .finish()
+    }
+}
+
+#[cfg(all(test, feature = "msgs-all"))]
+mod test {
+    use super::*;
+    use crate::testing::{Link, SimBus};
+    use crate::*;
+    use std::vec;
+
+    /// A steering command, answered by feedback near the commanded angle within 100 ms.
+    fn steering() -> SequenceExpectation {
+        SequenceExpectation::new()
+            .then::<SetAngle>()
+            .then::<GetAngle>()
+            .matching(
+                "angle within 0.5 of 10",
+                |m| matches!(m, CanMessage::GetAngle(a) if (a.angle - 10.0).abs() <= 0.5),
+            )
+            .within_ms(100)
+    }
+
+    #[test]
+    fn test_steering_feedback() {
+        let mut bus = SimBus::new(1);
+        let [interface, steering_node, logger] = [bus.attach(), bus.attach(), bus.attach()];
+        bus.set_link(
+            steering_node,
+            logger,
+            Link {
+                latency_ms: 5,
+                ..Link::PERFECT
+            },
+        );
+
+        bus.send(interface, SetAngle { angle: 10.0 });
+        bus.advance(20);
+        bus.send(
+            steering_node,
+            EncoderCount {
+                count: 1,
+                velocity: 0.0,
+            },
+        );
+        bus.advance(40);
+        bus.send(steering_node, GetAngle { angle: 9.7 });
+        bus.advance(10);
+
+        let recorded: Vec<_> = bus.drain(logger).collect();
+        assert_eq!(recorded.len(), 3);
+        steering().check(recorded).unwrap();
+    }
+
+    #[test]
+    fn test_missing() {
+        let err = steering()
+            .check([(0, SetAngle { angle: 10.0 }.into())])
+            .unwrap_err();
+        assert!(matches!(err, Deviation::Missing { step: 1, .. }));
+        assert_eq!(
+            err.to_string(),
+            "step 1: expected GetAngle where angle within 0.5 of 10, within 100 ms of the last, \
+             but the stream ended"
+        );
+    }
+
+    #[test]
+    fn test_out_of_order() {
+        let stream = vec![
+            (0, GetAngle { angle: 10.0 }.into()),
+            (10, SetAngle { angle: 10.0 }.into()),
+        ];
+        let err = steering().check(stream).unwrap_err();
+        assert!(matches!(
+            err,
+            Deviation::OutOfOrder {
+                step: 0,
+                index: 0,
+                actual: CanMessage::GetAngle(_),
+                ..
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "step 0: expected SetAngle, got GetAngle(GetAngle { angle: 10.0 }) at index 0"
+        );
+    }
+
+    #[test]
+    fn test_mismatch() {
+        let stream = [
+            (0, SetAngle { angle: 10.0 }.into()),
+            (30, GetAngle { angle: 2.0 }.into()),
+        ];
+        let err = steering().check(stream).unwrap_err();
+        assert!(matches!(
+            err,
+            Deviation::Mismatch {
+                step: 1,
+                index: 1,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_timing() {
+        let stream = [
+            (0, SetAngle { angle: 10.0 }.into()),
+            (150, GetAngle { angle: 10.0 }.into()),
+        ];
+        let err = steering().check(stream).unwrap_err();
+        assert!(matches!(
+            err,
+            Deviation::Timing {
+                step: 1,
+                index: 1,
+                elapsed_ms: 150,
+                ..
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "step 1: expected GetAngle where angle within 0.5 of 10, within 100 ms of the last, \
+             got it 150 ms after at index 1"
+        );
+
+        // Too soon
+        let debounced = SequenceExpectation::new()
+            .then::<LockBrake>()
+            .then::<UnlockBrake>()
+            .after_ms(50);
+        let stream = [(0, LockBrake {}.into()), (20, UnlockBrake {}.into())];
+        assert!(matches!(
+            debounced.check(stream),
+            Err(Deviation::Timing { elapsed_ms: 20, .. })
+        ));
+        assert_eq!(
+            std::format!("{debounced:?}"),
+            r#"["LockBrake", "UnlockBrake, at least 50 ms after the last"]"#
+        );
+    }
+}
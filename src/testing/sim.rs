@@ -0,0 +1,339 @@
+extern crate std;
+
+use super::MockFrame;
+use crate::{frame_from_parts, CanMessage};
+use std::collections::{BTreeMap, VecDeque};
+use std::vec::Vec;
+
+/// A node attached to a [`SimBus`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct NodeId(usize);
+
+/// How frames get from one node to another.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Link {
+    /// Chance each frame is lost, from 0 to 1.
+    pub drop_probability: f32,
+    /// Delay before a frame is received, in virtual ms.
+    pub latency_ms: u32,
+}
+
+impl Link {
+    /// Delivers every frame at once.
+    pub const PERFECT: Self = Self {
+        drop_probability: 0.0,
+        latency_ms: 0,
+    };
+}
+
+/// A frame on its way to a node.
+#[derive(Copy, Clone, Debug)]
+struct InFlight {
+    at_ms: u32,
+    id: u32,
+    /// Order sent, to break ties.
+    seq: u64,
+    to: NodeId,
+    frame: MockFrame,
+}
+
+/// A loopback bus of virtual nodes, with a virtual clock, for testing protocols between nodes.
+///
+/// Each message sent is encoded to a frame and delivered to every other node, decoded again,
+/// after the latency of its link. Frames due at the same time are received in arbitration
+/// order, lowest ID first, then in the order they were sent. Time only moves with
+/// [`advance`](SimBus::advance), and lost frames are chosen by a PRNG with a fixed seed, so runs
+/// are deterministic.
+#[derive(Clone, Debug)]
+pub struct SimBus {
+    now_ms: u32,
+    inboxes: Vec<VecDeque<(u32, CanMessage)>>,
+    links: BTreeMap<(NodeId, NodeId), Link>,
+    in_flight: Vec<InFlight>,
+    seq: u64,
+    rng: u64,
+}
+
+impl SimBus {
+    /// Creates a bus with no nodes at time 0, losing frames with a PRNG seeded by `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            now_ms: 0,
+            inboxes: Vec::new(),
+            links: BTreeMap::new(),
+            in_flight: Vec::new(),
+            seq: 0,
+            // Xorshift gets stuck at zero
+            rng: seed | 1,
+        }
+    }
+
+    /// Attaches a node, linked perfectly to every other node.
+    pub fn attach(&mut self) -> NodeId {
+        self.inboxes.push(VecDeque::new());
+        NodeId(self.inboxes.len() - 1)
+    }
+
+    /// Sets the link for frames from `from` to `to`, leaving the other direction as is.
+    pub fn set_link(&mut self, from: NodeId, to: NodeId, link: Link) {
+        self.links.insert((from, to), link);
+    }
+
+    pub fn link(&self, from: NodeId, to: NodeId) -> Link {
+        self.links
+            .get(&(from, to))
+            .copied()
+            .unwrap_or(Link::PERFECT)
+    }
+
+    /// Virtual time, in ms.
+    pub fn now_ms(&self) -> u32 {
+        self.now_ms
+    }
+
+    /// Sends a message from a node to all others. Frames with no latency are received on the
+    /// next [`advance`](SimBus::advance), even by zero.
+    ///
+    /// # Panics
+    ///
+    /// If the message does not encode.
+    pub fn send(&mut self, from: NodeId, msg: impl Into<CanMessage>) {
+        let parts = msg.into().to_parts().expect("message does not encode");
+        let frame: MockFrame = frame_from_parts(parts).expect("message does not fit a frame");
+
+        for to in (0..self.inboxes.len()).map(NodeId).filter(|&to| to != from) {
+            let link = self.link(from, to);
+            if self.chance() < link.drop_probability {
+                continue;
+            }
+
+            self.seq += 1;
+            self.in_flight.push(InFlight {
+                at_ms: self.now_ms.saturating_add(link.latency_ms),
+                id: frame.raw_id(),
+                seq: self.seq,
+                to,
+                frame,
+            });
+        }
+    }
+
+    /// Moves time forward, delivering every frame due by then.
+    ///
+    /// # Panics
+    ///
+    /// If a frame on the bus does not decode.
+    pub fn advance(&mut self, ms: u32) {
+        self.now_ms = self.now_ms.saturating_add(ms);
+
+        let (mut due, pending): (Vec<_>, Vec<_>) = self
+            .in_flight
+            .drain(..)
+            .partition(|f| f.at_ms <= self.now_ms);
+        self.in_flight = pending;
+
+        due.sort_by_key(|f| (f.at_ms, f.id, f.seq));
+        for f in due {
+            let msg = CanMessage::from_frame(f.frame).expect("frame on the bus does not decode");
+            self.inboxes[f.to.0].push_back((f.at_ms, msg));
+        }
+    }
+
+    /// Takes the next message received by a node, with the time it was received.
+    pub fn recv(&mut self, node: NodeId) -> Option<(u32, CanMessage)> {
+        self.inboxes.get_mut(node.0)?.pop_front()
+    }
+
+    /// Takes every message received by a node, oldest first.
+    pub fn drain(&mut self, node: NodeId) -> impl Iterator<Item = (u32, CanMessage)> + '_ {
+        self.inboxes
+            .get_mut(node.0)
+            .into_iter()
+            .flat_map(|q| q.drain(..))
+    }
+
+    /// A uniform value in `[0, 1)`.
+    fn chance(&mut self) -> f32 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (self.rng >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+#[cfg(all(test, feature = "msgs-all"))]
+mod test {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn test_arbitration_order() {
+        let mut bus = SimBus::new(1);
+        let [a, b, c] = [bus.attach(), bus.attach(), bus.attach()];
+        bus.set_link(
+            a,
+            c,
+            Link {
+                latency_ms: 5,
+                ..Link::PERFECT
+            },
+        );
+
+        bus.send(
+            a,
+            SetSpeed {
+                centi_percent: 1000,
+            },
+        );
+        bus.send(b, LockBrake {});
+        bus.send(
+            a,
+            EstopAll {
+                source: EstopSource::Ros,
+            },
+        );
+        bus.advance(0);
+
+        // Not to the sender, and lowest ID first
+        let ids =
+            |bus: &mut SimBus, node| -> Vec<u32> { bus.drain(node).map(|(_, m)| m.id()).collect() };
+        assert_eq!(ids(&mut bus, a), [LockBrake::ID]);
+        assert_eq!(ids(&mut bus, b), [EstopAll::ID, SetSpeed::ID]);
+        assert_eq!(ids(&mut bus, c), [LockBrake::ID]);
+
+        bus.advance(4);
+        assert!(bus.recv(c).is_none());
+        bus.advance(1);
+        assert_eq!(ids(&mut bus, c), [EstopAll::ID, SetSpeed::ID]);
+        assert_eq!(bus.now_ms(), 5);
+    }
+
+    #[test]
+    fn test_drop_probability() {
+        let run = |seed| {
+            let mut bus = SimBus::new(seed);
+            let [a, b] = [bus.attach(), bus.attach()];
+            bus.set_link(
+                a,
+                b,
+                Link {
+                    drop_probability: 0.25,
+                    ..Link::PERFECT
+                },
+            );
+            for _ in 0..1000 {
+                bus.send(a, TrainingMode {});
+            }
+            bus.advance(0);
+            bus.drain(b).count()
+        };
+
+        let received = run(7);
+        assert!((700..800).contains(&received), "{received}");
+        assert_eq!(run(7), received);
+    }
+
+    /// The interface board gates brake commands from ROS with a [`BrakeGate`] before putting
+    /// them on the bus, so the brake node only sees them while unlocked.
+    #[test]
+    fn test_brake_lock_flow() {
+        let mut bus = SimBus::new(1);
+        let [interface, brake] = [bus.attach(), bus.attach()];
+        let mut gate = BrakeGate::new();
+
+        let from_ros = [
+            CanMessage::from(SetBrake {
+                centi_percent: 2000,
+            }),
+            LockBrake {}.into(),
+            SetBrake {
+                centi_percent: 4000,
+            }
+            .into(),
+            SetBrake {
+                centi_percent: 6000,
+            }
+            .into(),
+            UnlockBrake {}.into(),
+            SetBrake {
+                centi_percent: 8000,
+            }
+            .into(),
+        ];
+        for msg in from_ros {
+            gate.observe(&msg);
+            if gate.filter(&msg) == GateDecision::Forward {
+                bus.send(interface, msg);
+            }
+            bus.advance(10);
+        }
+
+        let percents: Vec<u8> = bus
+            .drain(brake)
+            .filter_map(|(_, m)| match m {
+                CanMessage::SetBrake(b) => Some((b.centi_percent / 100) as u8),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(percents, [20, 80]);
+    }
+
+    /// The brake node expects a command from the interface board every 50 ms, and brakes fully
+    /// once 200 ms pass without one. There is no watchdog helper, so it keeps the time itself.
+    #[test]
+    fn test_watchdog_expiry() {
+        const TIMEOUT_MS: u32 = 200;
+
+        let mut bus = SimBus::new(3);
+        let [interface, brake] = [bus.attach(), bus.attach()];
+        let mut last_ms = 0;
+        let mut braking_at = None;
+
+        for tick in 0..40 {
+            // The interface board loses the bus after 500 ms
+            if tick == 10 {
+                bus.set_link(
+                    interface,
+                    brake,
+                    Link {
+                        drop_probability: 1.0,
+                        ..Link::PERFECT
+                    },
+                );
+            }
+            bus.send(
+                interface,
+                SetSpeed {
+                    centi_percent: 3000,
+                },
+            );
+            bus.advance(50);
+
+            while let Some((at_ms, _)) = bus.recv(brake) {
+                last_ms = at_ms;
+            }
+            if braking_at.is_none() && bus.now_ms() - last_ms > TIMEOUT_MS {
+                braking_at = Some(bus.now_ms());
+                bus.send(
+                    brake,
+                    SetBrake {
+                        centi_percent: 10_000,
+                    },
+                );
+            }
+        }
+
+        // The last command to get through was at 450 ms
+        assert_eq!((last_ms, braking_at), (450, Some(700)));
+        let seen: Vec<_> = bus.drain(interface).collect();
+        assert!(matches!(
+            seen[..],
+            [(
+                700,
+                CanMessage::SetBrake(SetBrake {
+                    centi_percent: 10_000
+                })
+            )]
+        ));
+    }
+}
@@ -0,0 +1,601 @@
+use crate::*;
+
+/// Messages each node sends periodically, as ID and period in ms, like on the kart: IMU and
+/// steering and drive feedback, the commands the interface board repeats to keep the watchdogs
+/// fed, and slow battery and temperature reports.
+pub const PERIODS_MS: [(u32, u32); 7] = [
+    (ImuRates::ID, 10),
+    (GetAngle::ID, 20),
+    (EncoderCount::ID, 20),
+    (SetAngle::ID, 50),
+    (SetSpeed::ID, 50),
+    (CellVoltages::ID, 250),
+    (MotorTemperature::ID, 1000),
+];
+
+/// Most anomalies a [`MessageStreamGenerator`] can have at once.
+pub const MAX_ANOMALIES: usize = 4;
+
+/// Most messages [`Anomaly::Reorder`] shuffles together.
+pub const MAX_REORDER: usize = 8;
+
+/// A fault injected into a [`MessageStreamGenerator`] stream.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Anomaly {
+    /// Nothing with an ID in `ids` is sent from `from_ms` until `until_ms`, like a node that
+    /// lost power. Its periodic messages resume on schedule after.
+    Silence {
+        ids: &'static [u32],
+        from_ms: u32,
+        until_ms: u32,
+    },
+    /// Each message is sent a second time, at the same time, with this chance from 0 to 1.
+    Duplicate(f32),
+    /// Messages are taken in random order from a window of this many, up to
+    /// [`MAX_REORDER`]. Times stay in order, so a message may come with a time before the one
+    /// it was sent at.
+    Reorder(usize),
+}
+
+/// What the stream's nodes are doing, so consecutive messages agree, like feedback following
+/// the last command.
+#[derive(Copy, Clone, Debug)]
+struct World {
+    target_deg: f32,
+    angle_deg: f32,
+    speed: u8,
+    count: u16,
+    temp: i16,
+    imu_sequence: u16,
+    page: u8,
+}
+
+/// An endless, timestamped stream of plausible bus traffic for soak tests, as `(ms, message)`
+/// like [`SimBus::drain`](super::SimBus::drain).
+///
+/// Each message of [`PERIODS_MS`] is sent at its period, moved by up to the jitter either way.
+/// Between them come occasional bursts of commands, like a sharp turn or braking, and edge
+/// case messages such as [`LockBrake`] or a full [`SetBrake`] at a configured chance. Then each
+/// [`Anomaly`] is applied. Every choice is made by a PRNG with a fixed seed, so a seed always
+/// gives the same stream. This does not need std.
+///
+/// ```
+/// # use phnx_candefs::testing::{Anomaly, MessageStreamGenerator};
+/// let stream = MessageStreamGenerator::new(42)
+///     .jitter_ms(2)
+///     .inject(Anomaly::Duplicate(0.01));
+/// let first_second = stream.take_while(|(at_ms, _)| *at_ms < 1000).count();
+/// assert!(first_second > 200);
+/// ```
+#[derive(Clone, Debug)]
+pub struct MessageStreamGenerator {
+    rng: u64,
+    /// Reorders with a PRNG of its own, so the messages are the same as without it.
+    shuffle_rng: u64,
+    jitter_ms: u32,
+    burst_interval_ms: u32,
+    burst_len: u32,
+    fault_probability: f32,
+    anomalies: [Option<Anomaly>; MAX_ANOMALIES],
+
+    /// Nominal time of the next send of each of [`PERIODS_MS`], and the jittered time it is
+    /// sent at.
+    nominal_ms: [u32; PERIODS_MS.len()],
+    send_ms: [u32; PERIODS_MS.len()],
+    next_burst_ms: u32,
+    burst_left: u32,
+    now_ms: u32,
+    world: World,
+
+    fault: Option<(u32, CanMessage)>,
+    duplicate: Option<(u32, CanMessage)>,
+    window: [Option<(u32, CanMessage)>; MAX_REORDER],
+}
+
+impl MessageStreamGenerator {
+    /// Creates a stream starting at time 0 with every message of [`PERIODS_MS`], no jitter, a
+    /// burst of 5 commands about once a second, edge cases at a chance of 0.001 per message,
+    /// and no anomalies.
+    pub fn new(seed: u64) -> Self {
+        let mut stream = Self {
+            // Xorshift gets stuck at zero
+            rng: seed | 1,
+            shuffle_rng: !seed | 1,
+            jitter_ms: 0,
+            burst_interval_ms: 1000,
+            burst_len: 5,
+            fault_probability: 0.001,
+            anomalies: [None; MAX_ANOMALIES],
+            nominal_ms: [0; PERIODS_MS.len()],
+            send_ms: [0; PERIODS_MS.len()],
+            next_burst_ms: 0,
+            burst_left: 0,
+            now_ms: 0,
+            world: World {
+                target_deg: 0.0,
+                angle_deg: 0.0,
+                speed: 0,
+                count: 0,
+                temp: 400,
+                imu_sequence: 0,
+                page: 0,
+            },
+            fault: None,
+            duplicate: None,
+            window: [None; MAX_REORDER],
+        };
+        stream.next_burst_ms = stream.burst_gap();
+        stream
+    }
+
+    /// Moves each periodic send by up to `ms` either way, but never before the message before.
+    ///
+    /// # Panics
+    ///
+    /// If `ms` is not less than every period.
+    pub fn jitter_ms(mut self, ms: u32) -> Self {
+        assert!(
+            PERIODS_MS.iter().all(|&(_, period)| ms < period),
+            "jitter not less than every period"
+        );
+        self.jitter_ms = ms;
+        self
+    }
+
+    /// Sends a burst of `len` commands 1 ms apart every `interval_ms` on average, or none if
+    /// either is 0.
+    pub fn bursts(mut self, interval_ms: u32, len: u32) -> Self {
+        self.burst_interval_ms = interval_ms;
+        self.burst_len = len;
+        self.burst_left = 0;
+        self.next_burst_ms = self.now_ms.saturating_add(self.burst_gap());
+        self
+    }
+
+    /// Follows each message with an edge case message, at the same time, with this chance from
+    /// 0 to 1.
+    pub fn faults(mut self, probability: f32) -> Self {
+        self.fault_probability = probability;
+        self
+    }
+
+    /// Adds an anomaly. Silences apply first, then duplicates, then reordering, whatever the
+    /// order they are added in.
+    ///
+    /// # Panics
+    ///
+    /// If there are already [`MAX_ANOMALIES`], or a reorder window is over [`MAX_REORDER`].
+    pub fn inject(mut self, anomaly: Anomaly) -> Self {
+        if let Anomaly::Reorder(window) = anomaly {
+            assert!(window <= MAX_REORDER, "reorder window over {MAX_REORDER}");
+        }
+        let slot = self.anomalies.iter_mut().find(|a| a.is_none());
+        *slot.expect("too many anomalies") = Some(anomaly);
+        self
+    }
+
+    /// The next message, before any duplicates or reordering.
+    fn source(&mut self) -> (u32, CanMessage) {
+        loop {
+            if let Some(fault) = self.fault.take() {
+                return fault;
+            }
+
+            let (i, &at_ms) = self
+                .send_ms
+                .iter()
+                .enumerate()
+                .min_by_key(|&(_, at_ms)| at_ms)
+                .unwrap();
+            let bursts = self.burst_len > 0 && self.burst_interval_ms > 0;
+            let msg = if bursts && self.next_burst_ms < at_ms {
+                self.now_ms = self.next_burst_ms;
+                self.burst()
+            } else {
+                self.now_ms = at_ms;
+                self.periodic(i)
+            };
+
+            let at_ms = self.now_ms;
+            if self.silenced(msg, at_ms) {
+                continue;
+            }
+            if self.chance() < self.fault_probability {
+                self.fault = Some((at_ms, self.edge_case()));
+            }
+            return (at_ms, msg);
+        }
+    }
+
+    /// The next message, with duplicates.
+    fn duplicated(&mut self) -> (u32, CanMessage) {
+        if let Some(duplicate) = self.duplicate.take() {
+            return duplicate;
+        }
+        let next = self.source();
+        let probability = self.anomalies.iter().fold(0.0, |p, a| match a {
+            Some(Anomaly::Duplicate(chance)) => p + chance,
+            _ => p,
+        });
+        if self.chance() < probability {
+            self.duplicate = Some(next);
+        }
+        next
+    }
+
+    /// Sends the periodic message `i`, and schedules the next.
+    fn periodic(&mut self, i: usize) -> CanMessage {
+        let (id, period) = PERIODS_MS[i];
+        self.nominal_ms[i] = self.nominal_ms[i].saturating_add(period);
+        let jitter = self.below(2 * self.jitter_ms + 1);
+        self.send_ms[i] = (self.nominal_ms[i] + jitter)
+            .saturating_sub(self.jitter_ms)
+            .max(self.now_ms);
+
+        let noise = [
+            self.noise(50),
+            self.noise(200),
+            self.noise(200),
+            self.noise(200),
+        ];
+        let world = &mut self.world;
+        match id {
+            ImuRates::ID => {
+                world.imu_sequence = world.imu_sequence.wrapping_add(1);
+                ImuRates {
+                    yaw_rate: (world.angle_deg * world.speed as f32) as i16 + noise[0],
+                    lateral_accel: noise[1],
+                    sequence: world.imu_sequence,
+                }
+                .into()
+            }
+            GetAngle::ID => {
+                // The steering motor moves toward the target a little each report
+                world.angle_deg += (world.target_deg - world.angle_deg) * 0.2;
+                GetAngle {
+                    angle: world.angle_deg,
+                }
+                .into()
+            }
+            EncoderCount::ID => {
+                world.count = world.count.wrapping_add(world.speed as u16 / 4);
+                EncoderCount {
+                    count: world.count,
+                    velocity: world.speed as f32 * 0.05,
+                }
+                .into()
+            }
+            SetAngle::ID => {
+                let step = (noise[0] / 5) as f32 / 10.0;
+                world.target_deg = (world.target_deg + step).clamp(-20.0, 20.0);
+                SetAngle {
+                    angle: world.target_deg,
+                }
+                .into()
+            }
+            SetSpeed::ID => {
+                let step = noise[0] / 20;
+                world.speed = (world.speed as i16 + step).clamp(0, 100) as u8;
+                SetSpeed {
+                    centi_percent: world.speed as u16 * 100,
+                }
+                .into()
+            }
+            CellVoltages::ID => {
+                let [a, b, c] = [noise[1], noise[2], noise[3]].map(|n| (3900 + n) as u16);
+                world.page ^= 1;
+                match world.page {
+                    1 => CellVoltages::from(CellVoltages0 {
+                        cell_1: a,
+                        cell_2: b,
+                        cell_3: c,
+                    }),
+                    _ => CellVoltages::from(CellVoltages1 {
+                        cell_4: a,
+                        cell_5: b,
+                        cell_6: c,
+                    }),
+                }
+                .into()
+            }
+            _ => {
+                let step = noise[0] / 10 + (world.speed as i16 - 40) / 20;
+                world.temp = (world.temp + step).clamp(200, 900);
+                MotorTemperature { temp: world.temp }.into()
+            }
+        }
+    }
+
+    /// Sends the next command of a burst, and schedules the next.
+    fn burst(&mut self) -> CanMessage {
+        if self.burst_left == 0 {
+            self.burst_left = self.burst_len;
+        }
+        self.burst_left -= 1;
+        self.next_burst_ms = self.now_ms.saturating_add(match self.burst_left {
+            0 => self.burst_gap(),
+            _ => 1,
+        });
+
+        if self.chance() < 0.5 {
+            self.world.target_deg = self.noise(200) as f32 / 10.0;
+            SetAngle {
+                angle: self.world.target_deg,
+            }
+            .into()
+        } else {
+            SetBrake {
+                centi_percent: self.below(MAX_CENTI_PERCENT as u32 + 1) as u16,
+            }
+            .into()
+        }
+    }
+
+    /// Time until the next burst, from half to one and a half of the interval.
+    fn burst_gap(&mut self) -> u32 {
+        let interval = self.burst_interval_ms;
+        (interval / 2 + self.below(interval.saturating_add(1))).max(1)
+    }
+
+    fn edge_case(&mut self) -> CanMessage {
+        match self.below(8) {
+            0 => AutonDisable {}.into(),
+            1 => LockBrake {}.into(),
+            2 => UnlockBrake {}.into(),
+            3 => TrainingMode {}.into(),
+            4 => SetBrake {
+                centi_percent: MAX_CENTI_PERCENT,
+            }
+            .into(),
+            5 => SetSpeed { centi_percent: 0 }.into(),
+            6 => SetAngle { angle: 20.0 }.into(),
+            _ => GetAngleCompact { angle: i16::MIN }.into(),
+        }
+    }
+
+    fn silenced(&self, msg: CanMessage, at_ms: u32) -> bool {
+        let id = msg.id();
+        self.anomalies.iter().any(|a| {
+            matches!(a, Some(Anomaly::Silence { ids, from_ms, until_ms })
+                if ids.contains(&id) && (*from_ms..*until_ms).contains(&at_ms))
+        })
+    }
+
+    fn reorder_window(&self) -> usize {
+        self.anomalies
+            .iter()
+            .filter_map(|a| match a {
+                Some(Anomaly::Reorder(window)) => Some(*window),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn chance(&mut self) -> f32 {
+        chance(&mut self.rng)
+    }
+
+    fn below(&mut self, n: u32) -> u32 {
+        below(&mut self.rng, n)
+    }
+
+    /// A uniform value in `[-n, n]`.
+    fn noise(&mut self, n: u16) -> i16 {
+        self.below(2 * n as u32 + 1) as i16 - n as i16
+    }
+}
+
+/// A uniform value in `[0, 1)`, from a xorshift PRNG.
+fn chance(rng: &mut u64) -> f32 {
+    *rng ^= *rng << 13;
+    *rng ^= *rng >> 7;
+    *rng ^= *rng << 17;
+    (*rng >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// A uniform value in `[0, n)`, or 0 if `n` is 0.
+fn below(rng: &mut u64, n: u32) -> u32 {
+    (chance(rng) * n as f32) as u32 % n.max(1)
+}
+
+impl Iterator for MessageStreamGenerator {
+    type Item = (u32, CanMessage);
+
+    /// The next message. There always is one, until time saturates at `u32::MAX` ms.
+    fn next(&mut self) -> Option<Self::Item> {
+        let window = self.reorder_window();
+        if window < 2 {
+            return Some(self.duplicated());
+        }
+
+        for i in 0..window {
+            if self.window[i].is_none() {
+                self.window[i] = Some(self.duplicated());
+            }
+        }
+        // The earliest time of the window, which is the oldest
+        let at_ms = self.window[..window].iter().flatten().map(|m| m.0).min()?;
+        let pick = below(&mut self.shuffle_rng, window as u32) as usize;
+        let (_, msg) = self.window[pick].take()?;
+        Some((at_ms, msg))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    extern crate std;
+    use std::vec::Vec;
+
+    /// A round second of a stream.
+    fn second(stream: MessageStreamGenerator) -> Vec<(u32, CanMessage)> {
+        stream.take_while(|(at_ms, _)| *at_ms < 1000).collect()
+    }
+
+    #[test]
+    fn test_pinned() {
+        let stream = MessageStreamGenerator::new(7).jitter_ms(3);
+        let ids: Vec<_> = stream
+            .clone()
+            .take(12)
+            .map(|(at_ms, msg)| (at_ms, msg.id()))
+            .collect();
+        assert_eq!(
+            ids,
+            [
+                (0, 0xD),
+                (0, 0x5),
+                (0, 0x7),
+                (0, 0x4),
+                (0, 0x6),
+                (0, 0xC),
+                (0, 0x9),
+                (10, 0xD),
+                (21, 0x7),
+                (22, 0xD),
+                (22, 0x5),
+                (32, 0xD),
+            ]
+        );
+
+        // The same seed gives the same stream, with the same values
+        let again = MessageStreamGenerator::new(7).jitter_ms(3);
+        let parts = |s: MessageStreamGenerator| -> Vec<_> {
+            s.take(500)
+                .map(|(at_ms, m)| (at_ms, m.to_parts().unwrap()))
+                .collect()
+        };
+        assert_eq!(parts(stream), parts(again));
+        let other = MessageStreamGenerator::new(8).jitter_ms(3);
+        assert_ne!(
+            parts(other),
+            parts(MessageStreamGenerator::new(7).jitter_ms(3))
+        );
+    }
+
+    #[test]
+    fn test_rates() {
+        let stream = MessageStreamGenerator::new(1)
+            .jitter_ms(4)
+            .bursts(0, 0)
+            .faults(0.0);
+        let msgs: Vec<_> = stream.take_while(|(at_ms, _)| *at_ms < 60_000).collect();
+        assert!(msgs.windows(2).all(|w| w[0].0 <= w[1].0));
+
+        for (msg_id, period) in PERIODS_MS {
+            let times: Vec<_> = msgs
+                .iter()
+                .filter(|(_, m)| m.id() == msg_id)
+                .map(|(at_ms, _)| *at_ms)
+                .collect();
+            // Jitter may move the send at 60 s into the run
+            let expected = 60_000 / period as usize;
+            assert!(times.len().abs_diff(expected) <= 1, "{msg_id:#x}");
+            for gap in times.windows(2).map(|w| w[1] - w[0]) {
+                assert!(gap + 8 >= period && gap <= period + 8, "{msg_id:#x}: {gap}");
+            }
+        }
+        let jittered = msgs.iter().filter(|(at_ms, _)| at_ms % 10 != 0).count();
+        assert!(jittered * 2 > msgs.len(), "{jittered}");
+    }
+
+    #[test]
+    fn test_bursts() {
+        let stream = MessageStreamGenerator::new(6).faults(0.0);
+        let brakes: Vec<_> = stream
+            .take_while(|(at_ms, _)| *at_ms < 60_000)
+            .filter(|(_, m)| matches!(m, CanMessage::SetBrake(_)))
+            .map(|(at_ms, _)| at_ms)
+            .collect();
+        // About a burst of 5 a second, half of them brake commands
+        assert!(brakes.len().abs_diff(150) < 30, "{}", brakes.len());
+        assert!(brakes.windows(2).any(|w| w[1] - w[0] == 1));
+    }
+
+    #[test]
+    fn test_faults() {
+        let stream = MessageStreamGenerator::new(2).faults(0.05).bursts(0, 0);
+        let msgs: Vec<_> = stream.take(20_000).collect();
+        let edge = msgs
+            .iter()
+            .filter(|(_, m)| {
+                matches!(
+                    m,
+                    CanMessage::AutonDisable(_)
+                        | CanMessage::LockBrake(_)
+                        | CanMessage::UnlockBrake(_)
+                        | CanMessage::TrainingMode(_)
+                        | CanMessage::SetBrake(_)
+                        | CanMessage::GetAngleCompact(_)
+                )
+            })
+            .count();
+        // A chance of 0.05 after each other message, 6 of 8 of kinds not otherwise sent
+        let expected = 20_000 * 5 / 105 * 6 / 8;
+        assert!(edge.abs_diff(expected) < expected / 5, "{edge}");
+    }
+
+    #[test]
+    fn test_silence() {
+        const STEERING: &[u32] = &[GetAngle::ID, GetAngleCompact::ID];
+        let stream = MessageStreamGenerator::new(3)
+            .faults(0.0)
+            .inject(Anomaly::Silence {
+                ids: STEERING,
+                from_ms: 200,
+                until_ms: 500,
+            });
+        let msgs = second(stream);
+        let feedback: Vec<_> = msgs
+            .iter()
+            .filter(|(_, m)| STEERING.contains(&m.id()))
+            .map(|(at_ms, _)| *at_ms)
+            .collect();
+        assert!(!feedback.iter().any(|t| (200..500).contains(t)));
+        assert!(feedback.contains(&180) && feedback.contains(&500));
+        assert_eq!(feedback.len(), 50 - 15);
+    }
+
+    #[test]
+    fn test_duplicate() {
+        let stream = MessageStreamGenerator::new(4).inject(Anomaly::Duplicate(0.1));
+        let msgs: Vec<_> = stream.take(20_000).collect();
+        let repeats = msgs
+            .windows(2)
+            .filter(|w| w[0].0 == w[1].0 && w[0].1.to_parts().ok() == w[1].1.to_parts().ok())
+            .count();
+        // Of the 20k, about 1/11 are copies
+        assert!(repeats.abs_diff(1818) < 200, "{repeats}");
+    }
+
+    #[test]
+    fn test_reorder() {
+        let parts = |stream: MessageStreamGenerator, n| -> Vec<_> {
+            stream
+                .take(n)
+                .map(|(at_ms, m)| (at_ms, m.to_parts().unwrap()))
+                .collect()
+        };
+        let plain = parts(MessageStreamGenerator::new(5), 1003);
+        let shuffled = parts(
+            MessageStreamGenerator::new(5).inject(Anomaly::Reorder(4)),
+            1000,
+        );
+        assert!(shuffled.windows(2).all(|w| w[0].0 <= w[1].0));
+
+        // The same messages come from a window of the next 4, and most come out of place
+        let mut pending = plain.iter().map(|(_, p)| p).take(3).collect::<Vec<_>>();
+        let mut moved = 0;
+        for (i, (_, msg)) in shuffled.iter().enumerate() {
+            pending.push(&plain[i + 3].1);
+            let at = pending
+                .iter()
+                .position(|p| *p == msg)
+                .expect("not in the window");
+            moved += (at != 0) as usize;
+            pending.remove(at);
+        }
+        assert!(moved > 500, "{moved}");
+    }
+}
@@ -0,0 +1,338 @@
+//! Test vectors in a plain text format, for checking other implementations of the bus, like the
+//! C firmware, against this crate.
+//!
+//! Each line is one vector, with four comma separated columns: the message name, its ID in hex,
+//! the payload in hex, and the raw value of each signal as `name=value`, separated by spaces.
+//! Integers are written as such, and floats in the shortest form that reads back exactly.
+//! Blank lines and lines starting with `#` are ignored.
+//!
+//! ```text
+//! SetBrake,0x1,4B,percent=75
+//! CellVoltages,0xC,01740E800E720E,page=1 cell_4=3700 cell_5=3712 cell_6=3698
+//! ```
+//!
+//! Signals are laid out as in [`MESSAGES`], so only the signals of the page a multiplexed
+//! vector is on are listed.
+
+extern crate std;
+
+use super::{Golden, GOLDEN};
+use crate::*;
+use core::fmt::{self, Write};
+use std::format;
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+/// A message with its signal values and exact encoding.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Vector {
+    pub name: String,
+    pub id: u32,
+    pub data: Vec<u8>,
+    /// Raw value of each signal, by name.
+    pub fields: Vec<(String, f64)>,
+}
+
+/// A line of a vectors file that could not be read.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseVectorErr {
+    /// Line number, from 1.
+    pub line: usize,
+    pub reason: &'static str,
+}
+
+impl Vector {
+    /// A vector for a golden message, with its signals read from its payload.
+    pub fn from_golden(golden: &Golden) -> Self {
+        let info = info(golden.id).expect("golden vector for an unknown ID");
+        let fields = active(info, golden.data)
+            .map(|s| {
+                (
+                    s.name.to_string(),
+                    read_raw(s, golden.data).unwrap_or(f64::NAN),
+                )
+            })
+            .collect();
+        Self {
+            name: info.name.to_string(),
+            id: golden.id,
+            data: golden.data.to_vec(),
+            fields,
+        }
+    }
+
+    /// Checks the vector against this crate both ways: that its signals lay out to its payload,
+    /// and that the payload decodes as the named message, which encodes back to it.
+    pub fn check(&self) -> Result<(), String> {
+        let info = info(self.id).ok_or(format!("{:#x} is not a message", self.id))?;
+        if info.name != self.name {
+            return Err(format!(
+                "{:#x} is {}, not {}",
+                self.id, info.name, self.name
+            ));
+        }
+
+        // Signals to payload, by the layout alone
+        let mut data = [0; 8];
+        let len = info.dlc.min(8);
+        for (name, value) in &self.fields {
+            let signal = info
+                .signals
+                .iter()
+                .find(|s| s.name == name)
+                .ok_or(format!("{} has no signal {name}", self.name))?;
+            write_raw(signal, *value, &mut data)
+                .ok_or(format!("{}.{name} = {value} does not fit", self.name))?;
+        }
+        let missing =
+            active(info, &data[..len]).find(|s| !self.fields.iter().any(|f| f.0 == s.name));
+        if let Some(signal) = missing {
+            return Err(format!("{}.{} has no value", self.name, signal.name));
+        }
+        if data[..len] != self.data[..] {
+            return Err(format!(
+                "{} signals lay out to {}, not {}",
+                self.name,
+                hex(&data[..len]),
+                hex(&self.data)
+            ));
+        }
+
+        // Payload to message and back, by this crate
+        let msg = CanMessage::from_parts(self.id, &self.data)
+            .map_err(|_| format!("{} does not decode", self.name))?;
+        let (_, encoded, encoded_len) = msg
+            .to_parts()
+            .map_err(|_| format!("{} does not encode", self.name))?;
+        if encoded[..encoded_len] != self.data[..] {
+            return Err(format!(
+                "{} encodes to {}, not {}",
+                self.name,
+                hex(&encoded[..encoded_len]),
+                hex(&self.data)
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Vectors for every golden message in [`GOLDEN`].
+pub fn golden_vectors() -> Vec<Vector> {
+    GOLDEN.iter().map(Vector::from_golden).collect()
+}
+
+/// Writes vectors in the format read by [`read_vectors`], after a header comment.
+pub fn write_vectors<W: Write>(out: &mut W, vectors: &[Vector]) -> fmt::Result {
+    writeln!(out, "# name,id,data,fields")?;
+    for v in vectors {
+        write!(out, "{},{:#X},{},", v.name, v.id, hex(&v.data))?;
+        for (i, (name, value)) in v.fields.iter().enumerate() {
+            let sep = if i == 0 { "" } else { " " };
+            if value.fract() == 0.0 {
+                write!(out, "{sep}{name}={}", *value as i64)?;
+            } else {
+                // Every float signal is an f32
+                write!(out, "{sep}{name}={}", *value as f32)?;
+            }
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Reads vectors written by [`write_vectors`], or by hand.
+pub fn read_vectors(text: &str) -> Result<Vec<Vector>, ParseVectorErr> {
+    let mut vectors = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let err = |reason| ParseVectorErr {
+            line: i + 1,
+            reason,
+        };
+
+        let mut columns = line.split(',');
+        let (Some(name), Some(id), Some(data), Some(fields), None) = (
+            columns.next(),
+            columns.next(),
+            columns.next(),
+            columns.next(),
+            columns.next(),
+        ) else {
+            return Err(err("expected four columns"));
+        };
+
+        let id = id.trim_start_matches("0x").trim_start_matches("0X");
+        let id = u32::from_str_radix(id, 16).map_err(|_| err("bad ID"))?;
+        let data = unhex(data).ok_or(err("bad payload"))?;
+        let fields = fields
+            .split_whitespace()
+            .map(|f| {
+                let (name, value) = f.split_once('=')?;
+                let value: f64 = value.parse().ok()?;
+                // Every float signal is an f32, so read fractions as one
+                let value = if value.fract() == 0.0 {
+                    value
+                } else {
+                    value as f32 as f64
+                };
+                Some((name.to_string(), value))
+            })
+            .collect::<Option<_>>()
+            .ok_or(err("bad field"))?;
+
+        vectors.push(Vector {
+            name: name.to_string(),
+            id,
+            data,
+            fields,
+        });
+    }
+    Ok(vectors)
+}
+
+fn info(id: u32) -> Option<&'static MessageInfo> {
+    MESSAGES.iter().find(|m| m.id == id)
+}
+
+/// Signals present in a payload, skipping those on other pages.
+fn active<'a>(info: &'a MessageInfo, data: &'a [u8]) -> impl Iterator<Item = &'a Signal> {
+    info.signals.iter().filter(move |s| match s.mux {
+        Multiplex::Page(page) => data.first() == Some(&page),
+        _ => true,
+    })
+}
+
+fn read_raw(signal: &Signal, data: &[u8]) -> Option<f64> {
+    let bytes = data.get(signal.start..signal.start + signal.size)?;
+    let fold = |raw: u64, b: &u8| raw << 8 | *b as u64;
+    let raw = match signal.order {
+        ByteOrder::LittleEndian => bytes.iter().rev().fold(0, fold),
+        ByteOrder::BigEndian => bytes.iter().fold(0, fold),
+    };
+
+    let bits = signal.size as u32 * 8;
+    Some(match signal.kind {
+        SignalKind::Unsigned => raw as f64,
+        SignalKind::Signed => ((raw << (64 - bits)) as i64 >> (64 - bits)) as f64,
+        SignalKind::Float => f32::from_bits(raw as u32) as f64,
+    })
+}
+
+/// Writes a raw value, or returns `None` if it does not fit the signal.
+fn write_raw(signal: &Signal, value: f64, buf: &mut [u8; 8]) -> Option<()> {
+    let bits = signal.size as u32 * 8;
+    let raw = match signal.kind {
+        SignalKind::Float => (value as f32).to_bits() as u64,
+        _ if value.fract() != 0.0 => return None,
+        SignalKind::Unsigned if value < 0.0 || value >= 2f64.powi(bits as i32) => return None,
+        SignalKind::Signed if value.abs() >= 2f64.powi(bits as i32 - 1) && value >= 0.0 => {
+            return None
+        }
+        SignalKind::Signed if value < -2f64.powi(bits as i32 - 1) => return None,
+        _ => value as i64 as u64,
+    };
+
+    let bytes = buf.get_mut(signal.start..signal.start + signal.size)?;
+    for (i, b) in bytes.iter_mut().enumerate() {
+        let shift = match signal.order {
+            ByteOrder::LittleEndian => i * 8,
+            ByteOrder::BigEndian => (signal.size - 1 - i) * 8,
+        };
+        *b = (raw >> shift) as u8;
+    }
+    Some(())
+}
+
+fn hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{b:02X}")).collect()
+}
+
+fn unhex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(all(test, feature = "msgs-all"))]
+mod test {
+    use super::*;
+    use std::vec;
+
+    #[test]
+    fn test_write_read() {
+        let vectors = golden_vectors();
+        let mut text = String::new();
+        write_vectors(&mut text, &vectors).unwrap();
+
+        assert_eq!(read_vectors(&text), Ok(vectors.clone()));
+        for v in &vectors {
+            assert_eq!(v.check(), Ok(()), "{v:?}");
+        }
+    }
+
+    #[test]
+    fn test_format() {
+        let vectors = read_vectors(
+            "# comment\n\nSetBrake,0x1,4B,percent=75\n\
+             EncoderCount,0X7,341233332341,count=4660 velocity=10.2\n",
+        )
+        .unwrap();
+        assert_eq!(vectors.len(), 2);
+        assert_eq!(vectors[1].id, 7);
+        assert_eq!(vectors[1].data, [0x34, 0x12, 0x33, 0x33, 0x23, 0x41]);
+        assert_eq!(
+            vectors[1].fields,
+            [
+                ("count".into(), 4660.0),
+                ("velocity".into(), 10.2f32 as f64)
+            ]
+        );
+
+        let mut text = String::new();
+        write_vectors(&mut text, &vectors).unwrap();
+        assert!(text.ends_with("EncoderCount,0x7,341233332341,count=4660 velocity=10.2\n"));
+
+        let err = |text| read_vectors(text).unwrap_err().reason;
+        assert_eq!(err("SetBrake,0x1,4B"), "expected four columns");
+        assert_eq!(err("SetBrake,0xZ,4B,percent=75"), "bad ID");
+        assert_eq!(err("SetBrake,0x1,4,percent=75"), "bad payload");
+        assert_eq!(err("\nSetBrake,0x1,4B,percent"), "bad field");
+        assert_eq!(read_vectors("\nx,1,,a").unwrap_err().line, 2);
+    }
+
+    #[test]
+    fn test_mismatch() {
+        let good = Vector::from_golden(GOLDEN.iter().find(|g| g.id == SetBrake::ID).unwrap());
+        assert_eq!(good.name, "SetBrake");
+
+        let check = |f: fn(&mut Vector)| {
+            let mut v = good.clone();
+            f(&mut v);
+            v.check().unwrap_err()
+        };
+        assert_eq!(check(|v| v.id = 0x1FFF), "0x1fff is not a message");
+        assert_eq!(
+            check(|v| v.name = "SetSpeed".into()),
+            "0x1 is SetBrake, not SetSpeed"
+        );
+        assert_eq!(
+            check(|v| v.data = vec![0x4D, 0x1D]),
+            "SetBrake signals lay out to 4C1D, not 4D1D"
+        );
+        assert_eq!(
+            check(|v| v.fields[0].1 = 65536.0),
+            "SetBrake.centi_percent = 65536 does not fit"
+        );
+        assert_eq!(
+            check(|v| v.fields.clear()),
+            "SetBrake.centi_percent has no value"
+        );
+    }
+}
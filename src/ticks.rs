@@ -0,0 +1,185 @@
+use crate::EncoderCount;
+
+/// How the count in an [`EncoderCount`] should be interpreted.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum TickMode {
+    /// Count is ticks since the previous message, which is what current firmware sends.
+    Delta,
+    /// Count is a free running absolute count, which wraps at u16::MAX.
+    ///
+    /// Consecutive counts are differenced as a signed 16 bit value, so small reverse motion
+    /// is handled and dropped frames are recovered from, as long as less than half the range
+    /// passes between received messages.
+    Absolute,
+}
+
+/// Result of feeding a message to a [`TickAccumulator`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum TickUpdate {
+    /// Ticks were added to the total.
+    Accepted { ticks: i32 },
+    /// First message in absolute mode, which only sets the reference count.
+    Baseline,
+    /// More ticks than are physically possible in the elapsed time. Nothing was added to the total.
+    Implausible { ticks: i32, max: u32 },
+}
+
+/// Accumulates the encoder ticks from a stream of [`EncoderCount`] messages into a running total.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct TickAccumulator {
+    mode: TickMode,
+    max_ticks_per_s: u32,
+    total: i64,
+    /// Last count and receive time, in ms.
+    last: Option<(u16, u32)>,
+    implausible: u32,
+}
+
+impl TickAccumulator {
+    /// Creates an accumulator, where `max_ticks_per_s` is the tick rate at the kart's top speed.
+    pub const fn new(mode: TickMode, max_ticks_per_s: u32) -> Self {
+        Self {
+            mode,
+            max_ticks_per_s,
+            total: 0,
+            last: None,
+            implausible: 0,
+        }
+    }
+
+    /// Adds a message received at `now_ms` to the total.
+    ///
+    /// In delta mode the first message cannot be checked for plausibility, as its count covers
+    /// an unknown time span, so it is accepted as is.
+    pub fn update(&mut self, enc: &EncoderCount, now_ms: u32) -> TickUpdate {
+        let last = self.last.replace((enc.count, now_ms));
+
+        let ticks = match (self.mode, last) {
+            (TickMode::Delta, _) => enc.count as i32,
+            (TickMode::Absolute, Some((count, _))) => enc.count.wrapping_sub(count) as i16 as i32,
+            (TickMode::Absolute, None) => return TickUpdate::Baseline,
+        };
+
+        if let Some((_, last_ms)) = last {
+            let max = self.max_ticks(now_ms.wrapping_sub(last_ms));
+
+            if ticks.unsigned_abs() > max {
+                self.implausible = self.implausible.saturating_add(1);
+                return TickUpdate::Implausible { ticks, max };
+            }
+        }
+
+        self.total = self.total.saturating_add(ticks as i64);
+        TickUpdate::Accepted { ticks }
+    }
+
+    /// Total accumulated ticks.
+    pub fn total(&self) -> i64 {
+        self.total
+    }
+
+    /// Number of messages rejected as implausible.
+    pub fn implausible(&self) -> u32 {
+        self.implausible
+    }
+
+    /// Clears the total and reference count.
+    pub fn reset(&mut self) {
+        *self = Self::new(self.mode, self.max_ticks_per_s);
+    }
+
+    /// Most ticks possible in `elapsed_ms`, with a tick of slack for quantization.
+    fn max_ticks(&self, elapsed_ms: u32) -> u32 {
+        let max = self.max_ticks_per_s as u64 * elapsed_ms as u64 / 1000 + 1;
+        max.min(u32::MAX as u64) as u32
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn enc(count: u16) -> EncoderCount {
+        EncoderCount {
+            count,
+            velocity: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_absolute_wraparound() {
+        let mut acc = TickAccumulator::new(TickMode::Absolute, 100_000);
+
+        assert_eq!(acc.update(&enc(65_000), 0), TickUpdate::Baseline);
+        assert_eq!(
+            acc.update(&enc(65_500), 10),
+            TickUpdate::Accepted { ticks: 500 }
+        );
+        assert_eq!(
+            acc.update(&enc(200), 20),
+            TickUpdate::Accepted { ticks: 236 }
+        );
+        assert_eq!(acc.total(), 736);
+
+        // Reverse motion across the wrap
+        assert_eq!(
+            acc.update(&enc(65_530), 30),
+            TickUpdate::Accepted { ticks: -206 }
+        );
+        assert_eq!(acc.total(), 530);
+    }
+
+    #[test]
+    fn test_delta() {
+        let mut acc = TickAccumulator::new(TickMode::Delta, 10_000);
+
+        for i in 0..10 {
+            acc.update(&enc(50), i * 10);
+        }
+        assert_eq!(acc.total(), 500);
+    }
+
+    #[test]
+    fn test_dropped_frame() {
+        let mut acc = TickAccumulator::new(TickMode::Absolute, 15_000);
+
+        acc.update(&enc(100), 0);
+        acc.update(&enc(200), 10);
+        // Frame at 20ms with count 300 lost, twice the ticks over twice the time is plausible
+        assert_eq!(
+            acc.update(&enc(400), 30),
+            TickUpdate::Accepted { ticks: 200 }
+        );
+        assert_eq!(acc.total(), 300);
+    }
+
+    #[test]
+    fn test_implausible_spike() {
+        let mut acc = TickAccumulator::new(TickMode::Delta, 10_000);
+
+        acc.update(&enc(50), 0);
+        assert_eq!(
+            acc.update(&enc(5000), 10),
+            TickUpdate::Implausible {
+                ticks: 5000,
+                max: 101
+            }
+        );
+        assert_eq!(acc.update(&enc(50), 20), TickUpdate::Accepted { ticks: 50 });
+        assert_eq!(acc.total(), 100);
+        assert_eq!(acc.implausible(), 1);
+
+        let mut acc = TickAccumulator::new(TickMode::Absolute, 10_000);
+        acc.update(&enc(0), 0);
+        assert!(matches!(
+            acc.update(&enc(20_000), 10),
+            TickUpdate::Implausible { .. }
+        ));
+        // The spike becomes the new reference, so a counter reset doesn't flag forever
+        assert_eq!(
+            acc.update(&enc(20_050), 20),
+            TickUpdate::Accepted { ticks: 50 }
+        );
+        assert_eq!(acc.total(), 50);
+    }
+}
@@ -0,0 +1,94 @@
+//! Unit typed accessors, enabled by the `uom` feature. The wire format is unchanged.
+
+use crate::{EncoderCount, GetAngle, MotorTemperature, SetAngle};
+use uom::si::angle::degree;
+use uom::si::f32::{Angle, ThermodynamicTemperature, Velocity};
+use uom::si::thermodynamic_temperature::degree_celsius;
+use uom::si::velocity::meter_per_second;
+
+impl EncoderCount {
+    /// Current velocity.
+    pub fn velocity_si(&self) -> Velocity {
+        Velocity::new::<meter_per_second>(self.velocity)
+    }
+}
+
+impl SetAngle {
+    /// Commanded steering motor angle, where left is negative, and right is positive.
+    pub fn angle_si(&self) -> Angle {
+        Angle::new::<degree>(self.angle)
+    }
+
+    /// Creates a command for a steering motor angle, where left is negative, and right is positive.
+    pub fn from_angle(angle: Angle) -> Self {
+        Self {
+            angle: angle.get::<degree>(),
+        }
+    }
+}
+
+impl GetAngle {
+    /// Current steering motor angle, where left is negative, and right is positive.
+    pub fn angle_si(&self) -> Angle {
+        Angle::new::<degree>(self.angle)
+    }
+
+    /// Creates feedback for a steering motor angle, where left is negative, and right is positive.
+    pub fn from_angle(angle: Angle) -> Self {
+        Self {
+            angle: angle.get::<degree>(),
+        }
+    }
+}
+
+impl MotorTemperature {
+    /// Current motor temperature.
+    pub fn temp_si(&self) -> ThermodynamicTemperature {
+        ThermodynamicTemperature::new::<degree_celsius>(self.temp_scaled())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use uom::si::angle::radian;
+    use uom::si::thermodynamic_temperature::kelvin;
+    use uom::si::velocity::mile_per_hour;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-4, "{a} != {b}");
+    }
+
+    #[test]
+    fn test_velocity() {
+        for v in [0.0, 10.2, -3.5] {
+            let enc = EncoderCount {
+                count: 0,
+                velocity: v,
+            };
+            assert_close(enc.velocity_si().get::<meter_per_second>(), v);
+            assert_close(enc.velocity_si().get::<mile_per_hour>(), v * 2.236_936);
+        }
+    }
+
+    #[test]
+    fn test_angle() {
+        for a in [0.0, 4.818, -20.0] {
+            let get = GetAngle { angle: a };
+            assert_close(get.angle_si().get::<degree>(), a);
+            assert_close(get.angle_si().get::<radian>(), a.to_radians());
+            assert_close(GetAngle::from_angle(get.angle_si()).angle, a);
+
+            let set = SetAngle::from_angle(Angle::new::<radian>(a.to_radians()));
+            assert_close(set.angle, a);
+            assert_close(set.angle_si().get::<degree>(), a);
+        }
+    }
+
+    #[test]
+    fn test_temperature() {
+        let temp = MotorTemperature { temp: -125 };
+        assert_close(temp.temp_si().get::<degree_celsius>(), -12.5);
+        assert_close(temp.temp_si().get::<kelvin>(), 260.65);
+    }
+}
@@ -0,0 +1,258 @@
+use crate::EncoderCount;
+
+/// Smoothing applied by a [`VelocityFilter`].
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+pub enum FilterMode {
+    /// Exponential moving average. Alpha is the weight given to each new sample, in (0, 1].
+    /// Values outside that range are clamped.
+    Ema { alpha: f32 },
+    /// Median over the last N samples. Rejects spikes shorter than half the window.
+    Median,
+}
+
+/// Low-pass filter over the velocity reported by a stream of [`EncoderCount`] messages.
+///
+/// N is the median window length, and is unused in EMA mode.
+#[derive(Copy, Clone, Debug)]
+pub struct VelocityFilter<const N: usize> {
+    mode: FilterMode,
+    window: [f32; N],
+    /// Number of valid samples in the window.
+    len: usize,
+    /// Index the next sample will be written to.
+    head: usize,
+    output: f32,
+    primed: bool,
+}
+
+impl<const N: usize> VelocityFilter<N> {
+    pub const fn new(mode: FilterMode) -> Self {
+        Self {
+            mode,
+            window: [0.0; N],
+            len: 0,
+            head: 0,
+            output: 0.0,
+            primed: false,
+        }
+    }
+
+    /// Feeds a new encoder message into the filter, returning the filtered velocity in m/s.
+    ///
+    /// `dt_s` is the time since the previous message. A non-finite velocity, or a non-positive
+    /// or non-finite `dt_s`, is treated as a duplicate frame and leaves the output unchanged.
+    pub fn update(&mut self, enc: &EncoderCount, dt_s: f32) -> f32 {
+        let sample = enc.velocity;
+
+        if !(sample.is_finite() && dt_s > 0.0 && dt_s.is_finite()) {
+            return self.output;
+        }
+
+        match self.mode {
+            FilterMode::Ema { alpha } => {
+                if self.primed {
+                    let alpha = alpha.clamp(0.0, 1.0);
+                    self.output += alpha * (sample - self.output);
+                } else {
+                    self.output = sample;
+                }
+            }
+            FilterMode::Median => {
+                if N == 0 {
+                    self.output = sample;
+                } else {
+                    if let Some(slot) = self.window.get_mut(self.head) {
+                        *slot = sample;
+                    }
+                    self.head = self.head.wrapping_add(1) % N;
+                    self.len = self.len.saturating_add(1).min(N);
+                    self.output = self.median();
+                }
+            }
+        }
+
+        self.primed = true;
+        self.output
+    }
+
+    /// Last filtered velocity, in m/s. Zero until the first update.
+    pub fn value(&self) -> f32 {
+        self.output
+    }
+
+    /// Clears all history, as if the filter was just constructed.
+    pub fn reset(&mut self) {
+        *self = Self::new(self.mode);
+    }
+
+    fn median(&self) -> f32 {
+        let mut sorted = self.window;
+        let sorted = &mut sorted[..self.len.min(N)];
+
+        // Insertion sort, windows are small and this avoids pulling in sort code.
+        for i in 1..sorted.len() {
+            let mut j = i;
+            while j > 0 && sorted[j - 1] > sorted[j] {
+                sorted.swap(j - 1, j);
+                j -= 1;
+            }
+        }
+
+        let mid = sorted.len() / 2;
+        match (sorted.get(mid.wrapping_sub(1)), sorted.get(mid)) {
+            (Some(lo), Some(hi)) if sorted.len().is_multiple_of(2) => (lo + hi) / 2.0,
+            (_, Some(mid)) => *mid,
+            // Empty window, which update never leaves
+            _ => self.output,
+        }
+    }
+}
+
+/// A [`VelocityFilter`] that decays its output toward zero once encoder messages stop arriving.
+///
+/// Without this, a filter fed by an encoder node that has gone silent will report the last seen
+/// velocity forever.
+#[derive(Copy, Clone, Debug)]
+pub struct DecayingVelocityFilter<const N: usize> {
+    filter: VelocityFilter<N>,
+    /// Silence after which the output begins decaying, in seconds.
+    timeout_s: f32,
+    /// Time constant of the decay, in seconds.
+    tau_s: f32,
+    silence_s: f32,
+    output: f32,
+}
+
+impl<const N: usize> DecayingVelocityFilter<N> {
+    /// Creates a filter that begins decaying after `timeout_s` of silence, approaching zero with
+    /// time constant `tau_s`.
+    pub const fn new(mode: FilterMode, timeout_s: f32, tau_s: f32) -> Self {
+        Self {
+            filter: VelocityFilter::new(mode),
+            timeout_s,
+            tau_s,
+            silence_s: 0.0,
+            output: 0.0,
+        }
+    }
+
+    /// Feeds a new encoder message into the filter, returning the filtered velocity in m/s.
+    ///
+    /// If the filter went stale, the old history is discarded first so it cannot drag the output.
+    pub fn update(&mut self, enc: &EncoderCount, dt_s: f32) -> f32 {
+        if self.is_stale() {
+            self.filter.reset();
+        }
+
+        self.silence_s = 0.0;
+        self.output = self.filter.update(enc, dt_s);
+        self.output
+    }
+
+    /// Advances time by `dt_s` without a new message, returning the possibly decayed velocity.
+    pub fn idle(&mut self, dt_s: f32) -> f32 {
+        if !(dt_s > 0.0 && dt_s.is_finite()) {
+            return self.output;
+        }
+
+        self.silence_s += dt_s;
+
+        if self.is_stale() {
+            if self.tau_s > 0.0 {
+                // First order discrete decay, avoids needing exp in no_std.
+                self.output *= self.tau_s / (self.tau_s + dt_s);
+            } else {
+                self.output = 0.0;
+            }
+        }
+
+        self.output
+    }
+
+    /// True if no message has been seen for longer than the timeout.
+    pub fn is_stale(&self) -> bool {
+        self.silence_s > self.timeout_s
+    }
+
+    /// Last output velocity, in m/s.
+    pub fn value(&self) -> f32 {
+        self.output
+    }
+
+    /// Clears all history, as if the filter was just constructed.
+    pub fn reset(&mut self) {
+        self.filter.reset();
+        self.silence_s = 0.0;
+        self.output = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn enc(velocity: f32) -> EncoderCount {
+        EncoderCount { count: 0, velocity }
+    }
+
+    #[test]
+    fn test_ema_smooths_noise() {
+        let mut filter = VelocityFilter::<1>::new(FilterMode::Ema { alpha: 0.2 });
+        let noise = [0.3, -0.25, 0.1, -0.3, 0.2, -0.15, 0.3, -0.2];
+
+        let mut out = 0.0;
+        for i in 0..80 {
+            out = filter.update(&enc(2.0 + noise[i % noise.len()]), 0.01);
+        }
+
+        assert!((out - 2.0).abs() < 0.15, "{out}");
+    }
+
+    #[test]
+    fn test_median_rejects_spikes() {
+        let mut filter = VelocityFilter::<5>::new(FilterMode::Median);
+        let samples = [1.0, 1.1, 0.9, 40.0, 1.0, 1.05, -30.0, 0.95];
+
+        for s in samples {
+            let out = filter.update(&enc(s), 0.01);
+            assert!(out < 1.2 && out > -0.1, "{out}");
+        }
+    }
+
+    #[test]
+    fn test_invalid_samples_ignored() {
+        let mut filter = VelocityFilter::<3>::new(FilterMode::Ema { alpha: 0.5 });
+        filter.update(&enc(3.0), 0.01);
+
+        assert_eq!(filter.update(&enc(f32::NAN), 0.01), 3.0);
+        assert_eq!(filter.update(&enc(10.0), 0.0), 3.0);
+        assert_eq!(filter.update(&enc(10.0), f32::NAN), 3.0);
+
+        filter.reset();
+        assert_eq!(filter.value(), 0.0);
+        assert_eq!(filter.update(&enc(10.0), 0.01), 10.0);
+    }
+
+    #[test]
+    fn test_decay_on_silence() {
+        let mut filter = DecayingVelocityFilter::<3>::new(FilterMode::Median, 0.1, 0.2);
+        filter.update(&enc(5.0), 0.01);
+
+        // Within the timeout the value holds
+        assert_eq!(filter.idle(0.05), 5.0);
+        assert!(!filter.is_stale());
+
+        let mut last = 5.0;
+        for _ in 0..200 {
+            let out = filter.idle(0.01);
+            assert!(out <= last);
+            last = out;
+        }
+        assert!(filter.is_stale());
+        assert!(last < 0.01, "{last}");
+
+        // Old history is dropped once messages resume
+        assert_eq!(filter.update(&enc(1.0), 0.01), 1.0);
+        assert!(!filter.is_stale());
+    }
+}
@@ -0,0 +1,323 @@
+//! Properties of the codec, checked for every input by the Kani proofs in `proofs`, and over
+//! bounded inputs by the exhaustive tests, which run with the rest.
+//!
+//! The proofs run with `cargo kani`, or one at a time with `cargo kani --harness set_brake`.
+//! Each is kept to one message, or a few of one layout, so they stay small.
+
+use crate::*;
+use core::fmt::Debug;
+
+/// Decoding any payload with any ID returns, the same way owned and viewed, and whatever decodes
+/// is exactly its DLC, or its legacy DLC. Under [`DecodePolicy::Raw`] it encodes back to the
+/// same bytes, unless legacy, and under [`DecodePolicy::Normalize`] to bytes that decode back to
+/// the same message.
+fn decode_any(id: u32, data: &[u8]) {
+    let owned = CanMessage::from_parts(id, data);
+    let viewed = CanMessageRef::from_parts(id, data).map(|view| view.into_owned());
+    assert_eq!(owned.is_ok(), viewed.is_ok());
+    let raw = CanMessage::from_parts_with_policy(id, data, DecodePolicy::Raw);
+    assert!(owned.is_err() || raw.is_ok());
+    let legacy = MESSAGES
+        .iter()
+        .any(|m| m.id == id && m.legacy_dlc == Some(data.len()));
+
+    if let Ok(msg) = owned {
+        let (encoded_id, encoded, len) = msg.to_parts().unwrap();
+        assert_eq!(encoded_id.as_raw(), id);
+        assert_eq!(Some(len), REGISTRY.get(id).map(|e| e.dlc));
+        assert!(len == data.len() || legacy);
+        let again = CanMessage::from_parts(id, &encoded[..len]).and_then(|m| m.to_parts());
+        assert_eq!(again.ok(), Some((encoded_id, encoded, len)));
+    }
+    if let (Ok(msg), false) = (raw, legacy) {
+        let (_, encoded, len) = msg.to_parts().unwrap();
+        assert_eq!(encoded[..len], data[..len]);
+    }
+}
+
+/// Encoding gives a payload of exactly `M::DLC` bytes, which decodes back to an equal message,
+/// both as `M` and as a [`CanMessage`]. Out of range, it only decodes under
+/// [`DecodePolicy::Raw`], and otherwise errors naming the same field.
+fn round_trip<M>(msg: M)
+where
+    M: IscFrame + Copy + PartialEq + Debug + Into<CanMessage>,
+{
+    let (data, len) = msg.encode();
+    assert_eq!(len, M::DLC, "{msg:?} encodes to another length");
+    let data = &data[..len];
+
+    let decoded = M::decode(data);
+    if let Err(RangeErr { field }) = msg.check_range() {
+        assert!(
+            matches!(decoded, Err(ConvertErr::OutOfRange { field: f }) if f == field),
+            "{msg:?} out of range decodes as {decoded:?}"
+        );
+        let raw = M::decode_with(data, DecodePolicy::Raw);
+        assert!(
+            matches!(raw, Ok(m) if m == msg),
+            "{msg:?} decodes raw as {raw:?}"
+        );
+        return;
+    }
+    assert!(
+        matches!(decoded, Ok(m) if m == msg),
+        "{msg:?} decodes as {decoded:?}"
+    );
+
+    let parts = msg.into().to_parts().unwrap();
+    let decoded = CanMessage::from_parts(M::ID, data).and_then(|m| m.to_parts());
+    assert!(
+        matches!(decoded, Ok(p) if p == parts),
+        "{msg:?} decodes as another CanMessage"
+    );
+}
+
+#[cfg(kani)]
+mod proofs {
+    use super::*;
+
+    /// Any f32 that decodes unchanged: normal, or 0.0.
+    fn normal() -> f32 {
+        let v: f32 = kani::any();
+        kani::assume(v.is_normal() || v.to_bits() == 0);
+        v
+    }
+
+    #[kani::proof]
+    #[kani::unwind(9)]
+    fn from_parts_never_panics() {
+        let id: u32 = kani::any();
+        let data: [u8; 8] = kani::any();
+        let len: usize = kani::any();
+        kani::assume(len <= 8);
+        decode_any(id, &data[..len]);
+    }
+
+    #[kani::proof]
+    fn estop_all() {
+        let source = EstopSource::from_raw(kani::any());
+        kani::assume(source.is_some());
+        round_trip(EstopAll {
+            source: source.unwrap(),
+        });
+    }
+
+    #[kani::proof]
+    fn empty_messages() {
+        round_trip(AutonDisable {});
+        round_trip(LockBrake {});
+        round_trip(UnlockBrake {});
+        round_trip(TrainingMode {});
+    }
+
+    #[kani::proof]
+    fn set_brake() {
+        round_trip(SetBrake {
+            centi_percent: kani::any(),
+        });
+    }
+
+    #[kani::proof]
+    fn set_speed() {
+        round_trip(SetSpeed {
+            centi_percent: kani::any(),
+        });
+    }
+
+    #[kani::proof]
+    fn encoder_count() {
+        round_trip(EncoderCount {
+            count: kani::any(),
+            velocity: normal(),
+        });
+    }
+
+    #[kani::proof]
+    fn angles() {
+        round_trip(SetAngle { angle: normal() });
+        round_trip(GetAngle { angle: normal() });
+    }
+
+    #[kani::proof]
+    fn compact_angles() {
+        round_trip(SetAngleCompact { angle: kani::any() });
+        round_trip(GetAngleCompact { angle: kani::any() });
+    }
+
+    #[kani::proof]
+    fn motor_temperature() {
+        round_trip(MotorTemperature { temp: kani::any() });
+    }
+
+    #[kani::proof]
+    fn cell_voltages() {
+        round_trip(CellVoltages::from(CellVoltages0 {
+            cell_1: kani::any(),
+            cell_2: kani::any(),
+            cell_3: kani::any(),
+        }));
+        round_trip(CellVoltages::from(CellVoltages1 {
+            cell_4: kani::any(),
+            cell_5: kani::any(),
+            cell_6: kani::any(),
+        }));
+    }
+
+    #[kani::proof]
+    fn imu_rates() {
+        round_trip(ImuRates {
+            yaw_rate: kani::any(),
+            lateral_accel: kani::any(),
+            sequence: kani::any(),
+        });
+    }
+
+    #[kani::proof]
+    fn heartbeat() {
+        let node = NodeId::from_raw(kani::any());
+        kani::assume(node.is_some());
+        round_trip(Heartbeat {
+            node: node.unwrap(),
+            faults: Faults::from_bits(kani::any()),
+            uptime: kani::any(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// About 65k f32 bit patterns spread over every exponent, with the edges, keeping those that
+    /// decode unchanged.
+    fn floats() -> impl Iterator<Item = f32> {
+        let edges = [
+            0.0,
+            -0.0,
+            f32::MIN,
+            f32::MAX,
+            f32::MIN_POSITIVE,
+            f32::EPSILON,
+        ];
+        (0..=u32::MAX)
+            .step_by(65_537)
+            .map(f32::from_bits)
+            .chain(edges)
+            .filter(|v| v.is_normal() || v.to_bits() == 0)
+    }
+
+    #[test]
+    fn test_decode_never_panics() {
+        let ids = (0..=0x20).chain([0x1FFF_FFFF, u32::MAX]);
+        for id in ids {
+            for len in 0..=8 {
+                for first in 0..=u8::MAX {
+                    for fill in [0x00, 0xFF, 0x7F, 0x80, 0xA5] {
+                        let mut data = [fill; 8];
+                        data[0] = first;
+                        decode_any(id, &data[..len]);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_bytes() {
+        round_trip(AutonDisable {});
+        round_trip(LockBrake {});
+        round_trip(UnlockBrake {});
+        round_trip(TrainingMode {});
+        for &source in EstopSource::ALL {
+            round_trip(EstopAll { source });
+        }
+    }
+
+    /// Each 16 bit field over every value, with the others at an edge.
+    #[test]
+    fn test_16_bit() {
+        for v in i16::MIN..=i16::MAX {
+            round_trip(MotorTemperature { temp: v });
+            round_trip(SetAngleCompact { angle: v });
+            round_trip(GetAngleCompact { angle: v });
+            round_trip(ImuRates {
+                yaw_rate: v,
+                lateral_accel: i16::MIN,
+                sequence: u16::MAX,
+            });
+            round_trip(ImuRates {
+                yaw_rate: -1,
+                lateral_accel: v,
+                sequence: 0,
+            });
+        }
+        for v in 0..=u16::MAX {
+            round_trip(SetBrake { centi_percent: v });
+            round_trip(SetSpeed { centi_percent: v });
+            round_trip(EncoderCount {
+                count: v,
+                velocity: -1.5,
+            });
+            round_trip(ImuRates {
+                yaw_rate: i16::MAX,
+                lateral_accel: 1,
+                sequence: v,
+            });
+            round_trip(CellVoltages::from(CellVoltages0 {
+                cell_1: v,
+                cell_2: !v,
+                cell_3: v.rotate_left(8),
+            }));
+            round_trip(CellVoltages::from(CellVoltages1 {
+                cell_4: !v,
+                cell_5: v,
+                cell_6: v.rotate_left(8),
+            }));
+            round_trip(Heartbeat {
+                node: NodeId::Drive,
+                faults: Faults::from_bits(v as u8),
+                uptime: v,
+            });
+        }
+    }
+
+    /// Every node with every fault byte, at the edges of uptime.
+    #[test]
+    fn test_heartbeat() {
+        for &node in NodeId::ALL {
+            for faults in 0..=u8::MAX {
+                for uptime in [0, 1, u16::MAX] {
+                    round_trip(Heartbeat {
+                        node,
+                        faults: Faults::from_bits(faults),
+                        uptime,
+                    });
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_floats() {
+        let mut checked = 0;
+        for v in floats() {
+            round_trip(SetAngle { angle: v });
+            round_trip(GetAngle { angle: v });
+            round_trip(EncoderCount {
+                count: u16::MAX,
+                velocity: v,
+            });
+            checked += 1;
+        }
+        assert!(checked > 60_000, "{checked}");
+    }
+
+    #[test]
+    fn test_dlc() {
+        for entry in REGISTRY.entries() {
+            assert!(entry.dlc <= 8, "{:#x} is over 8 bytes", entry.id);
+            let info = MESSAGES.iter().find(|m| m.id == entry.id).unwrap();
+            assert_eq!(info.dlc, entry.dlc, "{}", info.name);
+        }
+    }
+}
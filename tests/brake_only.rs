@@ -0,0 +1,95 @@
+//! Decoding with only the brake messages compiled in, as on the brake node. Run with
+//! `cargo test --no-default-features --features msgs-brake --test brake_only`.
+
+#![cfg(all(
+    feature = "msgs-brake",
+    not(any(
+        feature = "msgs-steering",
+        feature = "msgs-drive",
+        feature = "msgs-power"
+    ))
+))]
+
+use phnx_candefs::testing::MockFrame;
+use phnx_candefs::*;
+
+fn frame(id: u32, data: &[u8]) -> MockFrame {
+    MockFrame::extended(id, data)
+}
+
+#[test]
+fn test_in_group() {
+    let msg = CanMessage::from_frame(
+        SetBrake {
+            centi_percent: 4000,
+        }
+        .into_frame::<MockFrame>()
+        .unwrap(),
+    );
+    assert!(matches!(
+        msg,
+        Ok(CanMessage::SetBrake(SetBrake {
+            centi_percent: 4000
+        }))
+    ));
+
+    assert!(matches!(
+        CanMessage::from_frame(frame(0x2, &[])),
+        Ok(CanMessage::LockBrake(_))
+    ));
+    assert!(matches!(
+        CanMessage::from_frame(frame(0x3, &[])),
+        Ok(CanMessage::UnlockBrake(_))
+    ));
+
+    // System messages are always compiled in
+    assert!(matches!(
+        CanMessage::from_frame(frame(0x0, &[1])),
+        Ok(CanMessage::EstopAll(EstopAll {
+            source: EstopSource::Ros
+        }))
+    ));
+    assert!(matches!(
+        CanMessage::from_frame(frame(0xF, &[])),
+        Ok(CanMessage::AutonDisable(_))
+    ));
+    assert!(matches!(
+        CanMessage::from_frame(frame(0x8, &[])),
+        Ok(CanMessage::TrainingMode(_))
+    ));
+    assert!(matches!(
+        CanMessage::from_frame(frame(0xE, &[1, 0, 0x2C, 0x01])),
+        Ok(CanMessage::Heartbeat(Heartbeat {
+            node: NodeId::Steering,
+            uptime: 300,
+            ..
+        }))
+    ));
+
+    // The legacy layout is understood in this group too
+    assert!(matches!(
+        CanMessage::from_frame(frame(0x1, &[75])),
+        Ok(CanMessage::SetBrake(SetBrake {
+            centi_percent: 7500
+        }))
+    ));
+}
+
+#[test]
+fn test_out_of_group() {
+    // SetAngle, GetAngle, SetSpeed, EncoderCount, MotorTemperature, the compact angles,
+    // CellVoltages, and ImuRates, each with a payload that would decode if they were compiled in
+    let payload = [0x00, 0x00, 0xC0, 0x3F, 0x00, 0x00, 0x00];
+    for id in [0x4, 0x5, 0x6, 0x7, 0x9, 0xA, 0xB, 0xC, 0xD] {
+        assert!(REGISTRY.get(id).is_none(), "{id:#x}");
+        assert!(
+            CanMessage::from_frame(frame(id, &payload)).is_err(),
+            "{id:#x}"
+        );
+        assert!(CanMessageRef::from_parts(id, &payload).is_err(), "{id:#x}");
+    }
+
+    let ids: Vec<u32> = REGISTRY.entries().iter().map(|e| e.id).collect();
+    assert_eq!(ids, [0x0, 0x1, 0x2, 0x3, 0x8, 0xE, 0xF]);
+    assert_eq!(MESSAGES.len(), ids.len());
+}
@@ -0,0 +1,48 @@
+//! Encoding and decoding with a real driver's frames, to check that `MockFrame` is not hiding
+//! a difference. Run with `cargo test --features bxcan --test bxcan`.
+
+#![cfg(feature = "bxcan")]
+
+use bxcan::{Data, ExtendedId, Frame, Id, StandardId};
+use phnx_candefs::testing::MockFrame;
+use phnx_candefs::*;
+
+#[test]
+fn test_round_trip() {
+    let msg = EncoderCount {
+        count: 0x1234,
+        velocity: 10.2,
+    };
+    let frame: Frame = msg.into_frame().unwrap();
+    let mock: MockFrame = msg.into_frame().unwrap();
+
+    assert_eq!(
+        frame.id(),
+        Id::Extended(ExtendedId::new(EncoderCount::ID).unwrap())
+    );
+    assert_eq!(
+        frame.data().unwrap().as_ref(),
+        embedded_hal::can::Frame::data(&mock)
+    );
+    assert_eq!(frame.dlc() as usize, embedded_hal::can::Frame::dlc(&mock));
+
+    let Ok(CanMessage::EncoderCount(back)) = CanMessage::from_frame(frame.clone()) else {
+        panic!()
+    };
+    assert_eq!((back.count, back.velocity), (0x1234, 10.2));
+
+    let view = CanMessageRef::from_frame(&frame).unwrap();
+    assert_eq!(view.as_bytes(), frame.data().unwrap().as_ref());
+}
+
+#[test]
+fn test_rejects() {
+    let standard = Frame::new_data(StandardId::new(0x7).unwrap(), Data::new(&[0; 6]).unwrap());
+    assert!(CanMessage::from_frame(standard).is_err());
+
+    // Remote frames have no data, so only empty messages decode
+    let remote = Frame::new_remote(ExtendedId::new(LockBrake::ID).unwrap(), 0);
+    assert!(CanMessage::from_frame(remote).is_ok());
+    let remote = Frame::new_remote(ExtendedId::new(SetBrake::ID).unwrap(), 1);
+    assert!(CanMessage::from_frame(remote).is_err());
+}
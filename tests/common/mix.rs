@@ -0,0 +1,39 @@
+//! Frames in the mix the interface board forwards, shared by `tests/throughput.rs` and
+//! `benches/throughput.rs`.
+
+use phnx_candefs::testing::GOLDEN;
+use phnx_candefs::*;
+
+/// How often each golden vector is sent, relative to the others, roughly as on the kart.
+/// Telemetry is most of the traffic, and the system and brake lock messages are rare enough to
+/// leave out.
+fn weight(id: u32) -> usize {
+    match id {
+        ImuRates::ID => 12,
+        EncoderCount::ID | GetAngle::ID => 8,
+        SetAngle::ID | SetSpeed::ID => 6,
+        SetBrake::ID => 4,
+        GetAngleCompact::ID | SetAngleCompact::ID => 2,
+        MotorTemperature::ID | CellVoltages::ID => 1,
+        _ => 0,
+    }
+}
+
+/// `n` frames as ID and payload, drawn from the golden vectors by [`weight`], with a fixed seed.
+/// Every payload decodes.
+pub fn mixed_frames(n: usize) -> Vec<(u32, &'static [u8])> {
+    let pool: Vec<_> = GOLDEN
+        .iter()
+        .flat_map(|g| std::iter::repeat_n((g.id, g.data), weight(g.id)))
+        .collect();
+
+    let mut rng = 0x2545_F491_u32;
+    (0..n)
+        .map(|_| {
+            rng ^= rng << 13;
+            rng ^= rng >> 17;
+            rng ^= rng << 5;
+            pool[rng as usize % pool.len()]
+        })
+        .collect()
+}
@@ -0,0 +1,126 @@
+//! Cross-checks the big endian ImuRates against its DBC description, read the way cantools
+//! reads it, using the fixtures in `tests/fixtures`.
+
+#![cfg(feature = "msgs-drive")]
+
+use phnx_candefs::*;
+
+const DBC: &str = include_str!("fixtures/imu_rates.dbc");
+const FRAMES: &str = include_str!("fixtures/imu_rates.txt");
+
+/// A signal as described by an `SG_` line.
+struct DbcSignal<'a> {
+    name: &'a str,
+    start: usize,
+    len: usize,
+    big_endian: bool,
+    signed: bool,
+    scale: f64,
+    offset: f64,
+}
+
+fn parse_signal(line: &str) -> DbcSignal<'_> {
+    // SG_ name : start|len@order sign (scale,offset) ...
+    let mut parts = line.split_whitespace().skip(1);
+    let name = parts.next().unwrap();
+    assert_eq!(parts.next(), Some(":"));
+
+    let layout = parts.next().unwrap();
+    let (start, rest) = layout.split_once('|').unwrap();
+    let (len, rest) = rest.split_once('@').unwrap();
+    let (scale, offset) = parts
+        .next()
+        .unwrap()
+        .trim_matches(|c| c == '(' || c == ')')
+        .split_once(',')
+        .unwrap();
+
+    DbcSignal {
+        name,
+        start: start.parse().unwrap(),
+        len: len.parse().unwrap(),
+        big_endian: rest.starts_with('0'),
+        signed: rest.ends_with('-'),
+        scale: scale.parse().unwrap(),
+        offset: offset.parse().unwrap(),
+    }
+}
+
+/// Physical value of a signal, reading bits as cantools does. Bits are numbered from the least
+/// significant of byte 0, and a big endian signal starts at its most significant bit, running
+/// down through each byte before moving to the top of the next.
+fn extract(signal: &DbcSignal, data: &[u8]) -> f64 {
+    let mut raw = 0u64;
+    let mut pos = signal.start;
+    for i in 0..signal.len {
+        let bit = (data[pos / 8] >> (pos % 8)) as u64 & 1;
+        if signal.big_endian {
+            raw = raw << 1 | bit;
+            pos = if pos.is_multiple_of(8) {
+                pos + 15
+            } else {
+                pos - 1
+            };
+        } else {
+            raw |= bit << i;
+            pos += 1;
+        }
+    }
+
+    let raw = if signal.signed && raw >> (signal.len - 1) & 1 == 1 {
+        raw as i64 - (1 << signal.len)
+    } else {
+        raw as i64
+    };
+    raw as f64 * signal.scale + signal.offset
+}
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_dbc_matches_fixture() {
+    let mut out = String::new();
+    write_dbc(&mut out, &[MessageInfo::of::<ImuRates>()]).unwrap();
+    assert_eq!(out, DBC);
+}
+
+#[test]
+fn test_decode_matches_dbc() {
+    let signals: Vec<DbcSignal> = DBC
+        .lines()
+        .filter(|l| l.trim_start().starts_with("SG_ "))
+        .map(parse_signal)
+        .collect();
+    assert_eq!(signals.len(), ImuRates::SIGNALS.len());
+
+    let frames = FRAMES.lines().filter(|l| !l.starts_with('#'));
+    for line in frames {
+        let mut parts = line.split_whitespace();
+        let data = hex(parts.next().unwrap());
+        let msg = ImuRates::decode(&data).unwrap();
+
+        for (expected, signal) in parts.zip(&signals) {
+            let (name, value) = expected.split_once('=').unwrap();
+            let value: f64 = value.parse().unwrap();
+            assert_eq!(name, signal.name);
+
+            let ours = match name {
+                "yaw_rate" => msg.yaw_rate_scaled() as f64,
+                "lateral_accel" => msg.lateral_accel_scaled() as f64,
+                "sequence" => msg.sequence as f64,
+                _ => panic!("{name}"),
+            };
+            assert!((extract(signal, &data) - value).abs() < 1e-9, "{line}");
+            assert!((ours - value).abs() < 1e-4, "{line}: {name} {ours}");
+        }
+
+        // And back to the same payload
+        let (buf, len) = msg.encode();
+        assert_eq!(buf[..len], data[..]);
+    }
+}
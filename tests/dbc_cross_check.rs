@@ -0,0 +1,190 @@
+//! Cross-checks the exported DBC against the Rust decoders. The DBC from [`MESSAGES`] is parsed
+//! with `can-dbc`, and each frame of a corpus is decoded both ways: by a small interpreter of
+//! the DBC signals, and by `CanMessage`, comparing the engineering value of every signal.
+//!
+//! The corpus is the golden vectors, and random payloads for every message.
+
+#![cfg(feature = "msgs-all")]
+
+use can_dbc::{ByteOrder, Dbc, MessageId, MultiplexIndicator, SignalExtendedValueType, ValueType};
+use phnx_candefs::testing::GOLDEN;
+use phnx_candefs::*;
+use proptest::prelude::*;
+use std::sync::OnceLock;
+
+fn dbc() -> &'static Dbc {
+    static DBC: OnceLock<Dbc> = OnceLock::new();
+    DBC.get_or_init(|| {
+        let mut out = String::new();
+        write_dbc(&mut out, &MESSAGES).unwrap();
+        Dbc::try_from(out.as_str()).unwrap()
+    })
+}
+
+/// Engineering value of every signal of a decoded message, by name.
+///
+/// The match has no wildcard, so a new message does not compile until it is listed here.
+fn fields(msg: &CanMessage) -> Vec<(&'static str, f64)> {
+    let fields: &[(&str, f32)] = match msg {
+        CanMessage::EstopAll(m) => &[("source", m.source.raw() as f32)],
+        CanMessage::AutonDisable(_)
+        | CanMessage::LockBrake(_)
+        | CanMessage::UnlockBrake(_)
+        | CanMessage::TrainingMode(_) => &[],
+        CanMessage::SetBrake(m) => &[("centi_percent", m.percent())],
+        CanMessage::SetSpeed(m) => &[("centi_percent", m.percent())],
+        CanMessage::SetAngle(m) => &[("angle", m.angle)],
+        CanMessage::GetAngle(m) => &[("angle", m.angle)],
+        CanMessage::EncoderCount(m) => &[("count", m.count as f32), ("velocity", m.velocity)],
+        CanMessage::MotorTemperature(m) => &[("temp", m.temp_scaled())],
+        CanMessage::SetAngleCompact(m) => &[("angle", m.angle_scaled())],
+        CanMessage::GetAngleCompact(m) => &[("angle", m.angle_scaled())],
+        CanMessage::CellVoltages(CellVoltages::CellVoltages0(m)) => &[
+            ("page", 0.0),
+            ("cell_1", m.cell_1_scaled()),
+            ("cell_2", m.cell_2_scaled()),
+            ("cell_3", m.cell_3_scaled()),
+        ],
+        CanMessage::CellVoltages(CellVoltages::CellVoltages1(m)) => &[
+            ("page", 1.0),
+            ("cell_4", m.cell_4_scaled()),
+            ("cell_5", m.cell_5_scaled()),
+            ("cell_6", m.cell_6_scaled()),
+        ],
+        CanMessage::ImuRates(m) => &[
+            ("yaw_rate", m.yaw_rate_scaled()),
+            ("lateral_accel", m.lateral_accel_scaled()),
+            ("sequence", m.sequence as f32),
+        ],
+        CanMessage::Heartbeat(m) => &[
+            ("node", m.node as u8 as f32),
+            ("faults", m.faults.bits() as f32),
+            ("uptime", m.uptime as f32),
+        ],
+    };
+    fields.iter().map(|&(name, v)| (name, v as f64)).collect()
+}
+
+/// Raw bits of a signal, numbered as in DBC from the least significant of byte 0. A big endian
+/// signal starts at its most significant bit, running down through each byte before moving to
+/// the top of the next.
+fn raw_bits(signal: &can_dbc::Signal, data: &[u8]) -> u64 {
+    let mut raw = 0u64;
+    let mut pos = signal.start_bit as usize;
+    for i in 0..signal.size {
+        let bit = (data[pos / 8] >> (pos % 8)) as u64 & 1;
+        match signal.byte_order {
+            ByteOrder::BigEndian => {
+                raw = raw << 1 | bit;
+                pos = if pos.is_multiple_of(8) {
+                    pos + 15
+                } else {
+                    pos - 1
+                };
+            }
+            ByteOrder::LittleEndian => {
+                raw |= bit << i;
+                pos += 1;
+            }
+        }
+    }
+    raw
+}
+
+/// Engineering value of a signal, as any DBC reader would compute it.
+fn extract(id: MessageId, signal: &can_dbc::Signal, data: &[u8]) -> f64 {
+    let raw = raw_bits(signal, data);
+    let value = match dbc().extended_value_type_for_signal(id, &signal.name) {
+        Some(SignalExtendedValueType::IEEEfloat32Bit) => f32::from_bits(raw as u32) as f64,
+        Some(SignalExtendedValueType::IEEEdouble64bit) => f64::from_bits(raw),
+        _ => match signal.value_type {
+            ValueType::Signed if raw >> (signal.size - 1) & 1 == 1 => {
+                raw as i64 as f64 - (1u64 << signal.size) as f64
+            }
+            _ => raw as f64,
+        },
+    };
+    value * signal.factor + signal.offset
+}
+
+/// Checks that the DBC and the Rust decoders agree on a frame, returning each disagreement.
+fn disagreements(id: u32, data: &[u8]) -> Vec<String> {
+    let Ok(msg) = CanMessage::from_parts(id, data) else {
+        return vec![];
+    };
+    let dbc_id = MessageId::Extended(id);
+    let Some(message) = dbc().messages.iter().find(|m| m.id == dbc_id) else {
+        return vec![format!("{id:#x} is not in the DBC")];
+    };
+
+    let selector = message
+        .signals
+        .iter()
+        .find(|s| s.multiplexer_indicator == MultiplexIndicator::Multiplexor)
+        .map(|s| extract(dbc_id, s, data) as u64);
+    let active = message
+        .signals
+        .iter()
+        .filter(|s| match s.multiplexer_indicator {
+            MultiplexIndicator::MultiplexedSignal(page) => Some(page) == selector,
+            _ => true,
+        });
+
+    let rust = fields(&msg);
+    let mut errors = vec![];
+    for signal in active.clone() {
+        let dbc_value = extract(dbc_id, signal, data);
+        match rust.iter().find(|(name, _)| *name == signal.name) {
+            Some(&(_, rust_value)) if close(dbc_value, rust_value) => {}
+            Some(&(_, rust_value)) => errors.push(format!(
+                "{}.{}: DBC {dbc_value}, Rust {rust_value}, payload {data:02X?}",
+                message.name, signal.name
+            )),
+            None => errors.push(format!("{}.{} is not decoded", message.name, signal.name)),
+        }
+    }
+    for (name, _) in rust {
+        if !active.clone().any(|s| s.name == name) {
+            errors.push(format!("{}.{name} is not in the DBC", message.name));
+        }
+    }
+    errors
+}
+
+fn close(a: f64, b: f64) -> bool {
+    (a.is_nan() && b.is_nan()) || (a - b).abs() <= 1e-5 * a.abs().max(b.abs()).max(1.0)
+}
+
+#[test]
+fn test_golden() {
+    let errors: Vec<String> = GOLDEN
+        .iter()
+        .flat_map(|g| disagreements(g.id, g.data))
+        .collect();
+    assert!(errors.is_empty(), "{errors:#?}");
+}
+
+#[test]
+fn test_every_message_in_dbc() {
+    for info in &MESSAGES {
+        let message = dbc()
+            .messages
+            .iter()
+            .find(|m| m.id == MessageId::Extended(info.id));
+        assert!(message.is_some(), "{} is not in the DBC", info.name);
+        assert_eq!(message.unwrap().size, info.dlc as u64, "{}", info.name);
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    /// Random payloads, skipping those that do not decode.
+    #[test]
+    fn test_random(index in 0..ALL_IDS.len(), data in any::<[u8; 8]>()) {
+        let id = ALL_IDS[index];
+        let len = REGISTRY.get(id).unwrap().dlc;
+        let errors = disagreements(id, &data[..len]);
+        prop_assert!(errors.is_empty(), "{:#?}", errors);
+    }
+}
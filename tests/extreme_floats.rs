@@ -0,0 +1,139 @@
+//! Extreme float payloads through decoding, the Ackermann conversion, and encoding again, under
+//! each `DecodePolicy`.
+
+#![cfg(feature = "msgs-all")]
+
+use phnx_candefs::*;
+
+/// Bit patterns at the edges of f32, and what `DecodePolicy::Normalize` reads them as.
+const PATTERNS: [(&str, u32, Option<u32>); 14] = [
+    ("0.0", 0x0000_0000, Some(0)),
+    ("-0.0", 0x8000_0000, Some(0)),
+    ("smallest subnormal", 0x0000_0001, Some(0)),
+    ("largest subnormal", 0x007F_FFFF, Some(0)),
+    ("negative subnormal", 0x8000_0001, Some(0)),
+    ("MIN_POSITIVE", 0x0080_0000, Some(0x0080_0000)),
+    ("-MIN_POSITIVE", 0x8080_0000, Some(0x8080_0000)),
+    ("MAX", 0x7F7F_FFFF, Some(0x7F7F_FFFF)),
+    ("MIN", 0xFF7F_FFFF, Some(0xFF7F_FFFF)),
+    ("INFINITY", 0x7F80_0000, None),
+    ("NEG_INFINITY", 0xFF80_0000, None),
+    ("NaN", 0x7FC0_0000, None),
+    ("signaling NaN", 0x7F80_0001, None),
+    ("negative NaN", 0xFFC0_0000, None),
+];
+
+/// A payload of each message with a float, with the float set to `bits`.
+fn payloads(bits: u32) -> [(u32, [u8; 8], usize); 3] {
+    let v = f32::from_bits(bits);
+    let parts = |msg: CanMessage| {
+        let (id, data, len) = msg.to_parts().unwrap();
+        (id.as_raw(), data, len)
+    };
+    [
+        parts(SetAngle { angle: v }.into()),
+        parts(GetAngle { angle: v }.into()),
+        parts(
+            EncoderCount {
+                count: 0x1234,
+                velocity: v,
+            }
+            .into(),
+        ),
+    ]
+}
+
+/// The float field of a decoded message.
+fn float_of(msg: &CanMessage) -> f32 {
+    match msg {
+        CanMessage::SetAngle(m) => m.angle,
+        CanMessage::GetAngle(m) => m.angle,
+        CanMessage::EncoderCount(m) => m.velocity,
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn test_normalize() {
+    for (name, bits, expected) in PATTERNS {
+        for (id, data, len) in payloads(bits) {
+            let data = &data[..len];
+            let decoded = CanMessage::from_parts_with_policy(id, data, DecodePolicy::Normalize);
+            let label = (name, id);
+
+            // The default entry points agree, owned and viewed
+            let plain = CanMessage::from_parts(id, data);
+            let viewed = CanMessageRef::from_parts(id, data).map(|v| v.into_owned());
+            assert_eq!(plain.is_ok(), decoded.is_ok(), "{label:?}");
+            assert_eq!(viewed.is_ok(), decoded.is_ok(), "{label:?}");
+
+            let Some(expected) = expected else {
+                assert!(decoded.is_err(), "{label:?}");
+                continue;
+            };
+            // SetAngle is also limited to the steering stops
+            if id == SetAngle::ID && f32::from_bits(expected).abs() > MAX_ANGLE {
+                assert!(
+                    matches!(decoded, Err(ConvertErr::OutOfRange { field: "angle" })),
+                    "{label:?}"
+                );
+                continue;
+            }
+            let msg = decoded.unwrap();
+            assert_eq!(float_of(&msg).to_bits(), expected, "{label:?}");
+            assert_eq!(float_of(&plain.unwrap()).to_bits(), expected, "{label:?}");
+            assert_eq!(float_of(&viewed.unwrap()).to_bits(), expected, "{label:?}");
+
+            if let CanMessage::GetAngle(angle) = msg {
+                assert!(!angle.ackermann_angle().is_nan(), "{label:?}");
+            }
+
+            // Encoding again is stable, and only changes the bytes of values read as 0.0
+            let (_, encoded, encoded_len) = msg.to_parts().unwrap();
+            let again = CanMessage::from_parts(id, &encoded[..encoded_len]).unwrap();
+            assert_eq!(again.to_parts().unwrap().1, encoded, "{label:?}");
+            assert_eq!(
+                encoded[..encoded_len] == *data,
+                bits == expected,
+                "{label:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_raw() {
+    for (name, bits, _) in PATTERNS {
+        for (id, data, len) in payloads(bits) {
+            let data = &data[..len];
+            let label = (name, id);
+            let msg = CanMessage::from_parts_with_policy(id, data, DecodePolicy::Raw).unwrap();
+            assert_eq!(float_of(&msg).to_bits(), bits, "{label:?}");
+
+            // NaN goes through the conversion, for a logger to record
+            if let CanMessage::GetAngle(angle) = msg {
+                let converted = angle.ackermann_angle();
+                assert_eq!(
+                    converted.is_nan(),
+                    f32::from_bits(bits).is_nan(),
+                    "{label:?}"
+                );
+            }
+
+            let (_, encoded, encoded_len) = msg.to_parts().unwrap();
+            assert_eq!(&encoded[..encoded_len], data, "{label:?}");
+        }
+    }
+}
+
+#[test]
+fn test_decode_with() {
+    let (_, data, len) = payloads(0x8000_0001)[1];
+    let raw = GetAngle::decode_with(&data[..len], DecodePolicy::Raw).unwrap();
+    assert_eq!(raw.angle.to_bits(), 0x8000_0001);
+    assert_eq!(GetAngle::decode(&data[..len]).unwrap().angle.to_bits(), 0);
+
+    let frame = testing::MockFrame::extended(GetAngle::ID, &[0, 0, 0xC0, 0x7F]);
+    assert!(CanMessage::from_frame_with_policy(frame, DecodePolicy::Normalize).is_err());
+    assert!(CanMessage::from_frame_with_policy(frame, DecodePolicy::Raw).is_ok());
+}
@@ -0,0 +1,90 @@
+//! Checks the fuzz seeds in `fuzz/seeds/decode` are the golden vectors, laid out as
+//! `fuzz/fuzz_targets/decode.rs` reads its input.
+//!
+//! After changing the golden vectors, regenerate them with
+//! `UPDATE_SEEDS=1 cargo test --all-features --test fuzz_seeds`.
+
+#![cfg(feature = "msgs-all")]
+
+use phnx_candefs::testing::{Golden, GOLDEN, LEGACY_GOLDEN};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+const SEEDS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fuzz/seeds/decode");
+
+/// Message name in snake case, like `set_brake`.
+fn snake_case(name: &str) -> String {
+    let mut out = String::new();
+    for c in name.chars() {
+        if c.is_ascii_uppercase() && !out.is_empty() {
+            out.push('_');
+        }
+        out.push(c.to_ascii_lowercase());
+    }
+    out
+}
+
+/// An extended data frame with the payload length as its DLC.
+fn seed(golden: &Golden) -> Vec<u8> {
+    let mut input = vec![0];
+    input.extend(golden.id.to_le_bytes());
+    input.push(golden.data.len() as u8);
+    input.extend(golden.data);
+    input
+}
+
+/// Every seed by file name, numbered within each message.
+fn golden_seeds() -> BTreeMap<String, Vec<u8>> {
+    let mut seeds = BTreeMap::new();
+    for (vectors, suffix) in [(GOLDEN, ""), (LEGACY_GOLDEN, "_legacy")] {
+        let mut counts = BTreeMap::new();
+        for golden in vectors {
+            let name = snake_case(golden.msg.kind().name()) + suffix;
+            let n = counts.entry(name.clone()).or_insert(0);
+            seeds.insert(format!("{name}_{n}"), seed(golden));
+            *n += 1;
+        }
+    }
+    seeds
+}
+
+fn read_seeds(dir: &Path) -> BTreeMap<String, Vec<u8>> {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_str().unwrap().to_string();
+            (name, std::fs::read(&path).unwrap())
+        })
+        .collect()
+}
+
+#[test]
+fn test_seeds_up_to_date() {
+    let seeds = golden_seeds();
+    let dir = Path::new(SEEDS);
+
+    if std::env::var_os("UPDATE_SEEDS").is_some() {
+        for name in read_seeds(dir).keys() {
+            std::fs::remove_file(dir.join(name)).unwrap();
+        }
+        for (name, input) in &seeds {
+            std::fs::write(dir.join(name), input).unwrap();
+        }
+    } else {
+        assert!(
+            read_seeds(dir) == seeds,
+            "fuzz/seeds/decode is out of date, rerun with UPDATE_SEEDS=1"
+        );
+    }
+}
+
+#[test]
+fn test_seeds_cover_every_message() {
+    let seeds = golden_seeds();
+    for kind in phnx_candefs::MessageKind::ALL {
+        let name = snake_case(kind.name());
+        assert!(seeds.contains_key(&format!("{name}_0")), "{name}");
+    }
+    assert!(seeds.contains_key("set_brake_legacy_0"));
+}
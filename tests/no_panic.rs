@@ -0,0 +1,21 @@
+//! Checks that no panic is reachable from the public API, by building `no-panic/`, which only
+//! links if every panic was optimized out.
+
+use std::process::Command;
+
+#[test]
+fn test_no_panic() {
+    let root = env!("CARGO_MANIFEST_DIR");
+    let status = Command::new(env!("CARGO"))
+        .args(["build", "--release", "--manifest-path"])
+        .arg(format!("{root}/no-panic/Cargo.toml"))
+        .arg("--target-dir")
+        .arg(format!("{root}/target/no-panic"))
+        .status()
+        .unwrap();
+
+    assert!(
+        status.success(),
+        "a panic is reachable, see no-panic/main.rs to find it"
+    );
+}
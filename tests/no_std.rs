@@ -0,0 +1,52 @@
+//! Checks that the crate builds and works without std, with `no-std/`: built for a target with no
+//! std at all, then run on the host.
+
+#![cfg(feature = "msgs-all")]
+
+use std::process::Command;
+
+const TARGET: &str = "thumbv7m-none-eabi";
+
+fn cargo(args: &[&str]) -> Command {
+    let root = env!("CARGO_MANIFEST_DIR");
+    let mut cmd = Command::new(env!("CARGO"));
+    cmd.args(args)
+        .arg("--manifest-path")
+        .arg(format!("{root}/no-std/Cargo.toml"))
+        .arg("--target-dir")
+        .arg(format!("{root}/target/no-std"));
+    cmd
+}
+
+#[test]
+fn test_builds_without_std() {
+    let status = cargo(&["build", "--lib", "--target", TARGET])
+        .status()
+        .unwrap();
+    assert!(
+        status.success(),
+        "no-std/ does not build for {TARGET}, so std is used, or the target is missing \
+         (rustup target add {TARGET})"
+    );
+}
+
+#[test]
+fn test_runs_without_std() {
+    let output = cargo(&["run", "--release", "--quiet"]).output().unwrap();
+    assert!(
+        output.status.success(),
+        "no-std/ failed a check:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let checked = stdout
+        .strip_prefix("checked ")
+        .and_then(|s| s.split_once(' '))
+        .map(|(n, _)| n.parse::<usize>().unwrap());
+    assert_eq!(
+        checked,
+        Some(phnx_candefs::testing::GOLDEN.len()),
+        "{stdout}"
+    );
+}
@@ -0,0 +1,267 @@
+//! Property tests that every message survives encoding to a frame and decoding back.
+//!
+//! Each message has its own test, so a failure names the kind, and the shrunk value is printed
+//! with its fields. Decoded messages are compared by encoding them again, so NaN angles compare
+//! equal to themselves.
+
+#![cfg(feature = "msgs-all")]
+
+use phnx_candefs::testing::{roundtrip, MockFrame};
+use phnx_candefs::*;
+use proptest::prelude::*;
+
+/// Cases per test, kept low so the whole file runs in about a second.
+const CASES: u32 = 256;
+
+/// The ID and payload of a frame.
+fn parts(frame: &MockFrame) -> (u32, Vec<u8>) {
+    (
+        frame.raw_id(),
+        embedded_hal::can::Frame::data(frame).to_vec(),
+    )
+}
+
+/// Any float that decodes unchanged under `DecodePolicy::Normalize`: normal, or 0.0.
+fn float() -> impl Strategy<Value = f32> {
+    use proptest::num::f32::{NEGATIVE, NORMAL, POSITIVE};
+    prop_oneof![9 => POSITIVE | NEGATIVE | NORMAL, 1 => Just(0.0)]
+}
+
+/// Any steering command angle that decodes unchanged: within `MAX_ANGLE`, and normal or 0.0.
+fn angle() -> impl Strategy<Value = f32> {
+    prop_oneof![
+        8 => (-MAX_ANGLE..=MAX_ANGLE).prop_filter("normal", |v| v.is_normal()),
+        1 => Just(0.0),
+        1 => prop_oneof![Just(MAX_ANGLE), Just(-MAX_ANGLE)],
+    ]
+}
+
+macro_rules! round_trip {
+    ($($test:ident: $name:ident = $strategy:expr,)+) => {
+        /// Encodes any message, as `IscFrame::into_frame` on the message it holds.
+        ///
+        /// The match has no wildcard, so adding a message without a test here does not compile.
+        fn encode(msg: CanMessage) -> MockFrame {
+            match msg {
+                $(CanMessage::$name(msg) => msg.into_frame().unwrap(),)+
+            }
+        }
+
+        /// Any message, of any kind.
+        fn any_message() -> impl Strategy<Value = CanMessage> {
+            prop_oneof![$(($strategy).prop_map(CanMessage::from)),+]
+        }
+
+        proptest! {
+            #![proptest_config(ProptestConfig::with_cases(CASES))]
+
+            $(
+                #[test]
+                fn $test(msg in $strategy) {
+                    check_round_trip(msg)?;
+                }
+            )+
+        }
+    };
+}
+
+/// Checks that a message decodes from its own frame as the same kind, and encodes back to the
+/// same frame.
+fn check_round_trip<M>(msg: M) -> Result<(), TestCaseError>
+where
+    M: IscFrame + Into<CanMessage> + Copy,
+{
+    let frame: MockFrame = msg.into_frame().unwrap();
+    let expected = parts(&frame);
+
+    let back = CanMessage::from_frame(frame);
+    prop_assert!(back.is_ok(), "{} did not decode", M::NAME);
+    prop_assert_eq!(
+        &parts(&encode(back.unwrap())),
+        &expected,
+        "{} decoded",
+        M::NAME
+    );
+
+    let back = M::decode(embedded_hal::can::Frame::data(&frame));
+    prop_assert!(back.is_ok(), "{} did not decode alone", M::NAME);
+    prop_assert_eq!(
+        parts(&back.unwrap().into_frame().unwrap()),
+        expected,
+        "{} decoded alone",
+        M::NAME
+    );
+    Ok(())
+}
+
+round_trip! {
+    test_estop_all: EstopAll = proptest::sample::select(EstopSource::ALL)
+        .prop_map(|source| EstopAll { source }),
+    test_auton_disable: AutonDisable = Just(AutonDisable {}),
+    test_set_brake: SetBrake =
+        (0..=MAX_CENTI_PERCENT).prop_map(|centi_percent| SetBrake { centi_percent }),
+    test_lock_brake: LockBrake = Just(LockBrake {}),
+    test_unlock_brake: UnlockBrake = Just(UnlockBrake {}),
+    test_set_angle: SetAngle = angle().prop_map(|angle| SetAngle { angle }),
+    test_get_angle: GetAngle = float().prop_map(|angle| GetAngle { angle }),
+    test_set_speed: SetSpeed =
+        (0..=MAX_CENTI_PERCENT).prop_map(|centi_percent| SetSpeed { centi_percent }),
+    test_encoder_count: EncoderCount = (any::<u16>(), float())
+        .prop_map(|(count, velocity)| EncoderCount { count, velocity }),
+    test_training_mode: TrainingMode = Just(TrainingMode {}),
+    test_motor_temperature: MotorTemperature =
+        any::<i16>().prop_map(|temp| MotorTemperature { temp }),
+    test_set_angle_compact: SetAngleCompact =
+        (-2000..=2000i16).prop_map(|angle| SetAngleCompact { angle }),
+    test_get_angle_compact: GetAngleCompact =
+        any::<i16>().prop_map(|angle| GetAngleCompact { angle }),
+    test_cell_voltages: CellVoltages = prop_oneof![
+        any::<[u16; 3]>().prop_map(|[cell_1, cell_2, cell_3]| CellVoltages::from(
+            CellVoltages0 { cell_1, cell_2, cell_3 }
+        )),
+        any::<[u16; 3]>().prop_map(|[cell_4, cell_5, cell_6]| CellVoltages::from(
+            CellVoltages1 { cell_4, cell_5, cell_6 }
+        )),
+    ],
+    test_imu_rates: ImuRates = any::<(i16, i16, u16)>().prop_map(
+        |(yaw_rate, lateral_accel, sequence)| ImuRates { yaw_rate, lateral_accel, sequence }
+    ),
+    test_heartbeat: Heartbeat = (proptest::sample::select(NodeId::ALL), any::<(u8, u16)>())
+        .prop_map(|(node, (faults, uptime))| Heartbeat {
+            node,
+            faults: Faults::from_bits(faults),
+            uptime,
+        }),
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(CASES))]
+
+    /// Nothing the crate encodes is rejected by any of the ways to decode it.
+    #[test]
+    fn test_encoded_decodes(msg in any_message()) {
+        let frame = encode(msg);
+        let (id, data) = parts(&frame);
+
+        prop_assert!(CanMessage::from_frame(frame).is_ok(), "{:?}", msg);
+        prop_assert!(CanMessageRef::from_frame(&frame).is_ok(), "{:?}", msg);
+        prop_assert!(CanMessageRef::from_parts(id, &data).is_ok(), "{:?}", msg);
+        prop_assert!(matches!(REGISTRY.decode(id, &data), Some(Ok(_))), "{:?}", msg);
+    }
+}
+
+/// Checks that a message comes back from `testing::roundtrip` as the same kind with the same
+/// fields, compared by encoding, and with a payload of its DLC.
+fn check_boundary<M>(msg: M)
+where
+    M: IscFrame + Copy + std::fmt::Debug,
+{
+    let back = roundtrip(msg).to_parts().unwrap();
+    let (id, data, len) = msg.to_parts().unwrap();
+    assert_eq!(back, (id, data, len), "{msg:?}");
+    assert_eq!(len, M::DLC, "{msg:?}");
+}
+
+#[test]
+fn test_boundaries() {
+    check_boundary(AutonDisable {});
+    check_boundary(LockBrake {});
+    check_boundary(UnlockBrake {});
+    check_boundary(TrainingMode {});
+    for centi_percent in [0, 1, MAX_CENTI_PERCENT] {
+        check_boundary(SetBrake { centi_percent });
+        check_boundary(SetSpeed { centi_percent });
+    }
+    for angle in [0.0, MAX_ANGLE, -MAX_ANGLE, f32::MIN_POSITIVE, -1.0] {
+        check_boundary(SetAngle { angle });
+    }
+    for angle in [0.0, f32::MAX, -f32::MAX, f32::MIN_POSITIVE, -1.0] {
+        check_boundary(GetAngle { angle });
+        for count in [0, u16::MAX] {
+            check_boundary(EncoderCount {
+                count,
+                velocity: angle,
+            });
+        }
+    }
+    for v in [0, 1, -1, i16::MIN, i16::MAX] {
+        check_boundary(MotorTemperature { temp: v });
+        check_boundary(GetAngleCompact { angle: v });
+        check_boundary(ImuRates {
+            yaw_rate: v,
+            lateral_accel: v,
+            sequence: v as u16,
+        });
+    }
+    for &node in NodeId::ALL {
+        for faults in [
+            Faults::empty(),
+            Faults::from_bits(Faults::DEFINED),
+            Faults::from_bits(0xFF),
+        ] {
+            for uptime in [0, 1, u16::MAX] {
+                check_boundary(Heartbeat {
+                    node,
+                    faults,
+                    uptime,
+                });
+            }
+        }
+    }
+    for v in [0, 1, u16::MAX] {
+        check_boundary(CellVoltages::from(CellVoltages0 {
+            cell_1: v,
+            cell_2: v,
+            cell_3: v,
+        }));
+        check_boundary(CellVoltages::from(CellVoltages1 {
+            cell_4: v,
+            cell_5: v,
+            cell_6: v,
+        }));
+    }
+}
+
+/// Commands past their limits are rejected by the default policy, naming the field, and only
+/// round trip raw.
+#[test]
+fn test_out_of_range() {
+    fn check<M: IscFrame + Copy + std::fmt::Debug>(msg: M, field: &str) {
+        let frame: MockFrame = msg.into_frame().unwrap();
+        let decoded = CanMessage::from_frame(frame);
+        assert!(
+            matches!(decoded, Err(ConvertErr::OutOfRange { field: f }) if f == field),
+            "{msg:?} decodes as {decoded:?}"
+        );
+        assert!(CanMessageRef::from_frame(&frame).is_err(), "{msg:?}");
+
+        let raw = CanMessage::from_frame_with_policy(frame, DecodePolicy::Raw).unwrap();
+        assert_eq!(raw.to_parts().unwrap(), msg.to_parts().unwrap(), "{msg:?}");
+    }
+
+    for centi_percent in [MAX_CENTI_PERCENT + 1, u16::MAX] {
+        check(SetBrake { centi_percent }, "centi_percent");
+        check(SetSpeed { centi_percent }, "centi_percent");
+    }
+
+    for angle in [MAX_ANGLE + 0.01, -MAX_ANGLE - 0.01, f32::MAX, f32::MIN] {
+        check(SetAngle { angle }, "angle");
+    }
+    for angle in [2001, -2001, i16::MIN, i16::MAX] {
+        check(SetAngleCompact { angle }, "angle");
+    }
+}
+
+/// NaN is rejected by the default policy, so it only round trips raw.
+#[test]
+fn test_nan_boundary() {
+    let frame: MockFrame = SetAngle { angle: f32::NAN }.into_frame().unwrap();
+    assert!(CanMessage::from_frame(frame).is_err());
+    let raw = CanMessage::from_frame_with_policy(frame, DecodePolicy::Raw).unwrap();
+    assert!(matches!(raw, CanMessage::SetAngle(a) if a.angle.is_nan()));
+
+    let velocity = f32::NAN;
+    let frame: MockFrame = EncoderCount { count: 7, velocity }.into_frame().unwrap();
+    let raw = CanMessage::from_frame_with_policy(frame, DecodePolicy::Raw).unwrap();
+    assert!(matches!(raw, CanMessage::EncoderCount(e) if e.count == 7 && e.velocity.is_nan()));
+}
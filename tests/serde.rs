@@ -0,0 +1,84 @@
+//! Every message through serde, as JSON for logs and the ROS bridge, and as postcard, which
+//! needs no allocator, for firmware.
+//!
+//! Run with `cargo test --features serde --test serde`.
+
+#![cfg(all(feature = "serde", feature = "msgs-all"))]
+
+use phnx_candefs::testing::GOLDEN;
+use phnx_candefs::*;
+
+#[test]
+fn test_json() {
+    for golden in GOLDEN {
+        let json = serde_json::to_string(&golden.msg).unwrap();
+        let back: CanMessage = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            back.to_parts().unwrap(),
+            golden.msg.to_parts().unwrap(),
+            "{json}"
+        );
+    }
+}
+
+#[test]
+fn test_json_format() {
+    let json = |msg: CanMessage| serde_json::to_string(&msg).unwrap();
+    assert_eq!(
+        json(SetAngle { angle: 4.8 }.into()),
+        r#"{"SetAngle":{"angle":4.8}}"#
+    );
+    assert_eq!(json(LockBrake {}.into()), r#"{"LockBrake":{}}"#);
+    assert_eq!(
+        json(
+            CellVoltages::from(CellVoltages1 {
+                cell_4: 3700,
+                cell_5: 3712,
+                cell_6: 3698,
+            })
+            .into()
+        ),
+        r#"{"CellVoltages":{"CellVoltages1":{"cell_4":3700,"cell_5":3712,"cell_6":3698}}}"#
+    );
+
+    let msg: CanMessage = serde_json::from_str(r#"{"SetBrake":{"centi_percent":4000}}"#).unwrap();
+    assert!(matches!(
+        msg,
+        CanMessage::SetBrake(SetBrake {
+            centi_percent: 4000
+        })
+    ));
+    assert!(serde_json::from_str::<CanMessage>(r#"{"SetBrake":{}}"#).is_err());
+    assert!(serde_json::from_str::<CanMessage>(r#"{"Unknown":{}}"#).is_err());
+
+    // Messages serialize alone too
+    let brake: SetBrake = serde_json::from_str(r#"{"centi_percent":4000}"#).unwrap();
+    assert_eq!(
+        brake,
+        SetBrake {
+            centi_percent: 4000
+        }
+    );
+}
+
+#[test]
+fn test_postcard() {
+    for golden in GOLDEN {
+        let mut buf = [0; 32];
+        let bytes = postcard::to_slice(&golden.msg, &mut buf).unwrap();
+        let back: CanMessage = postcard::from_bytes(bytes).unwrap();
+        assert_eq!(
+            back.to_parts().unwrap(),
+            golden.msg.to_parts().unwrap(),
+            "{:?}",
+            golden.msg
+        );
+    }
+
+    let mut buf = [0; 32];
+    let brake = SetBrake {
+        centi_percent: 4000,
+    };
+    let bytes = postcard::to_slice(&CanMessage::from(brake), &mut buf).unwrap();
+    assert_eq!(bytes, [1, 0xA0, 0x1F]);
+}
@@ -0,0 +1,99 @@
+//! Conversions with socketcan's frames, and through a `vcan0` interface if there is one. Run
+//! with `cargo test --features socketcan --test socketcan`. To set up `vcan0`:
+//!
+//! ```sh
+//! sudo modprobe vcan
+//! sudo ip link add dev vcan0 type vcan
+//! sudo ip link set up vcan0
+//! ```
+
+#![cfg(all(feature = "socketcan", feature = "msgs-all"))]
+
+use embedded_hal::can as hal;
+use phnx_candefs::testing::{MockFrame, GOLDEN};
+use phnx_candefs::*;
+use socketcan::{
+    CanDataFrame, CanErrorFrame, CanFrame, CanRemoteFrame, CanSocket, EmbeddedFrame, ExtendedId,
+    Socket, StandardId,
+};
+use std::time::Duration;
+
+#[test]
+fn test_round_trip() {
+    for golden in GOLDEN {
+        let frame = CanFrame::try_from(golden.msg).unwrap();
+        assert_eq!(
+            frame.id(),
+            socketcan::Id::Extended(ExtendedId::new(golden.id).unwrap())
+        );
+        assert_eq!(frame.data(), golden.data, "{:?}", golden.msg);
+
+        let back = CanMessage::try_from(frame).unwrap();
+        assert_eq!(back.to_parts().unwrap(), golden.msg.to_parts().unwrap());
+    }
+}
+
+#[test]
+fn test_rejects() {
+    let standard = CanDataFrame::new(StandardId::new(0x7).unwrap(), &[0; 6]).unwrap();
+    assert!(matches!(
+        CanMessage::try_from(CanFrame::Data(standard)),
+        Err(ConvertErr::InvalidFrame)
+    ));
+
+    let error = CanErrorFrame::new_error(0, &[0; 8]).unwrap();
+    assert!(matches!(
+        CanMessage::try_from(CanFrame::Error(error)),
+        Err(ConvertErr::InvalidFrame)
+    ));
+
+    let unknown = CanDataFrame::new(ExtendedId::new(0x1FF).unwrap(), &[]).unwrap();
+    assert!(matches!(
+        CanMessage::try_from(unknown),
+        Err(ConvertErr::UnknownId { id: 0x1FF })
+    ));
+}
+
+#[test]
+fn test_remote_like_from_frame() {
+    // Remote frames have no data, so only empty messages decode, as with embedded-hal frames
+    for (id, dlc) in [
+        (LockBrake::ID, 0),
+        (UnlockBrake::ID, 0),
+        (AutonDisable::ID, 0),
+    ] {
+        let remote = CanRemoteFrame::new_remote(ExtendedId::new(id).unwrap(), dlc).unwrap();
+        let mock = <MockFrame as hal::Frame>::new_remote(
+            hal::Id::Extended(hal::ExtendedId::new(id).unwrap()),
+            dlc,
+        )
+        .unwrap();
+        let msg = CanMessage::try_from(CanFrame::Remote(remote)).unwrap();
+        assert_eq!(msg.kind(), CanMessage::from_frame(mock).unwrap().kind());
+    }
+
+    let remote = CanRemoteFrame::new_remote(ExtendedId::new(SetBrake::ID).unwrap(), 2).unwrap();
+    assert!(CanMessage::try_from(CanFrame::Remote(remote)).is_err());
+    let mock = <MockFrame as hal::Frame>::new_remote(
+        hal::Id::Extended(hal::ExtendedId::new(SetBrake::ID).unwrap()),
+        2,
+    )
+    .unwrap();
+    assert!(CanMessage::from_frame(mock).is_err());
+}
+
+#[test]
+fn test_vcan() {
+    let (Ok(tx), Ok(rx)) = (CanSocket::open("vcan0"), CanSocket::open("vcan0")) else {
+        eprintln!("no vcan0 interface, skipping");
+        return;
+    };
+    rx.set_read_timeout(Duration::from_secs(1)).unwrap();
+
+    for golden in GOLDEN {
+        tx.write_frame(&CanFrame::try_from(golden.msg).unwrap())
+            .unwrap();
+        let back = CanMessage::try_from(rx.read_frame().unwrap()).unwrap();
+        assert_eq!(back.to_parts().unwrap(), golden.msg.to_parts().unwrap());
+    }
+}
@@ -0,0 +1,35 @@
+//! Catches gross slowdowns in decoding, like formatting or allocation on the decode path, without
+//! criterion. The bound is far above the real cost even in a debug build, so it only fails when
+//! something is badly wrong. `benches/throughput.rs` has the real numbers.
+
+#![cfg(feature = "msgs-all")]
+
+#[path = "common/mix.rs"]
+mod mix;
+
+use phnx_candefs::*;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const FRAMES: usize = 10_000;
+
+/// About 25 us a frame. Debug builds decode a frame in well under a microsecond.
+const BOUND: Duration = Duration::from_millis(250);
+
+#[test]
+fn test_mixed_decode_time() {
+    let frames = mix::mixed_frames(FRAMES);
+
+    let start = Instant::now();
+    let decoded = frames
+        .iter()
+        .filter(|(id, data)| CanMessage::from_parts(black_box(*id), black_box(data)).is_ok())
+        .count();
+    let elapsed = start.elapsed();
+
+    assert_eq!(decoded, FRAMES);
+    assert!(
+        elapsed < BOUND,
+        "{FRAMES} frames took {elapsed:?} to decode, over {BOUND:?}"
+    );
+}
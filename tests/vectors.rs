@@ -0,0 +1,36 @@
+//! Checks the committed test vectors in `tests/fixtures/vectors.csv`, the file shared with the C
+//! firmware, against this crate, and that it is up to date with the golden vectors.
+//!
+//! After changing the golden vectors, regenerate it with
+//! `UPDATE_VECTORS=1 cargo test --all-features --test vectors`.
+
+#![cfg(feature = "msgs-all")]
+
+use phnx_candefs::testing::vectors::*;
+
+const VECTORS: &str = include_str!("fixtures/vectors.csv");
+
+#[test]
+fn test_fixture() {
+    let vectors = read_vectors(VECTORS).unwrap();
+    assert!(!vectors.is_empty());
+
+    let errors: Vec<String> = vectors.iter().filter_map(|v| v.check().err()).collect();
+    assert!(errors.is_empty(), "{errors:#?}");
+}
+
+#[test]
+fn test_fixture_up_to_date() {
+    let mut text = String::new();
+    write_vectors(&mut text, &golden_vectors()).unwrap();
+
+    if std::env::var_os("UPDATE_VECTORS").is_some() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/vectors.csv");
+        std::fs::write(path, &text).unwrap();
+    } else {
+        assert!(
+            VECTORS == text,
+            "tests/fixtures/vectors.csv is out of date, rerun with UPDATE_VECTORS=1"
+        );
+    }
+}